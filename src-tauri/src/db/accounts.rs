@@ -1,22 +1,26 @@
 use rusqlite::{params, Connection, OptionalExtension};
-use super::models::Account;
+use super::models::{Account, CreditUtilization};
 
 pub fn create(conn: &Connection, account: &Account) -> Result<i64, rusqlite::Error> {
     conn.execute(
-        "INSERT INTO accounts (name, account_number, currency) VALUES (?1, ?2, ?3)",
-        params![account.name, account.account_number, account.currency],
+        "INSERT INTO accounts (name, account_number, currency, account_type, credit_limit, color, icon) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![account.name, account.account_number, account.currency, account.account_type, account.credit_limit, account.color, account.icon],
     )?;
     Ok(conn.last_insert_rowid())
 }
 
 pub fn get_all(conn: &Connection) -> Result<Vec<Account>, rusqlite::Error> {
-    let mut stmt = conn.prepare("SELECT id, name, account_number, currency FROM accounts ORDER BY name")?;
+    let mut stmt = conn.prepare("SELECT id, name, account_number, currency, account_type, credit_limit, color, icon FROM accounts ORDER BY name")?;
     let rows = stmt.query_map([], |row| {
         Ok(Account {
             id: Some(row.get(0)?),
             name: row.get(1)?,
             account_number: row.get(2)?,
             currency: row.get(3)?,
+            account_type: row.get(4)?,
+            credit_limit: row.get(5)?,
+            color: row.get(6)?,
+            icon: row.get(7)?,
         })
     })?;
     rows.collect()
@@ -24,7 +28,7 @@ pub fn get_all(conn: &Connection) -> Result<Vec<Account>, rusqlite::Error> {
 
 pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<Account>, rusqlite::Error> {
     conn.query_row(
-        "SELECT id, name, account_number, currency FROM accounts WHERE id = ?1",
+        "SELECT id, name, account_number, currency, account_type, credit_limit, color, icon FROM accounts WHERE id = ?1",
         params![id],
         |row| {
             Ok(Account {
@@ -32,6 +36,10 @@ pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<Account>, rusqlite
                 name: row.get(1)?,
                 account_number: row.get(2)?,
                 currency: row.get(3)?,
+                account_type: row.get(4)?,
+                credit_limit: row.get(5)?,
+                color: row.get(6)?,
+                icon: row.get(7)?,
             })
         },
     )
@@ -43,11 +51,87 @@ pub fn update(conn: &Connection, account: &Account) -> Result<usize, rusqlite::E
         "Account ID is required for update".to_string(),
     ))?;
     conn.execute(
-        "UPDATE accounts SET name = ?1, account_number = ?2, currency = ?3 WHERE id = ?4",
-        params![account.name, account.account_number, account.currency, id],
+        "UPDATE accounts SET name = ?1, account_number = ?2, currency = ?3, account_type = ?4, credit_limit = ?5, color = ?6, icon = ?7 WHERE id = ?8",
+        params![account.name, account.account_number, account.currency, account.account_type, account.credit_limit, account.color, account.icon, id],
     )
 }
 
 pub fn delete(conn: &Connection, id: i64) -> Result<usize, rusqlite::Error> {
     conn.execute("DELETE FROM accounts WHERE id = ?1", params![id])
 }
+
+/// Utilization above this percentage of the credit limit is flagged as high
+const HIGH_UTILIZATION_PCT: f64 = 30.0;
+
+/// How much of a credit account's limit is in use. The balance is the sum of its transactions,
+/// so money owed is a negative balance. Fails for non-credit accounts and accounts without a limit.
+pub fn get_credit_utilization(conn: &Connection, account_id: i64) -> Result<CreditUtilization, rusqlite::Error> {
+    let account = get_by_id(conn, account_id)?
+        .ok_or_else(|| rusqlite::Error::InvalidParameterName("Account not found".to_string()))?;
+    if account.account_type != "credit" {
+        return Err(rusqlite::Error::InvalidParameterName("Account is not a credit account".to_string()));
+    }
+    let Some(credit_limit) = account.credit_limit.filter(|limit| *limit > 0) else {
+        return Err(rusqlite::Error::InvalidParameterName("Account has no credit limit".to_string()));
+    };
+
+    let current_balance: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(amount), 0) FROM transactions WHERE account_id = ?1",
+        params![account_id],
+        |row| row.get(0),
+    )?;
+    let owed = (-current_balance).max(0);
+    let utilization_pct = owed as f64 / credit_limit as f64 * 100.0;
+
+    Ok(CreditUtilization {
+        account,
+        current_balance,
+        credit_limit,
+        utilization_pct,
+        available_credit: credit_limit - owed,
+        is_high_utilization: utilization_pct > HIGH_UTILIZATION_PCT,
+    })
+}
+
+/// Utilization for every credit account that has a credit limit
+pub fn get_all_credit_utilizations(conn: &Connection) -> Result<Vec<CreditUtilization>, rusqlite::Error> {
+    get_all(conn)?
+        .into_iter()
+        .filter(|a| a.account_type == "credit" && a.credit_limit.is_some_and(|limit| limit > 0))
+        .filter_map(|a| a.id)
+        .map(|id| get_credit_utilization(conn, id))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn custom_color_and_icon_are_read_back() {
+        let conn = setup();
+        let id = create(
+            &conn,
+            &Account {
+                name: "Opsparing".to_string(),
+                color: "#f97316".to_string(),
+                icon: Some("piggy-bank".to_string()),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let account = get_by_id(&conn, id).unwrap().unwrap();
+        assert_eq!((account.color.as_str(), account.icon.as_deref()), ("#f97316", Some("piggy-bank")));
+
+        let plain = create(&conn, &Account { name: "Lønkonto".to_string(), ..Default::default() }).unwrap();
+        let plain = get_all(&conn).unwrap().into_iter().find(|a| a.id == Some(plain)).unwrap();
+        assert_eq!((plain.color.as_str(), plain.icon), ("#6366f1", None));
+    }
+}
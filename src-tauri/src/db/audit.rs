@@ -0,0 +1,134 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use super::models::AuditLogEntry;
+
+/// Number of changes returned when no transaction is given
+const RECENT_CHANGES_LIMIT: i64 = 500;
+
+/// Record that `field` of a transaction changed from `old_value` to `new_value`
+pub fn log_change(
+    conn: &Connection,
+    transaction_id: i64,
+    field: &str,
+    old_value: Option<&str>,
+    new_value: Option<&str>,
+) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT INTO transaction_audit_log (transaction_id, field_changed, old_value, new_value)
+         VALUES (?1, ?2, ?3, ?4)",
+        params![transaction_id, field, old_value, new_value],
+    )?;
+    Ok(())
+}
+
+/// Record a category change in the audit log, unless the transaction already has that category
+pub fn log_category_change(
+    conn: &Connection,
+    transaction_id: i64,
+    category_id: Option<i64>,
+) -> Result<(), rusqlite::Error> {
+    let current: Option<Option<i64>> = conn
+        .query_row(
+            "SELECT category_id FROM transactions WHERE id = ?1",
+            params![transaction_id],
+            |row| row.get(0),
+        )
+        .optional()?;
+    match current {
+        Some(old) if old != category_id => log_change(
+            conn,
+            transaction_id,
+            "category_id",
+            old.map(|id| id.to_string()).as_deref(),
+            category_id.map(|id| id.to_string()).as_deref(),
+        ),
+        _ => Ok(()),
+    }
+}
+
+/// Changes to one transaction, or the most recent changes across all transactions, newest first
+pub fn get_log(conn: &Connection, transaction_id: Option<i64>) -> Result<Vec<AuditLogEntry>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT id, transaction_id, field_changed, old_value, new_value, changed_at
+           FROM transaction_audit_log
+           WHERE ?1 IS NULL OR transaction_id = ?1
+           ORDER BY changed_at DESC, id DESC
+           LIMIT ?2"#,
+    )?;
+    let limit = if transaction_id.is_some() { -1 } else { RECENT_CHANGES_LIMIT };
+    let rows = stmt.query_map(params![transaction_id, limit], |row| {
+        Ok(AuditLogEntry {
+            id: row.get(0)?,
+            transaction_id: row.get(1)?,
+            field_changed: row.get(2)?,
+            old_value: row.get(3)?,
+            new_value: row.get(4)?,
+            changed_at: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{schema, transactions};
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        conn.execute("INSERT INTO categories (name) VALUES ('Dagligvarer')", []).unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, payee: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-03-01', ?1, -12500)",
+            params![payee],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn changes(conn: &Connection, transaction_id: i64) -> Vec<(String, Option<String>, Option<String>)> {
+        get_log(conn, Some(transaction_id))
+            .unwrap()
+            .into_iter()
+            .rev()
+            .map(|e| (e.field_changed, e.old_value, e.new_value))
+            .collect()
+    }
+
+    #[test]
+    fn update_category_logs_only_real_changes() {
+        let conn = setup();
+        let id = insert(&conn, "Netto");
+
+        transactions::update_category(&conn, id, Some(1)).unwrap();
+        transactions::update_category(&conn, id, Some(1)).unwrap();
+        transactions::update_category(&conn, id, None).unwrap();
+
+        assert_eq!(
+            changes(&conn, id),
+            [
+                ("category_id".to_string(), None, Some("1".to_string())),
+                ("category_id".to_string(), Some("1".to_string()), None),
+            ]
+        );
+    }
+
+    #[test]
+    fn rename_logs_every_renamed_transaction() {
+        let conn = setup();
+        let first = insert(&conn, "NETTO 1234");
+        let second = insert(&conn, "NETTO 1234");
+        let other = insert(&conn, "Føtex");
+
+        assert_eq!(transactions::rename_payee_across_transactions(&conn, "NETTO 1234", "Netto", None).unwrap(), 2);
+
+        let renamed = [("payee".to_string(), Some("NETTO 1234".to_string()), Some("Netto".to_string()))];
+        assert_eq!(changes(&conn, first), renamed);
+        assert_eq!(changes(&conn, second), renamed);
+        assert!(changes(&conn, other).is_empty());
+    }
+}
@@ -1,5 +1,10 @@
 use rusqlite::{params, Connection, OptionalExtension};
-use super::models::{Budget, BudgetAllocation, BudgetWithSpending};
+use super::models::{Budget, BudgetAllocation, BudgetFundingStatus, BudgetWithSpending, OverlapReport, PaceStatus};
+use super::{categories, income_streams, reports, transactions};
+use chrono::{Datelike, NaiveDate};
+
+/// Percentage points spending may deviate from the elapsed share of the month and still count as on track
+const PACE_TOLERANCE_PCT: f64 = 5.0;
 
 pub fn create(conn: &Connection, budget: &Budget) -> Result<i64, rusqlite::Error> {
     conn.execute(
@@ -57,9 +62,145 @@ pub fn get_categories(conn: &Connection, budget_id: i64) -> Result<Vec<i64>, rus
     rows.collect()
 }
 
+/// Linked categories plus their direct subcategories
+pub fn get_categories_with_children(conn: &Connection, budget_id: i64) -> Result<Vec<i64>, rusqlite::Error> {
+    let category_ids = get_categories(conn, budget_id)?;
+    let mut all_affected_ids = category_ids.clone();
+
+    // For simplicity in a small app, we'll just check one level of children
+    // If they want deeper, a recursive CTE would be better
+    let mut stmt = conn.prepare("SELECT id FROM categories WHERE parent_id = ?1")?;
+    for cat_id in &category_ids {
+        let children = stmt.query_map(params![cat_id], |row| row.get::<_, i64>(0))?;
+        for child in children {
+            all_affected_ids.push(child?);
+        }
+    }
+    Ok(all_affected_ids)
+}
+
+/// Move transactions into one of a budget's categories.
+/// Fails if the category (or its parent) is not linked to the budget.
+pub fn assign_transactions(
+    conn: &Connection,
+    budget_id: i64,
+    transaction_ids: Vec<i64>,
+    category_id: i64,
+) -> Result<usize, rusqlite::Error> {
+    if !get_categories_with_children(conn, budget_id)?.contains(&category_id) {
+        return Err(rusqlite::Error::InvalidParameterName(
+            "Category is not part of this budget".to_string(),
+        ));
+    }
+    transactions::update_batch_categories(conn, transaction_ids, Some(category_id))
+}
+
+/// Categories linked to more than one budget, which makes their spending count in each of them
+pub fn get_category_overlap(conn: &Connection) -> Result<Vec<OverlapReport>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT category_id FROM budget_categories
+           GROUP BY category_id HAVING COUNT(*) > 1
+           ORDER BY category_id"#,
+    )?;
+    let category_ids: Vec<i64> = stmt
+        .query_map([], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+
+    let mut budgets_stmt = conn.prepare(
+        r#"SELECT b.id, b.name FROM budget_categories bc
+           JOIN budgets b ON bc.budget_id = b.id
+           WHERE bc.category_id = ?1
+           ORDER BY b.name"#,
+    )?;
+    // Budgets include direct subcategories, so their transactions are double-counted too
+    let mut count_stmt = conn.prepare(
+        r#"SELECT COUNT(*) FROM transactions t
+           LEFT JOIN categories c ON t.category_id = c.id
+           WHERE t.category_id = ?1 OR c.parent_id = ?1"#,
+    )?;
+
+    let mut overlaps = Vec::new();
+    for category_id in category_ids {
+        let Some(category) = categories::get_by_id(conn, category_id)? else {
+            continue;
+        };
+        let budgets: Vec<(i64, String)> = budgets_stmt
+            .query_map(params![category_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        let transaction_count = count_stmt.query_row(params![category_id], |row| row.get(0))?;
+        let (budget_ids, budget_names) = budgets.into_iter().unzip();
+        overlaps.push(OverlapReport {
+            category,
+            budget_ids,
+            budget_names,
+            transaction_count,
+        });
+    }
+    Ok(overlaps)
+}
+
+/// Unlink a category from every budget except `keep_budget_id`. Returns the number of links removed.
+pub fn resolve_category_overlap(
+    conn: &Connection,
+    category_id: i64,
+    keep_budget_id: i64,
+) -> Result<usize, rusqlite::Error> {
+    if !get_categories(conn, keep_budget_id)?.contains(&category_id) {
+        return Err(rusqlite::Error::InvalidParameterName(
+            "Category is not part of this budget".to_string(),
+        ));
+    }
+    conn.execute(
+        "DELETE FROM budget_categories WHERE category_id = ?1 AND budget_id != ?2",
+        params![category_id, keep_budget_id],
+    )
+}
+
+// === Budget Account Links ===
+
+pub fn add_account(conn: &Connection, budget_id: i64, account_id: i64) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR IGNORE INTO budget_accounts (budget_id, account_id) VALUES (?1, ?2)",
+        params![budget_id, account_id],
+    )?;
+    Ok(())
+}
+
+pub fn remove_all_accounts(conn: &Connection, budget_id: i64) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "DELETE FROM budget_accounts WHERE budget_id = ?1",
+        params![budget_id],
+    )?;
+    Ok(())
+}
+
+/// Accounts the budget is limited to. Empty means the budget covers all accounts.
+pub fn get_accounts(conn: &Connection, budget_id: i64) -> Result<Vec<i64>, rusqlite::Error> {
+    let mut stmt = conn.prepare("SELECT account_id FROM budget_accounts WHERE budget_id = ?1")?;
+    let rows = stmt.query_map(params![budget_id], |row| row.get(0))?;
+    rows.collect()
+}
+
 // === Budget Allocations ===
 
+/// Check that `month` is a YYYY-MM month and normalize it, zero-padding the month ("2024-3" -> "2024-03")
+pub fn validate_month(month: &str) -> Result<String, rusqlite::Error> {
+    let invalid = || rusqlite::Error::InvalidParameterName(format!("Invalid month '{}', expected YYYY-MM", month));
+    let (year, month_num) = month.trim().split_once('-').ok_or_else(invalid)?;
+    let digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if year.len() != 4 || !(1..=2).contains(&month_num.len()) || !digits(year) || !digits(month_num) {
+        return Err(invalid());
+    }
+    let year: u32 = year.parse().map_err(|_| invalid())?;
+    let month_num: u32 = month_num.parse().map_err(|_| invalid())?;
+    if !(1..=12).contains(&month_num) {
+        return Err(invalid());
+    }
+    Ok(format!("{:04}-{:02}", year, month_num))
+}
+
 pub fn set_allocation(conn: &Connection, budget_id: i64, month: &str, amount: i64) -> Result<(), rusqlite::Error> {
+    let month = validate_month(month)?;
     conn.execute(
         "INSERT INTO budget_allocations (budget_id, month, allocated_amount) 
          VALUES (?1, ?2, ?3)
@@ -69,9 +210,111 @@ pub fn set_allocation(conn: &Connection, budget_id: i64, month: &str, amount: i6
     Ok(())
 }
 
+/// Change a budget's allocation for `month` by a signed `delta`, returning the new allocation.
+/// The result is clamped at zero unless `allow_negative` is set.
+pub fn adjust_allocation(
+    conn: &Connection,
+    budget_id: i64,
+    month: &str,
+    delta: i64,
+    allow_negative: bool,
+) -> Result<i64, rusqlite::Error> {
+    let month = validate_month(month)?;
+    let current = get_allocation(conn, budget_id, &month)?;
+    let mut amount = current.saturating_add(delta);
+    if !allow_negative {
+        amount = amount.max(0);
+    }
+    set_allocation(conn, budget_id, &month, amount)?;
+    Ok(amount)
+}
+
+/// Move `amount` of `month`'s allocation from one budget to another in a single transaction,
+/// e.g. "move 200 kr from Dining to Groceries". Without `allow_negative` only what the source
+/// budget has allocated is moved. Returns the amount actually moved.
+pub fn move_between_budgets(
+    conn: &Connection,
+    from_budget_id: i64,
+    to_budget_id: i64,
+    month: &str,
+    amount: i64,
+    allow_negative: bool,
+) -> Result<i64, rusqlite::Error> {
+    if amount <= 0 {
+        return Err(rusqlite::Error::InvalidParameterName("Amount to move must be positive".to_string()));
+    }
+    if from_budget_id == to_budget_id {
+        return Err(rusqlite::Error::InvalidParameterName("Cannot move money to the same budget".to_string()));
+    }
+    let month = validate_month(month)?;
+
+    let db_tx = conn.unchecked_transaction()?;
+    let before = get_allocation(&db_tx, from_budget_id, &month)?;
+    let after = adjust_allocation(&db_tx, from_budget_id, &month, -amount, allow_negative)?;
+    let moved = before - after;
+    adjust_allocation(&db_tx, to_budget_id, &month, moved, allow_negative)?;
+    db_tx.commit()?;
+    Ok(moved)
+}
+
+/// A budget's allocation for a normalized month, zero when none is set
+fn get_allocation(conn: &Connection, budget_id: i64, month: &str) -> Result<i64, rusqlite::Error> {
+    Ok(conn
+        .query_row(
+            "SELECT allocated_amount FROM budget_allocations WHERE budget_id = ?1 AND month = ?2",
+            params![budget_id, month],
+            |row| row.get(0),
+        )
+        .optional()?
+        .unwrap_or(0))
+}
+
+/// Total allocated across all budgets for `month` against the expected income from active income
+/// streams, and what is left to budget
+pub fn funding_status(conn: &Connection, month: &str) -> Result<BudgetFundingStatus, rusqlite::Error> {
+    let month = validate_month(month)?;
+    let total_allocated: i64 = conn.query_row(
+        "SELECT COALESCE(SUM(allocated_amount), 0) FROM budget_allocations WHERE month = ?1",
+        params![month],
+        |row| row.get(0),
+    )?;
+    let expected_income = income_streams::get_expected_monthly_total(conn)?;
+    Ok(BudgetFundingStatus {
+        month,
+        total_allocated,
+        expected_income,
+        unallocated: expected_income - total_allocated,
+    })
+}
+
 // === Budget with Spending ===
 
-pub fn get_budgets_with_spending(conn: &Connection, month: &str) -> Result<Vec<BudgetWithSpending>, rusqlite::Error> {
+/// Budgets with their allocation and spending for `month`.
+/// With `prorate_from_day` set, the current month's allocations are scaled to the share of the
+/// month from that day onwards, for users who start budgeting mid-month. Other months are unaffected.
+pub fn get_budgets_with_spending(
+    conn: &Connection,
+    month: &str,
+    prorate_from_day: Option<u32>,
+) -> Result<Vec<BudgetWithSpending>, rusqlite::Error> {
+    get_budgets_with_spending_at(conn, month, prorate_from_day, reports::current_date(conn)?)
+}
+
+/// Same as `get_budgets_with_spending`, with pacing and proration computed relative to `today`.
+/// Pacing is only filled in when `month` is the month containing `today`.
+pub fn get_budgets_with_spending_at(
+    conn: &Connection,
+    month: &str,
+    prorate_from_day: Option<u32>,
+    today: NaiveDate,
+) -> Result<Vec<BudgetWithSpending>, rusqlite::Error> {
+    let month = validate_month(month)?;
+    let month = month.as_str();
+    let proration = prorate_from_day.and_then(|day| remaining_month_fraction(month, day, today));
+    let percent_month_elapsed = match prorate_from_day.filter(|_| proration.is_some()) {
+        Some(day) => month_elapsed_percent_from(month, day, today),
+        None => month_elapsed_percent(month, today),
+    };
     let budgets = get_all(conn)?;
     let mut result = Vec::new();
 
@@ -82,48 +325,303 @@ pub fn get_budgets_with_spending(conn: &Connection, month: &str) -> Result<Vec<B
         let category_ids = get_categories(conn, budget_id)?;
         
         // Get allocation for this month
-        let allocated_amount: i64 = conn.query_row(
+        let full_allocation: i64 = conn.query_row(
             "SELECT allocated_amount FROM budget_allocations WHERE budget_id = ?1 AND month = ?2",
             params![budget_id, month],
             |row| row.get(0)
         ).unwrap_or(0);
+        let allocated_amount = match proration {
+            Some(fraction) => (full_allocation as f64 * fraction).round() as i64,
+            None => full_allocation,
+        };
 
         // Calculate spending
         // We include spending from the category AND any subcategories
         let mut spent_amount = 0;
         if !category_ids.is_empty() {
             // Build a set of all category IDs (including children)
-            let mut all_affected_ids = category_ids.clone();
-            
-            // For simplicity in a small app, we'll just check one level of children
-            // If they want deeper, a recursive CTE would be better
-            for cat_id in &category_ids {
-                let mut stmt = conn.prepare("SELECT id FROM categories WHERE parent_id = ?1")?;
-                let children = stmt.query_map(params![cat_id], |row| row.get::<_, i64>(0))?;
-                for child in children {
-                    all_affected_ids.push(child?);
-                }
-            }
+            let all_affected_ids = get_categories_with_children(conn, budget_id)?;
+
+            // Restrict to the budget's accounts, if any are linked
+            let account_ids = get_accounts(conn, budget_id)?;
+            let account_filter = if account_ids.is_empty() {
+                String::new()
+            } else {
+                format!(" AND account_id IN ({})", vec!["?"; account_ids.len()].join(","))
+            };
 
             // Query transactions for these categories in the given month
             // month is YYYY-MM, transactions date is YYYY-MM-DD
             let query = format!(
                 "SELECT SUM(ABS(amount)) FROM transactions 
-                 WHERE category_id IN ({}) AND date LIKE '{}%' AND amount < 0",
-                all_affected_ids.iter().map(|id| id.to_string()).collect::<Vec<_>>().join(","),
-                month
+                 WHERE category_id IN ({}) AND date LIKE ? || '%' AND amount < 0{}",
+                vec!["?"; all_affected_ids.len()].join(","),
+                account_filter
             );
-            
-            spent_amount = conn.query_row(&query, [], |row| row.get::<_, Option<i64>>(0))?.unwrap_or(0);
+            let mut args: Vec<&dyn rusqlite::ToSql> = all_affected_ids.iter().map(|id| id as &dyn rusqlite::ToSql).collect();
+            args.push(&month);
+            args.extend(account_ids.iter().map(|id| id as &dyn rusqlite::ToSql));
+
+            spent_amount = conn.query_row(&query, args.as_slice(), |row| row.get::<_, Option<i64>>(0))?.unwrap_or(0);
         }
 
+        let percent_spent = if allocated_amount > 0 {
+            spent_amount as f64 / allocated_amount as f64 * 100.0
+        } else {
+            0.0
+        };
+        let pace_status = percent_month_elapsed
+            .filter(|_| allocated_amount > 0)
+            .map(|elapsed| pace_status(percent_spent, elapsed));
+
         result.push(BudgetWithSpending {
             budget,
             category_ids,
             allocated_amount,
             spent_amount,
+            percent_spent,
+            percent_month_elapsed,
+            pace_status,
+            is_prorated: proration.is_some(),
         });
     }
 
     Ok(result)
 }
+
+/// Share of `month` (YYYY-MM) that has passed as of `today`, or None if it isn't the current month
+fn month_elapsed_percent(month: &str, today: NaiveDate) -> Option<f64> {
+    if today.format("%Y-%m").to_string() != month {
+        return None;
+    }
+    let days_in_month = days_in_month(today)?;
+    Some(today.day() as f64 / days_in_month as f64 * 100.0)
+}
+
+/// Share of the current month from `start_day` to month-end, or None if `month` isn't the current month
+fn remaining_month_fraction(month: &str, start_day: u32, today: NaiveDate) -> Option<f64> {
+    if today.format("%Y-%m").to_string() != month {
+        return None;
+    }
+    let days_in_month = days_in_month(today)?;
+    let start_day = start_day.clamp(1, days_in_month);
+    Some((days_in_month - start_day + 1) as f64 / days_in_month as f64)
+}
+
+/// Share of the days from `start_day` to month-end that have passed as of `today`
+fn month_elapsed_percent_from(month: &str, start_day: u32, today: NaiveDate) -> Option<f64> {
+    if today.format("%Y-%m").to_string() != month {
+        return None;
+    }
+    let days_in_month = days_in_month(today)?;
+    let start_day = start_day.clamp(1, days_in_month);
+    let elapsed = (today.day() + 1).saturating_sub(start_day);
+    Some(elapsed as f64 / (days_in_month - start_day + 1) as f64 * 100.0)
+}
+
+fn days_in_month(date: NaiveDate) -> Option<u32> {
+    let next_month = if date.month() == 12 {
+        NaiveDate::from_ymd_opt(date.year() + 1, 1, 1)?
+    } else {
+        NaiveDate::from_ymd_opt(date.year(), date.month() + 1, 1)?
+    };
+    Some(next_month.pred_opt()?.day())
+}
+
+fn pace_status(percent_spent: f64, percent_month_elapsed: f64) -> PaceStatus {
+    let diff = percent_spent - percent_month_elapsed;
+    if diff > PACE_TOLERANCE_PCT {
+        PaceStatus::Ahead
+    } else if diff < -PACE_TOLERANCE_PCT {
+        PaceStatus::Behind
+    } else {
+        PaceStatus::OnTrack
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        conn
+    }
+
+    fn budget(conn: &Connection, name: &str) -> i64 {
+        create(conn, &Budget { id: None, name: name.to_string(), color: "#22c55e".to_string(), icon: None }).unwrap()
+    }
+
+    fn category(conn: &Connection, name: &str, parent_id: Option<i64>) -> i64 {
+        categories::find_or_create(conn, name, parent_id).unwrap()
+    }
+
+    fn insert(conn: &Connection, date: &str, amount: i64, category_id: Option<i64>) -> i64 {
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, ?1, 'Netto', ?2, ?3)",
+            params![date, amount, category_id],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn category_of(conn: &Connection, transaction_id: i64) -> Option<i64> {
+        conn.query_row("SELECT category_id FROM transactions WHERE id = ?1", [transaction_id], |row| row.get(0))
+            .unwrap()
+    }
+
+    #[test]
+    fn assign_transactions_only_to_the_budgets_categories() {
+        let conn = setup();
+        let food = budget(&conn, "Mad");
+        let groceries = category(&conn, "Dagligvarer", None);
+        let bakery = category(&conn, "Bager", Some(groceries));
+        let fun = category(&conn, "Fornøjelser", None);
+        add_category(&conn, food, groceries).unwrap();
+        let first = insert(&conn, "2024-03-01", -4500, None);
+        let second = insert(&conn, "2024-03-02", -3200, Some(fun));
+
+        assert_eq!(assign_transactions(&conn, food, vec![first, second], bakery).unwrap(), 2);
+        assert_eq!((category_of(&conn, first), category_of(&conn, second)), (Some(bakery), Some(bakery)));
+
+        assert!(assign_transactions(&conn, food, vec![first], fun).is_err());
+        assert_eq!(category_of(&conn, first), Some(bakery));
+    }
+
+    #[test]
+    fn pacing_is_computed_for_the_current_month_only() {
+        let conn = setup();
+        let food = budget(&conn, "Mad");
+        let groceries = category(&conn, "Dagligvarer", None);
+        add_category(&conn, food, groceries).unwrap();
+        set_allocation(&conn, food, "2024-04", 300000).unwrap();
+        set_allocation(&conn, food, "2024-03", 300000).unwrap();
+        // 15 of 30 days have passed, but 80% of the allocation is spent
+        insert(&conn, "2024-04-02", -240000, Some(groceries));
+        let mid_april = NaiveDate::from_ymd_opt(2024, 4, 15).unwrap();
+
+        let april = get_budgets_with_spending_at(&conn, "2024-04", None, mid_april).unwrap();
+        assert_eq!((april[0].percent_spent, april[0].percent_month_elapsed), (80.0, Some(50.0)));
+        assert_eq!(april[0].pace_status, Some(PaceStatus::Ahead));
+
+        let march = get_budgets_with_spending_at(&conn, "2024-03", None, mid_april).unwrap();
+        assert_eq!((march[0].percent_month_elapsed, march[0].pace_status), (None, None));
+    }
+
+    #[test]
+    fn pace_status_allows_a_few_points_either_way() {
+        assert_eq!(pace_status(52.0, 50.0), PaceStatus::OnTrack);
+        assert_eq!(pace_status(56.0, 50.0), PaceStatus::Ahead);
+        assert_eq!(pace_status(40.0, 50.0), PaceStatus::Behind);
+    }
+
+    #[test]
+    fn current_month_allocation_is_prorated_from_the_start_day() {
+        let conn = setup();
+        let food = budget(&conn, "Mad");
+        set_allocation(&conn, food, "2024-04", 300000).unwrap();
+        set_allocation(&conn, food, "2024-03", 300000).unwrap();
+        let april_20 = NaiveDate::from_ymd_opt(2024, 4, 20).unwrap();
+
+        // Starting on the 16th leaves 15 of April's 30 days
+        let prorated = get_budgets_with_spending_at(&conn, "2024-04", Some(16), april_20).unwrap();
+        assert_eq!((prorated[0].allocated_amount, prorated[0].is_prorated), (150000, true));
+        assert_eq!(prorated[0].percent_month_elapsed.map(|p| p.round()), Some(33.0));
+
+        let full = get_budgets_with_spending_at(&conn, "2024-04", None, april_20).unwrap();
+        assert_eq!((full[0].allocated_amount, full[0].is_prorated), (300000, false));
+
+        let march = get_budgets_with_spending_at(&conn, "2024-03", Some(16), april_20).unwrap();
+        assert_eq!((march[0].allocated_amount, march[0].is_prorated), (300000, false));
+    }
+
+    #[test]
+    fn months_are_validated_and_zero_padded() {
+        assert_eq!(validate_month("2024-03").unwrap(), "2024-03");
+        assert_eq!(validate_month("2024-3").unwrap(), "2024-03");
+        for invalid in ["2024-13", "2024-0", "2024-003", "24-03", "2024/03", "march"] {
+            assert!(validate_month(invalid).is_err(), "{}", invalid);
+        }
+
+        let conn = setup();
+        let food = budget(&conn, "Mad");
+        set_allocation(&conn, food, "2024-3", 250000).unwrap();
+        assert_eq!(get_allocation(&conn, food, "2024-03").unwrap(), 250000);
+        assert!(set_allocation(&conn, food, "2024-13", 250000).is_err());
+    }
+
+    #[test]
+    fn funding_status_reports_what_is_left_to_budget() {
+        let conn = setup();
+        conn.execute_batch(
+            r#"INSERT INTO income_streams (name, expected_amount, frequency) VALUES ('Løn', 3000000, 'monthly');
+               INSERT INTO income_streams (name, expected_amount, frequency, is_active) VALUES ('Gammelt job', 2000000, 'monthly', 0);"#,
+        )
+        .unwrap();
+        let rent = budget(&conn, "Husleje");
+        let food = budget(&conn, "Mad");
+        set_allocation(&conn, rent, "2024-04", 1500000).unwrap();
+        set_allocation(&conn, food, "2024-04", 1260000).unwrap();
+        set_allocation(&conn, food, "2024-05", 500000).unwrap();
+
+        let status = funding_status(&conn, "2024-4").unwrap();
+        assert_eq!(status.month, "2024-04");
+        assert_eq!((status.total_allocated, status.expected_income, status.unallocated), (2760000, 3000000, 240000));
+    }
+
+    #[test]
+    fn moving_between_budgets_nets_out() {
+        let conn = setup();
+        let dining = budget(&conn, "Restaurant");
+        let groceries = budget(&conn, "Dagligvarer");
+        set_allocation(&conn, dining, "2024-04", 50000).unwrap();
+        set_allocation(&conn, groceries, "2024-04", 300000).unwrap();
+        let allocations = |conn: &Connection| {
+            (get_allocation(conn, dining, "2024-04").unwrap(), get_allocation(conn, groceries, "2024-04").unwrap())
+        };
+
+        assert_eq!(move_between_budgets(&conn, dining, groceries, "2024-04", 20000, false).unwrap(), 20000);
+        assert_eq!(allocations(&conn), (30000, 320000));
+
+        // Only what the source has is moved when it can't go negative
+        assert_eq!(move_between_budgets(&conn, dining, groceries, "2024-04", 50000, false).unwrap(), 30000);
+        assert_eq!(allocations(&conn), (0, 350000));
+
+        assert_eq!(move_between_budgets(&conn, dining, groceries, "2024-4", 10000, true).unwrap(), 10000);
+        assert_eq!(allocations(&conn), (-10000, 360000));
+
+        assert!(move_between_budgets(&conn, dining, dining, "2024-04", 10000, true).is_err());
+        assert!(move_between_budgets(&conn, dining, groceries, "2024-04", 0, true).is_err());
+        assert_eq!(allocations(&conn), (-10000, 360000));
+    }
+
+    #[test]
+    fn linked_accounts_limit_the_spending_counted() {
+        let conn = setup();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Budgetkonto')", []).unwrap();
+        let food = budget(&conn, "Mad");
+        let groceries = category(&conn, "Dagligvarer", None);
+        add_category(&conn, food, groceries).unwrap();
+        set_allocation(&conn, food, "2024-04", 300000).unwrap();
+        insert(&conn, "2024-04-02", -40000, Some(groceries));
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (2, '2024-04-03', 'Irma', -25000, ?1)",
+            params![groceries],
+        )
+        .unwrap();
+        let spent = |conn: &Connection| {
+            get_budgets_with_spending_at(conn, "2024-04", None, NaiveDate::from_ymd_opt(2024, 4, 15).unwrap()).unwrap()[0]
+                .spent_amount
+        };
+
+        assert_eq!(spent(&conn), 65000);
+        add_account(&conn, food, 2).unwrap();
+        assert_eq!(get_accounts(&conn, food).unwrap(), [2]);
+        assert_eq!(spent(&conn), 25000);
+        remove_all_accounts(&conn, food).unwrap();
+        assert_eq!(spent(&conn), 65000);
+    }
+}
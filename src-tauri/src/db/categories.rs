@@ -1,23 +1,29 @@
 use rusqlite::{params, Connection, OptionalExtension};
-use super::models::Category;
+use super::{audit, categorization_rules};
+use super::models::{Category, CategorySnapshot, CategoryWithDepth, DuplicateCategoryGroup};
 
 pub fn create(conn: &Connection, category: &Category) -> Result<i64, rusqlite::Error> {
     conn.execute(
-        "INSERT INTO categories (name, parent_id) VALUES (?1, ?2)",
-        params![category.name, category.parent_id],
+        "INSERT INTO categories (name, parent_id, spending_type, is_hidden) VALUES (?1, ?2, ?3, ?4)",
+        params![category.name, category.parent_id, category.spending_type, category.is_hidden],
     )?;
     Ok(conn.last_insert_rowid())
 }
 
-pub fn get_all(conn: &Connection) -> Result<Vec<Category>, rusqlite::Error> {
+/// All categories; hidden ones are only included when `include_hidden` is set
+pub fn get_all(conn: &Connection, include_hidden: bool) -> Result<Vec<Category>, rusqlite::Error> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, parent_id FROM categories ORDER BY parent_id NULLS FIRST, name",
+        "SELECT id, name, parent_id, spending_type, is_hidden FROM categories
+         WHERE ?1 OR is_hidden = 0
+         ORDER BY parent_id NULLS FIRST, name",
     )?;
-    let rows = stmt.query_map([], |row| {
+    let rows = stmt.query_map(params![include_hidden], |row| {
         Ok(Category {
             id: Some(row.get(0)?),
             name: row.get(1)?,
             parent_id: row.get(2)?,
+            spending_type: row.get(3)?,
+            is_hidden: row.get(4)?,
         })
     })?;
     rows.collect()
@@ -25,13 +31,15 @@ pub fn get_all(conn: &Connection) -> Result<Vec<Category>, rusqlite::Error> {
 
 pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<Category>, rusqlite::Error> {
     conn.query_row(
-        "SELECT id, name, parent_id FROM categories WHERE id = ?1",
+        "SELECT id, name, parent_id, spending_type, is_hidden FROM categories WHERE id = ?1",
         params![id],
         |row| {
             Ok(Category {
                 id: Some(row.get(0)?),
                 name: row.get(1)?,
                 parent_id: row.get(2)?,
+                spending_type: row.get(3)?,
+                is_hidden: row.get(4)?,
             })
         },
     )
@@ -66,34 +74,391 @@ pub fn find_or_create(
 }
 
 /// Get all top-level categories (those without a parent)
-pub fn get_top_level(conn: &Connection) -> Result<Vec<Category>, rusqlite::Error> {
-    let mut stmt =
-        conn.prepare("SELECT id, name, parent_id FROM categories WHERE parent_id IS NULL ORDER BY name")?;
-    let rows = stmt.query_map([], |row| {
+pub fn get_top_level(conn: &Connection, include_hidden: bool) -> Result<Vec<Category>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, parent_id, spending_type, is_hidden FROM categories
+         WHERE parent_id IS NULL AND (?1 OR is_hidden = 0) ORDER BY name",
+    )?;
+    let rows = stmt.query_map(params![include_hidden], |row| {
         Ok(Category {
             id: Some(row.get(0)?),
             name: row.get(1)?,
             parent_id: None,
+            spending_type: row.get(3)?,
+            is_hidden: row.get(4)?,
         })
     })?;
     rows.collect()
 }
 
 /// Get subcategories for a given parent category
-pub fn get_children(conn: &Connection, parent_id: i64) -> Result<Vec<Category>, rusqlite::Error> {
+pub fn get_children(
+    conn: &Connection,
+    parent_id: i64,
+    include_hidden: bool,
+) -> Result<Vec<Category>, rusqlite::Error> {
     let mut stmt = conn.prepare(
-        "SELECT id, name, parent_id FROM categories WHERE parent_id = ?1 ORDER BY name",
+        "SELECT id, name, parent_id, spending_type, is_hidden FROM categories
+         WHERE parent_id = ?1 AND (?2 OR is_hidden = 0) ORDER BY name",
     )?;
-    let rows = stmt.query_map(params![parent_id], |row| {
+    let rows = stmt.query_map(params![parent_id, include_hidden], |row| {
         Ok(Category {
             id: Some(row.get(0)?),
             name: row.get(1)?,
             parent_id: Some(row.get(2)?),
+            spending_type: row.get(3)?,
+            is_hidden: row.get(4)?,
         })
     })?;
     rows.collect()
 }
 
-pub fn delete(conn: &Connection, id: i64) -> Result<usize, rusqlite::Error> {
-    conn.execute("DELETE FROM categories WHERE id = ?1", params![id])
+/// Update name, parent and spending type; visibility is only changed through `set_hidden`
+pub fn update(conn: &Connection, category: &Category) -> Result<usize, rusqlite::Error> {
+    let id = category.id.ok_or(rusqlite::Error::InvalidParameterName(
+        "Category ID is required for update".to_string(),
+    ))?;
+    if let Some(parent_id) = category.parent_id {
+        if is_descendant_or_self(conn, parent_id, id)? {
+            return Err(rusqlite::Error::InvalidParameterName(
+                "A category cannot be moved under itself or one of its subcategories".to_string(),
+            ));
+        }
+    }
+    conn.execute(
+        "UPDATE categories SET name = ?1, parent_id = ?2, spending_type = ?3 WHERE id = ?4",
+        params![category.name, category.parent_id, category.spending_type, id],
+    )
+}
+
+/// Whether `id` is `ancestor_id` or somewhere below it, following parent links upwards from `id`
+fn is_descendant_or_self(conn: &Connection, id: i64, ancestor_id: i64) -> Result<bool, rusqlite::Error> {
+    conn.query_row(
+        r#"WITH RECURSIVE ancestors(id) AS (
+               SELECT ?1
+               UNION
+               SELECT c.parent_id FROM categories c JOIN ancestors a ON c.id = a.id
+               WHERE c.parent_id IS NOT NULL
+           )
+           SELECT EXISTS(SELECT 1 FROM ancestors WHERE id = ?2)"#,
+        params![id, ancestor_id],
+        |row| row.get(0),
+    )
+}
+
+/// Hide a category from pickers (or show it again). Its transactions keep the category and still count in reports.
+pub fn set_hidden(conn: &Connection, id: i64, hidden: bool) -> Result<usize, rusqlite::Error> {
+    let updated = conn.execute("UPDATE categories SET is_hidden = ?1 WHERE id = ?2", params![hidden, id])?;
+    if updated == 0 {
+        return Err(rusqlite::Error::InvalidParameterName("Category not found".to_string()));
+    }
+    Ok(updated)
+}
+
+/// Find categories with identical names at the same level (same parent), e.g. after bulk imports.
+/// Each group lists the duplicate IDs with their transaction counts, to help pick the one to keep.
+pub fn find_duplicates(conn: &Connection) -> Result<Vec<DuplicateCategoryGroup>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT name, parent_id, COUNT(*) as count FROM categories
+         GROUP BY name, parent_id HAVING count > 1
+         ORDER BY name",
+    )?;
+    let groups: Vec<(String, Option<i64>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut members = conn.prepare(
+        "SELECT c.id, COUNT(t.id) FROM categories c
+         LEFT JOIN transactions t ON t.category_id = c.id
+         WHERE c.name = ?1 AND c.parent_id IS ?2
+         GROUP BY c.id ORDER BY c.id",
+    )?;
+    groups
+        .into_iter()
+        .map(|(name, parent_id)| {
+            let rows: Vec<(i64, i64)> = members
+                .query_map(params![name, parent_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+                .collect::<Result<Vec<_>, _>>()?;
+            Ok(DuplicateCategoryGroup {
+                name,
+                parent_id,
+                category_ids: rows.iter().map(|(id, _)| *id).collect(),
+                transaction_counts: rows.iter().map(|(_, count)| *count).collect(),
+            })
+        })
+        .collect()
+}
+
+/// Every category with its depth (0 = top-level), ordered depth-first with siblings by name,
+/// so each category directly follows its parent. Categories in a parent cycle are never reached.
+pub fn get_hierarchy_report(conn: &Connection) -> Result<Vec<CategoryWithDepth>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"WITH RECURSIVE cat_depth(id, name, parent_id, spending_type, is_hidden, depth, path) AS (
+               SELECT id, name, parent_id, spending_type, is_hidden, 0, LOWER(name) || char(2) || id
+               FROM categories WHERE parent_id IS NULL
+               UNION ALL
+               SELECT c.id, c.name, c.parent_id, c.spending_type, c.is_hidden, d.depth + 1,
+                      d.path || char(1) || LOWER(c.name) || char(2) || c.id
+               FROM categories c JOIN cat_depth d ON c.parent_id = d.id
+           )
+           SELECT id, name, parent_id, spending_type, is_hidden, depth FROM cat_depth ORDER BY path"#,
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(CategoryWithDepth {
+            category: Category {
+                id: Some(row.get(0)?),
+                name: row.get(1)?,
+                parent_id: row.get(2)?,
+                spending_type: row.get(3)?,
+                is_hidden: row.get(4)?,
+            },
+            depth: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Delete a category and (through the cascade) its subcategories. Uncategorizing its transactions is audited.
+/// Returns a serialized snapshot that `restore` can use to undo the deletion.
+pub fn delete(conn: &Connection, id: i64) -> Result<String, rusqlite::Error> {
+    let snapshot = snapshot(conn, id)?;
+    let db_tx = conn.unchecked_transaction()?;
+    for (transaction_id, _) in &snapshot.transactions {
+        audit::log_category_change(&db_tx, *transaction_id, None)?;
+    }
+    db_tx.execute("DELETE FROM categories WHERE id = ?1", params![id])?;
+    db_tx.commit()?;
+    serde_json::to_string(&snapshot).map_err(|e| rusqlite::Error::ToSqlConversionFailure(Box::new(e)))
+}
+
+/// Undo a `delete`: recreate the categories with their original IDs, re-link budgets and rules, and
+/// move transactions back unless they have been categorized since. Returns the number of transactions restored.
+pub fn restore(conn: &Connection, snapshot: &str) -> Result<usize, rusqlite::Error> {
+    let snapshot: CategorySnapshot = serde_json::from_str(snapshot)
+        .map_err(|_| rusqlite::Error::InvalidParameterName("Invalid category snapshot".to_string()))?;
+
+    let db_tx = conn.unchecked_transaction()?;
+    for category in &snapshot.categories {
+        db_tx.execute(
+            "INSERT INTO categories (id, name, parent_id, spending_type, is_hidden) VALUES (?1, ?2, ?3, ?4, ?5)",
+            params![category.id, category.name, category.parent_id, category.spending_type, category.is_hidden],
+        )?;
+    }
+    for (budget_id, category_id) in &snapshot.budget_links {
+        // The budget may have been deleted in the meantime
+        db_tx.execute(
+            r#"INSERT OR IGNORE INTO budget_categories (budget_id, category_id)
+               SELECT id, ?2 FROM budgets WHERE id = ?1"#,
+            params![budget_id, category_id],
+        )?;
+    }
+    for rule in &snapshot.rules {
+        db_tx.execute(
+            r#"INSERT OR IGNORE INTO categorization_rules (id, pattern, category_id, is_regex, priority, metadata)
+               VALUES (?1, ?2, ?3, ?4, ?5, ?6)"#,
+            params![rule.id, rule.pattern, rule.category_id, rule.is_regex as i64, rule.priority, rule.metadata],
+        )?;
+    }
+    let mut restored = 0;
+    for (transaction_id, category_id) in &snapshot.transactions {
+        let updated = db_tx.execute(
+            "UPDATE transactions SET category_id = ?1 WHERE id = ?2 AND category_id IS NULL",
+            params![category_id, transaction_id],
+        )?;
+        if updated > 0 {
+            audit::log_change(&db_tx, *transaction_id, "category_id", None, Some(&category_id.to_string()))?;
+        }
+        restored += updated;
+    }
+    db_tx.commit()?;
+    Ok(restored)
+}
+
+/// Everything the deletion of a category removes or unlinks, parents listed before children
+fn snapshot(conn: &Connection, id: i64) -> Result<CategorySnapshot, rusqlite::Error> {
+    if get_by_id(conn, id)?.is_none() {
+        return Err(rusqlite::Error::InvalidParameterName("Category not found".to_string()));
+    }
+
+    let mut stmt = conn.prepare(
+        r#"WITH RECURSIVE subtree(id, depth) AS (
+               SELECT id, 0 FROM categories WHERE id = ?1
+               UNION
+               SELECT c.id, s.depth + 1 FROM categories c JOIN subtree s ON c.parent_id = s.id
+           )
+           SELECT c.id, c.name, c.parent_id, c.spending_type, c.is_hidden
+           FROM subtree s JOIN categories c ON c.id = s.id
+           ORDER BY s.depth, c.id"#,
+    )?;
+    let categories: Vec<Category> = stmt
+        .query_map(params![id], |row| {
+            Ok(Category {
+                id: Some(row.get(0)?),
+                name: row.get(1)?,
+                parent_id: row.get(2)?,
+                spending_type: row.get(3)?,
+                is_hidden: row.get(4)?,
+            })
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let ids = categories
+        .iter()
+        .filter_map(|c| c.id)
+        .map(|id| id.to_string())
+        .collect::<Vec<_>>()
+        .join(",");
+    let pairs = |sql: &str| -> Result<Vec<(i64, i64)>, rusqlite::Error> {
+        let mut stmt = conn.prepare(&sql.replace("{ids}", &ids))?;
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+        rows.collect()
+    };
+
+    Ok(CategorySnapshot {
+        transactions: pairs("SELECT id, category_id FROM transactions WHERE category_id IN ({ids})")?,
+        budget_links: pairs("SELECT budget_id, category_id FROM budget_categories WHERE category_id IN ({ids})")?,
+        rules: categorization_rules::get_all(conn)?
+            .into_iter()
+            .filter(|r| categories.iter().any(|c| c.id == Some(r.category_id)))
+            .collect(),
+        categories,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{schema, transactions};
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn
+    }
+
+    fn category(name: &str, parent_id: Option<i64>) -> Category {
+        Category {
+            id: None,
+            name: name.to_string(),
+            parent_id,
+            spending_type: "discretionary".to_string(),
+            is_hidden: false,
+        }
+    }
+
+    #[test]
+    fn update_rejects_parent_cycles() {
+        let conn = setup();
+        let food = create(&conn, &category("Mad", None)).unwrap();
+        let groceries = create(&conn, &category("Dagligvarer", Some(food))).unwrap();
+        let netto = create(&conn, &category("Netto", Some(groceries))).unwrap();
+
+        let mut moved = category("Mad", Some(netto));
+        moved.id = Some(food);
+        assert!(update(&conn, &moved).is_err());
+        moved.parent_id = Some(food);
+        assert!(update(&conn, &moved).is_err());
+
+        let mut moved = category("Netto", Some(food));
+        moved.id = Some(netto);
+        assert_eq!(update(&conn, &moved).unwrap(), 1);
+    }
+
+    #[test]
+    fn delete_and_restore_are_audited() {
+        let conn = setup();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        let food = create(&conn, &category("Mad", None)).unwrap();
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, '2024-03-01', 'Netto', -12500, ?1)",
+            params![food],
+        )
+        .unwrap();
+        let tx_id = conn.last_insert_rowid();
+
+        let snapshot = delete(&conn, food).unwrap();
+        assert_eq!(restore(&conn, &snapshot).unwrap(), 1);
+
+        let log: Vec<(Option<String>, Option<String>)> = audit::get_log(&conn, Some(tx_id))
+            .unwrap()
+            .into_iter()
+            .rev()
+            .map(|e| (e.old_value, e.new_value))
+            .collect();
+        assert_eq!(log, [(Some(food.to_string()), None), (None, Some(food.to_string()))]);
+    }
+
+    #[test]
+    fn restore_brings_back_subcategories_and_transactions() {
+        let conn = setup();
+        conn.execute_batch(
+            r#"INSERT INTO accounts (name) VALUES ('Lønkonto');
+               INSERT INTO budgets (name) VALUES ('Husholdning');"#,
+        )
+        .unwrap();
+        let food = create(&conn, &category("Mad", None)).unwrap();
+        let groceries = create(&conn, &category("Dagligvarer", Some(food))).unwrap();
+        let other = create(&conn, &category("Transport", None)).unwrap();
+        for category_id in [food, groceries, other] {
+            conn.execute(
+                "INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, '2024-03-01', 'Test', -100, ?1)",
+                params![category_id],
+            )
+            .unwrap();
+        }
+        conn.execute("INSERT INTO budget_categories (budget_id, category_id) VALUES (1, ?1)", params![groceries]).unwrap();
+        conn.execute(
+            "INSERT INTO categorization_rules (pattern, category_id) VALUES ('Netto', ?1)",
+            params![groceries],
+        )
+        .unwrap();
+        let categories_of = |conn: &Connection| -> Vec<Option<i64>> {
+            let mut stmt = conn.prepare("SELECT category_id FROM transactions ORDER BY id").unwrap();
+            stmt.query_map([], |row| row.get(0)).unwrap().collect::<Result<_, _>>().unwrap()
+        };
+
+        let snapshot = delete(&conn, food).unwrap();
+        assert!(get_by_id(&conn, groceries).unwrap().is_none());
+        assert_eq!(categories_of(&conn), [None, None, Some(other)]);
+
+        assert_eq!(restore(&conn, &snapshot).unwrap(), 2);
+        assert_eq!(get_by_id(&conn, groceries).unwrap().unwrap().parent_id, Some(food));
+        assert_eq!(categories_of(&conn), [Some(food), Some(groceries), Some(other)]);
+        let counts: (i64, i64) = conn
+            .query_row(
+                "SELECT (SELECT COUNT(*) FROM budget_categories), (SELECT COUNT(*) FROM categorization_rules)",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .unwrap();
+        assert_eq!(counts, (1, 1));
+        assert!(restore(&conn, "not a snapshot").is_err());
+    }
+
+    #[test]
+    fn hidden_categories_leave_pickers_but_still_report() {
+        let conn = setup();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        let food = create(&conn, &category("Mad", None)).unwrap();
+        let old = create(&conn, &category("Gammel", None)).unwrap();
+        let old_child = create(&conn, &category("Gammel under", Some(food))).unwrap();
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, '2024-03-01', 'Netto', -12500, ?1)",
+            params![old],
+        )
+        .unwrap();
+        set_hidden(&conn, old, true).unwrap();
+        set_hidden(&conn, old_child, true).unwrap();
+
+        let names = |list: Vec<Category>| list.into_iter().map(|c| c.name).collect::<Vec<_>>();
+        assert_eq!(names(get_all(&conn, false).unwrap()), ["Mad"]);
+        assert_eq!(names(get_top_level(&conn, false).unwrap()), ["Mad"]);
+        assert!(get_children(&conn, food, false).unwrap().is_empty());
+        assert_eq!(get_all(&conn, true).unwrap().len(), 3);
+        assert_eq!(names(get_children(&conn, food, true).unwrap()), ["Gammel under"]);
+
+        let spending = transactions::get_spending_by_category(&conn, 1, "2024-03-01", "2024-03-31").unwrap();
+        assert_eq!(spending, [("Gammel".to_string(), -12500)]);
+        assert!(set_hidden(&conn, 999, true).is_err());
+    }
 }
@@ -0,0 +1,312 @@
+use regex::Regex;
+use rusqlite::{params, Connection};
+use super::models::{ApplyRulesResult, CategorizationRule, MigrationResult};
+use super::transactions;
+use std::collections::{HashMap, HashSet};
+
+/// Share of a payee's transactions that must be in one category before a rule is generated for it
+const CONSISTENCY_THRESHOLD: f64 = 0.8;
+
+/// Plain-text rules below this priority (including the default 0) are treated as guesses,
+/// so the transactions they categorize are flagged for review. Regex rules always are.
+const TRUSTED_PRIORITY: i64 = 1;
+
+pub fn create(conn: &Connection, rule: &CategorizationRule) -> Result<i64, rusqlite::Error> {
+    if rule.is_regex {
+        compile(&rule.pattern)?;
+    }
+    conn.execute(
+        "INSERT INTO categorization_rules (pattern, category_id, is_regex, priority, metadata)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![rule.pattern, rule.category_id, rule.is_regex as i64, rule.priority, rule.metadata],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+/// Get all rules, highest priority first
+pub fn get_all(conn: &Connection) -> Result<Vec<CategorizationRule>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, pattern, category_id, is_regex, priority, metadata
+         FROM categorization_rules ORDER BY priority DESC, id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(CategorizationRule {
+            id: Some(row.get(0)?),
+            pattern: row.get(1)?,
+            category_id: row.get(2)?,
+            is_regex: row.get::<_, i64>(3)? != 0,
+            priority: row.get(4)?,
+            metadata: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn update(conn: &Connection, rule: &CategorizationRule) -> Result<usize, rusqlite::Error> {
+    let id = rule.id.ok_or(rusqlite::Error::InvalidParameterName(
+        "Rule ID is required for update".to_string(),
+    ))?;
+    if rule.is_regex {
+        compile(&rule.pattern)?;
+    }
+    conn.execute(
+        "UPDATE categorization_rules SET pattern = ?1, category_id = ?2, is_regex = ?3, priority = ?4, metadata = ?5
+         WHERE id = ?6",
+        params![rule.pattern, rule.category_id, rule.is_regex as i64, rule.priority, rule.metadata, id],
+    )
+}
+
+pub fn delete(conn: &Connection, id: i64) -> Result<usize, rusqlite::Error> {
+    conn.execute("DELETE FROM categorization_rules WHERE id = ?1", params![id])
+}
+
+/// Load all rules in priority order with their regexes compiled, ready for `find_match`.
+/// A stored regex that no longer compiles never matches.
+pub fn load_compiled(conn: &Connection) -> Result<Vec<(CategorizationRule, Option<Regex>)>, rusqlite::Error> {
+    Ok(get_all(conn)?
+        .into_iter()
+        .map(|rule| {
+            let regex = if rule.is_regex { compile(&rule.pattern).ok() } else { None };
+            (rule, regex)
+        })
+        .collect())
+}
+
+/// Check whether a rule matches a payee.
+/// Plain patterns must equal the whole payee, ignoring case and surrounding whitespace;
+/// regex patterns are used as-is, so partial matches need a regex.
+pub fn matches(rule: &CategorizationRule, payee: &str) -> bool {
+    let regex = if rule.is_regex { compile(&rule.pattern).ok() } else { None };
+    matches_compiled(rule, regex.as_ref(), payee)
+}
+
+fn matches_compiled(rule: &CategorizationRule, regex: Option<&Regex>, payee: &str) -> bool {
+    if rule.is_regex {
+        regex.is_some_and(|re| re.is_match(payee))
+    } else {
+        payee.trim().to_lowercase() == rule.pattern.trim().to_lowercase()
+    }
+}
+
+/// The highest-priority rule matching a payee, from the rules returned by `load_compiled`
+pub fn find_match<'a>(rules: &'a [(CategorizationRule, Option<Regex>)], payee: &str) -> Option<&'a CategorizationRule> {
+    rules
+        .iter()
+        .find(|(rule, regex)| matches_compiled(rule, regex.as_ref(), payee))
+        .map(|(rule, _)| rule)
+}
+
+/// Whether a category set by this rule should be confirmed by the user
+pub fn needs_review(rule: &CategorizationRule) -> bool {
+    rule.is_regex || rule.priority < TRUSTED_PRIORITY
+}
+
+/// Run the rules over an account's transactions (all accounts when `account_id` is None).
+/// Only uncategorized transactions are considered unless `overwrite_existing` is set.
+pub fn apply_to_account(
+    conn: &Connection,
+    account_id: Option<i64>,
+    overwrite_existing: bool,
+) -> Result<ApplyRulesResult, rusqlite::Error> {
+    let rules = load_compiled(conn)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT id, payee, category_id FROM transactions
+         WHERE (?1 IS NULL OR account_id = ?1) AND (?2 OR category_id IS NULL)",
+    )?;
+    let candidates: Vec<(i64, String, Option<i64>)> = stmt
+        .query_map(params![account_id, overwrite_existing], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut result = ApplyRulesResult {
+        transactions_categorized: 0,
+        transactions_unchanged: 0,
+        rules_applied: HashMap::new(),
+    };
+    for (id, payee, current) in candidates {
+        let Some(rule) = find_match(&rules, &payee) else {
+            result.transactions_unchanged += 1;
+            continue;
+        };
+        if let Some(rule_id) = rule.id {
+            *result.rules_applied.entry(rule_id).or_insert(0) += 1;
+        }
+        if current == Some(rule.category_id) {
+            result.transactions_unchanged += 1;
+            continue;
+        }
+
+        transactions::update_category(conn, id, Some(rule.category_id))?;
+        if needs_review(rule) {
+            transactions::flag_for_review(conn, id)?;
+        }
+        result.transactions_categorized += 1;
+    }
+    Ok(result)
+}
+
+/// Turn consistent payee → category assignments from imported history into plain-text rules.
+/// A payee qualifies when it has at least two categorized transactions and more than 80% share one category.
+/// Payees that already have a rule with the same pattern are left alone. The history backs these
+/// rules up, so they are created trusted and don't flag what they categorize for review.
+pub fn migrate_from_category_history(conn: &Connection) -> Result<MigrationResult, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT payee, category_id, COUNT(*) FROM transactions
+         WHERE category_id IS NOT NULL
+         GROUP BY payee, category_id",
+    )?;
+    let mut by_payee: HashMap<String, Vec<(i64, i64)>> = HashMap::new();
+    for row in stmt.query_map([], |row| Ok((row.get::<_, String>(0)?, row.get(1)?, row.get(2)?)))? {
+        let (payee, category_id, count) = row?;
+        by_payee.entry(payee).or_default().push((category_id, count));
+    }
+
+    let existing: HashSet<String> = get_all(conn)?
+        .into_iter()
+        .map(|rule| rule.pattern.to_lowercase())
+        .collect();
+
+    let mut rules_created = 0;
+    for (payee, assignments) in &by_payee {
+        let total: i64 = assignments.iter().map(|(_, count)| count).sum();
+        let (category_id, top) = match assignments.iter().max_by_key(|(_, count)| *count) {
+            Some(&best) => best,
+            None => continue,
+        };
+
+        if total < 2 || (top as f64 / total as f64) <= CONSISTENCY_THRESHOLD {
+            continue;
+        }
+        if existing.contains(&payee.to_lowercase()) {
+            continue;
+        }
+
+        create(
+            conn,
+            &CategorizationRule {
+                id: None,
+                pattern: payee.clone(),
+                category_id,
+                is_regex: false,
+                priority: TRUSTED_PRIORITY,
+                metadata: Some(format!(
+                    "Auto-generated from category history ({} of {} transactions)",
+                    top, total
+                )),
+            },
+        )?;
+        rules_created += 1;
+    }
+
+    Ok(MigrationResult {
+        rules_created,
+        payees_analyzed: by_payee.len(),
+    })
+}
+
+fn compile(pattern: &str) -> Result<Regex, rusqlite::Error> {
+    Regex::new(pattern)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(format!("Invalid rule pattern: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+    use rusqlite::Connection;
+
+    fn rule(pattern: &str, is_regex: bool) -> CategorizationRule {
+        CategorizationRule {
+            id: None,
+            pattern: pattern.to_string(),
+            category_id: 1,
+            is_regex,
+            priority: 0,
+            metadata: None,
+        }
+    }
+
+    #[test]
+    fn plain_rules_match_the_whole_payee() {
+        let netto = rule("Netto", false);
+        assert!(matches(&netto, "Netto"));
+        assert!(matches(&netto, " NETTO "));
+        assert!(!matches(&netto, "Netto Fotex Pay"));
+        assert!(!matches(&netto, "Dankort Netto"));
+    }
+
+    #[test]
+    fn regex_rules_may_match_part_of_the_payee() {
+        let netto = rule("(?i)netto", true);
+        assert!(matches(&netto, "Netto Fotex Pay"));
+        assert!(!matches(&netto, "Irma"));
+    }
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute_batch(
+            r#"INSERT INTO accounts (name) VALUES ('Lønkonto');
+               INSERT INTO categories (name) VALUES ('Dagligvarer');
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-03-01', 'Netto Fotex Pay', -4500);
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-03-02', 'Irma', -3000);"#,
+        )
+        .unwrap();
+        conn
+    }
+
+    fn needing_review(conn: &Connection) -> Vec<i64> {
+        transactions::get_needing_review(conn, 1)
+            .unwrap()
+            .iter()
+            .filter_map(|t| t.transaction.id)
+            .collect()
+    }
+
+    #[test]
+    fn guessed_categories_are_flagged_until_confirmed() {
+        let conn = setup();
+        create(&conn, &rule("(?i)netto", true)).unwrap();
+        create(&conn, &CategorizationRule { priority: TRUSTED_PRIORITY, ..rule("Irma", false) }).unwrap();
+
+        let result = apply_to_account(&conn, Some(1), false).unwrap();
+        assert_eq!(result.transactions_categorized, 2);
+        assert_eq!(needing_review(&conn), vec![1]);
+
+        transactions::confirm_categorization(&conn, 1).unwrap();
+        assert!(needing_review(&conn).is_empty());
+        assert_eq!(transactions::get_by_id(&conn, 1).unwrap().unwrap().category_id, Some(1));
+    }
+
+    #[test]
+    fn compiled_rules_match_in_priority_order() {
+        let conn = setup();
+        create(&conn, &rule("(?i)netto", true)).unwrap();
+        create(&conn, &CategorizationRule { priority: 5, ..rule("Netto Fotex Pay", false) }).unwrap();
+        // Written around `create`, which would reject it
+        conn.execute("INSERT INTO categorization_rules (pattern, category_id, is_regex) VALUES ('(', 1, 1)", []).unwrap();
+
+        let rules = load_compiled(&conn).unwrap();
+        assert_eq!(find_match(&rules, "Netto Fotex Pay").map(|r| r.priority), Some(5));
+        assert_eq!(find_match(&rules, "Dankort NETTO").map(|r| r.pattern.as_str()), Some("(?i)netto"));
+        assert!(find_match(&rules, "(").is_none());
+    }
+
+    #[test]
+    fn rules_from_history_are_trusted() {
+        let conn = setup();
+        conn.execute_batch(
+            r#"UPDATE transactions SET category_id = 1 WHERE payee = 'Irma';
+               INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, '2024-03-09', 'Irma', -2000, 1);
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-03-10', 'Irma', -2500);"#,
+        )
+        .unwrap();
+
+        assert_eq!(migrate_from_category_history(&conn).unwrap().rules_created, 1);
+        assert_eq!(get_all(&conn).unwrap()[0].priority, TRUSTED_PRIORITY);
+        assert_eq!(apply_to_account(&conn, Some(1), false).unwrap().transactions_categorized, 1);
+        assert!(needing_review(&conn).is_empty());
+    }
+}
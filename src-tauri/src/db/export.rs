@@ -0,0 +1,333 @@
+use csv::{ReaderBuilder, WriterBuilder};
+use rusqlite::Connection;
+use super::{categories, transactions};
+use super::models::Category;
+use std::collections::{HashMap, HashSet};
+
+const CATEGORY_HEADERS: [&str; 8] = [
+    "id",
+    "name",
+    "parent_id",
+    "parent_name",
+    "color",
+    "icon",
+    "is_tax_deductible",
+    "spending_type",
+];
+
+/// Export all categories as CSV, parents before their children.
+/// `color`, `icon` and `is_tax_deductible` are not stored on categories yet and are written empty
+/// so the file format stays stable when they are added.
+pub fn export_categories_csv(conn: &Connection) -> Result<String, String> {
+    let all = categories::get_all(conn, true).map_err(|e| e.to_string())?;
+    let names: HashMap<i64, String> = all
+        .iter()
+        .filter_map(|c| c.id.map(|id| (id, c.name.clone())))
+        .collect();
+
+    let mut writer = WriterBuilder::new().from_writer(Vec::new());
+    writer.write_record(CATEGORY_HEADERS).map_err(|e| e.to_string())?;
+    for category in &all {
+        let parent_name = category.parent_id.and_then(|id| names.get(&id)).cloned().unwrap_or_default();
+        writer
+            .write_record([
+                category.id.map(|id| id.to_string()).unwrap_or_default(),
+                category.name.clone(),
+                category.parent_id.map(|id| id.to_string()).unwrap_or_default(),
+                parent_name,
+                String::new(),
+                String::new(),
+                String::new(),
+                category.spending_type.clone(),
+            ])
+            .map_err(|e| e.to_string())?;
+    }
+
+    let bytes = writer.into_inner().map_err(|e| e.to_string())?;
+    String::from_utf8(bytes).map_err(|e| e.to_string())
+}
+
+/// Rebuild a category hierarchy from a file produced by `export_categories_csv`.
+/// IDs differ between databases, so parents are resolved within the file: through the file's own
+/// `id`/`parent_id` when present, otherwise by `parent_name`, which must then name a single row.
+/// A parent that isn't in the file is treated as a top-level category. Each category is matched on
+/// its whole path, so same-named subcategories under different parents stay apart, and existing
+/// categories are reused. Nothing is imported if any row fails. Returns the number of rows imported.
+pub fn import_categories_csv(conn: &Connection, csv_content: &str) -> Result<usize, String> {
+    let mut reader = ReaderBuilder::new()
+        .flexible(true)
+        .trim(csv::Trim::All)
+        .from_reader(csv_content.as_bytes());
+
+    let headers = reader.headers().map_err(|e| format!("Could not read headers: {}", e))?.clone();
+    let column = |name: &str| headers.iter().position(|h| h.eq_ignore_ascii_case(name));
+    let name_idx = column("name").ok_or("Missing 'name' column")?;
+    let id_idx = column("id");
+    let parent_id_idx = column("parent_id");
+    let parent_idx = column("parent_name");
+    let spending_idx = column("spending_type");
+
+    let mut rows: Vec<CategoryRow> = Vec::new();
+    for (i, record) in reader.records().enumerate() {
+        let record = record.map_err(|e| format!("Error in CSV row {}: {}", i + 1, e))?;
+        let value = |idx: Option<usize>| {
+            idx.and_then(|idx| record.get(idx))
+                .filter(|v| !v.is_empty())
+                .map(|v| v.to_string())
+        };
+        let Some(name) = value(Some(name_idx)) else {
+            return Err(format!("Row {} has no category name", i + 1));
+        };
+        rows.push(CategoryRow {
+            file_id: value(id_idx),
+            name,
+            parent_file_id: value(parent_id_idx),
+            parent_name: value(parent_idx),
+            spending_type: value(spending_idx),
+        });
+    }
+
+    let parents = link_parents(&rows)?;
+    check_for_cycles(&rows, &parents)?;
+
+    let db_tx = conn.unchecked_transaction().map_err(|e| e.to_string())?;
+    let mut ids = vec![None; rows.len()];
+    for (row_idx, row) in rows.iter().enumerate() {
+        let id = resolve(&db_tx, row_idx, &rows, &parents, &mut ids)?;
+        if let Some(spending_type) = &row.spending_type {
+            let parent_id = resolve_parent(&db_tx, row_idx, &rows, &parents, &mut ids)?;
+            categories::update(
+                &db_tx,
+                &Category {
+                    id: Some(id),
+                    name: row.name.clone(),
+                    parent_id,
+                    spending_type: spending_type.clone(),
+                    is_hidden: false,
+                },
+            )
+            .map_err(|e| format!("Could not import '{}': {}", row.name, e))?;
+        }
+    }
+    db_tx.commit().map_err(|e| e.to_string())?;
+
+    Ok(rows.len())
+}
+
+/// A row of a category file
+struct CategoryRow {
+    file_id: Option<String>,
+    name: String,
+    parent_file_id: Option<String>,
+    parent_name: Option<String>,
+    spending_type: Option<String>,
+}
+
+/// Where a row's parent is: another row of the file, or a top-level category outside it
+enum Parent {
+    Row(usize),
+    External(String),
+}
+
+/// Find each row's parent within the file, preferring the file's IDs over names
+fn link_parents(rows: &[CategoryRow]) -> Result<Vec<Option<Parent>>, String> {
+    let mut by_id: HashMap<&str, usize> = HashMap::new();
+    let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (idx, row) in rows.iter().enumerate() {
+        if let Some(file_id) = &row.file_id {
+            if by_id.insert(file_id, idx).is_some() {
+                return Err(format!("Category id {} appears more than once", file_id));
+            }
+        }
+        by_name.entry(&row.name).or_default().push(idx);
+    }
+
+    rows.iter()
+        .map(|row| {
+            if let Some(&parent) = row.parent_file_id.as_deref().and_then(|id| by_id.get(id)) {
+                return Ok(Some(Parent::Row(parent)));
+            }
+            let Some(parent_name) = &row.parent_name else {
+                return Ok(None);
+            };
+            match by_name.get(parent_name.as_str()).map(Vec::as_slice) {
+                None => Ok(Some(Parent::External(parent_name.clone()))),
+                Some([parent]) => Ok(Some(Parent::Row(*parent))),
+                Some(_) => Err(format!(
+                    "The parent '{}' of '{}' matches several categories; include the id and parent_id columns",
+                    parent_name, row.name
+                )),
+            }
+        })
+        .collect()
+}
+
+/// Reject files where following parents leads back to a row already on the path
+fn check_for_cycles(rows: &[CategoryRow], parents: &[Option<Parent>]) -> Result<(), String> {
+    for start in 0..rows.len() {
+        let mut path = vec![rows[start].name.as_str()];
+        let mut seen = HashSet::from([start]);
+        let mut current = start;
+        while let Some(Parent::Row(parent)) = parents[current] {
+            path.push(&rows[parent].name);
+            if !seen.insert(parent) {
+                return Err(format!("Circular parent reference: {}", path.join(" -> ")));
+            }
+            current = parent;
+        }
+    }
+    Ok(())
+}
+
+/// Category ID for a row, creating it (and its ancestors) first if needed
+fn resolve(
+    conn: &Connection,
+    row_idx: usize,
+    rows: &[CategoryRow],
+    parents: &[Option<Parent>],
+    ids: &mut Vec<Option<i64>>,
+) -> Result<i64, String> {
+    if let Some(id) = ids[row_idx] {
+        return Ok(id);
+    }
+    let parent_id = resolve_parent(conn, row_idx, rows, parents, ids)?;
+    let id = categories::find_or_create(conn, &rows[row_idx].name, parent_id).map_err(|e| e.to_string())?;
+    ids[row_idx] = Some(id);
+    Ok(id)
+}
+
+/// Category ID of a row's parent, if it has one
+fn resolve_parent(
+    conn: &Connection,
+    row_idx: usize,
+    rows: &[CategoryRow],
+    parents: &[Option<Parent>],
+    ids: &mut Vec<Option<i64>>,
+) -> Result<Option<i64>, String> {
+    match &parents[row_idx] {
+        Some(Parent::Row(parent)) => resolve(conn, *parent, rows, parents, ids).map(Some),
+        Some(Parent::External(name)) => categories::find_or_create(conn, name, None).map(Some).map_err(|e| e.to_string()),
+        None => Ok(None),
+    }
+}
+
+/// Export an account's transactions as a QIF `!Type:Bank` section (for Quicken, GnuCash, ...).
+/// Both dates are inclusive and optional. Subcategories are written as `Parent:Child`.
+pub fn export_qif(
+    conn: &Connection,
+    account_id: i64,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<String, String> {
+    let start = start_date.unwrap_or_else(|| "0000-01-01".to_string());
+    let end = end_date.unwrap_or_else(|| "9999-12-31".to_string());
+    let mut rows = transactions::get_by_date_range(conn, account_id, &start, &end).map_err(|e| e.to_string())?;
+    rows.reverse(); // Oldest first
+
+    let mut qif = String::from("!Type:Bank\n");
+    for row in &rows {
+        let tx = &row.transaction;
+        let date = chrono::NaiveDate::parse_from_str(&tx.date, "%Y-%m-%d")
+            .map(|d| d.format("%m/%d/%Y").to_string())
+            .unwrap_or_else(|_| tx.date.clone());
+        qif.push_str(&format!("D{}\n", date));
+        qif.push_str(&format!("T{}\n", format_ore(tx.amount)));
+        qif.push_str(&format!("P{}\n", tx.payee));
+        let category = match (&row.parent_category_name, &row.category_name) {
+            (Some(parent), Some(name)) => Some(format!("{}:{}", parent, name)),
+            (None, Some(name)) => Some(name.clone()),
+            _ => None,
+        };
+        if let Some(category) = category {
+            qif.push_str(&format!("L{}\n", category));
+        }
+        qif.push_str("^\n");
+    }
+    Ok(qif)
+}
+
+/// Øre as a decimal amount with two places, e.g. -123450 -> "-1234.50"
+fn format_ore(amount: i64) -> String {
+    let sign = if amount < 0 { "-" } else { "" };
+    format!("{}{}.{:02}", sign, amount.abs() / 100, amount.abs() % 100)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn
+    }
+
+    /// Every category as "Parent/Child" paths, sorted
+    fn paths(conn: &Connection) -> Vec<String> {
+        let all = categories::get_all(conn, true).unwrap();
+        let mut paths: Vec<String> = all
+            .iter()
+            .map(|c| match c.parent_id.and_then(|id| all.iter().find(|p| p.id == Some(id))) {
+                Some(parent) => format!("{}/{}", parent.name, c.name),
+                None => c.name.clone(),
+            })
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    #[test]
+    fn same_named_subcategories_round_trip_by_path() {
+        let source = setup();
+        let food = categories::find_or_create(&source, "Mad", None).unwrap();
+        let transport = categories::find_or_create(&source, "Transport", None).unwrap();
+        categories::find_or_create(&source, "Andet", Some(food)).unwrap();
+        categories::find_or_create(&source, "Andet", Some(transport)).unwrap();
+        let csv = export_categories_csv(&source).unwrap();
+
+        let target = setup();
+        categories::find_or_create(&target, "Transport", None).unwrap();
+        assert_eq!(import_categories_csv(&target, &csv).unwrap(), 4);
+        assert_eq!(paths(&target), ["Mad", "Mad/Andet", "Transport", "Transport/Andet"]);
+    }
+
+    #[test]
+    fn ambiguous_parent_names_are_rejected_without_ids() {
+        let conn = setup();
+        let csv = "name,parent_name\nAndet,Mad\nAndet,Transport\nMad,\nTransport,\nTaxa,Andet\n";
+        let error = import_categories_csv(&conn, csv).unwrap_err();
+        assert!(error.contains("'Andet' of 'Taxa'"), "{}", error);
+        assert!(categories::get_all(&conn, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn failed_import_leaves_no_categories_behind() {
+        let conn = setup();
+        let csv = "name,parent_name,spending_type\nBolig,,essential\nHusleje,Bolig,not-a-type\n";
+        assert!(import_categories_csv(&conn, csv).is_err());
+        assert!(categories::get_all(&conn, true).unwrap().is_empty());
+    }
+
+    #[test]
+    fn qif_export_has_one_record_per_transaction() {
+        let conn = setup();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        let food = categories::find_or_create(&conn, "Mad", None).unwrap();
+        let groceries = categories::find_or_create(&conn, "Dagligvarer", Some(food)).unwrap();
+        conn.execute_batch(&format!(
+            r#"INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, '2024-03-05', 'Netto', -12345, {groceries});
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-03-01', 'Løn', 2500000);
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-04-01', 'Husleje', -800000);"#
+        ))
+        .unwrap();
+
+        let qif = export_qif(&conn, 1, Some("2024-03-01".to_string()), Some("2024-03-31".to_string())).unwrap();
+        assert_eq!(
+            qif,
+            "!Type:Bank\n\
+             D03/01/2024\nT25000.00\nPLøn\n^\n\
+             D03/05/2024\nT-123.45\nPNetto\nLMad:Dagligvarer\n^\n"
+        );
+    }
+}
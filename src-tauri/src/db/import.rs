@@ -2,10 +2,82 @@ use csv::ReaderBuilder;
 use encoding_rs::WINDOWS_1252;
 use rusqlite::Connection;
 use sha2::{Digest, Sha256};
+use std::collections::HashMap;
 
-use super::models::ImportResult;
+use super::models::{AmbiguousColumn, CsvValidationResult, ImportGap, ImportPreview, ImportProfile, ImportResult, NumberFormat, PreviewRow, UnmappedCategoryMode};
 use super::models::Transaction;
-use super::{categories, transactions};
+use super::{accounts, categories, categorization_rules, payee_rules, settings, transactions};
+
+/// Header keywords for each field, matched case-insensitively as substrings
+const DATE_NAMES: &[&str] = &["dato", "date", "bogført"];
+const CATEGORY_NAMES: &[&str] = &["kategori", "category"];
+const SUBCATEGORY_NAMES: &[&str] = &["underkategori", "subcategory"];
+const TEXT_NAMES: &[&str] = &["tekst", "text", "description", "payee"];
+const MEMO_NAMES: &[&str] = &["meddelelse", "reference", "memo", "besked"];
+const AMOUNT_NAMES: &[&str] = &["beløb", "belob", "bel", "amount"];
+const BALANCE_NAMES: &[&str] = &["saldo", "balance"];
+const STATUS_NAMES: &[&str] = &["status"];
+const RECONCILED_NAMES: &[&str] = &["afstemt", "reconciled"];
+
+/// Amounts (øre) above this are flagged as likely misparsed when `import_amount_ceiling` is not set: 10 mio. kr
+const DEFAULT_IMPORT_AMOUNT_CEILING: f64 = 1_000_000_000.0;
+
+/// Field names (as used in column maps) and the header keywords that identify them
+const FIELDS: &[(&str, &[&str])] = &[
+    ("date", DATE_NAMES),
+    ("category", CATEGORY_NAMES),
+    ("subcategory", SUBCATEGORY_NAMES),
+    ("payee", TEXT_NAMES),
+    ("memo", MEMO_NAMES),
+    ("amount", AMOUNT_NAMES),
+    ("balance", BALANCE_NAMES),
+    ("status", STATUS_NAMES),
+    ("reconciled", RECONCILED_NAMES),
+];
+
+/// Number of data rows returned by `validate_csv` for the user to eyeball
+const SAMPLE_ROWS: usize = 5;
+
+/// How many lines to scan for the header row before giving up and assuming line one
+const MAX_PREAMBLE_LINES: usize = 20;
+
+/// Number of data rows whose amounts are inspected to guess the number format
+const FORMAT_SAMPLE_ROWS: usize = 100;
+
+/// Months with fewer transactions than this share of the median month are reported as import gaps
+const IMPORT_GAP_FRACTION: f64 = 0.25;
+
+/// Currencies whose banks normally write amounts as 1,234.56; all others are expected to use 1.234,56
+const US_FORMAT_CURRENCIES: &[&str] = &["USD", "GBP", "AUD", "CAD", "NZD", "JPY", "CNY", "INR"];
+
+/// Column position for each field, keyed by field name ("date", "payee", "amount", ...)
+pub type ColumnMap = HashMap<String, usize>;
+
+/// How a CSV file is laid out: its delimiter, headers and which column holds which field
+struct CsvLayout {
+    delimiter: u8,
+    preamble_lines: usize,
+    headers: csv::StringRecord,
+    columns: ColumnMap,
+    amount_columns: Vec<usize>,    // Summed into the amount when set, otherwise columns["amount"] is used
+    number_format: NumberFormat,   // Used for parsing: the profile's, or Danish
+    detected_format: NumberFormat, // Guessed from the amounts, only used for warnings
+    ambiguous_columns: Vec<AmbiguousColumn>,
+}
+
+/// A CSV row parsed into transaction fields, before any database lookups
+struct ParsedRow {
+    date: String,
+    raw_payee: String,
+    memo: Option<String>,
+    amount: i64,
+    balance: Option<i64>,
+    status: Option<String>,
+    is_reconciled: bool,
+    category: Option<String>,
+    subcategory: Option<String>,
+    import_hash: String,
+}
 
 /// Import a Danish bank CSV file from a UTF-8 string.
 pub fn import_csv(
@@ -14,24 +86,7 @@ pub fn import_csv(
     account_id: i64,
     filename: &str,
 ) -> Result<ImportResult, String> {
-    // Try semicolon delimiter first (common in Danish exports), fall back to comma
-    let result = try_import_with_delimiter(conn, csv_content, account_id, b';');
-
-    if result.is_err() {
-        // Try comma delimiter as fallback
-        let comma_result = try_import_with_delimiter(conn, csv_content, account_id, b',');
-        if comma_result.is_ok() {
-            let res = comma_result.unwrap();
-            log_import(conn, filename, res.imported)?;
-            return Ok(res);
-        }
-    }
-
-    if let Ok(ref res) = result {
-        log_import(conn, filename, res.imported)?;
-    }
-
-    result
+    commit_csv_import(conn, csv_content, account_id, filename, &ImportProfile::default())
 }
 
 /// Import CSV from raw bytes (handles encoding detection)
@@ -47,93 +102,549 @@ pub fn import_csv_bytes(
     }
 
     // 2. Try Windows-1252 (Common for Danish banks)
-    let (decoded, _encoding_used, _had_errors) = WINDOWS_1252.decode(bytes);
+    let (decoded, encoding_used, _had_errors) = WINDOWS_1252.decode(bytes);
     
     // Even if there were minor errors, it's likely better than nothing for bank files
     // as Latin-1/Windows-1252 mostly always "decodes" something.
-    import_csv(conn, &decoded, account_id, filename)
+    let mut result = import_csv(conn, &decoded, account_id, filename)?;
+    result.encoding_used = encoding_used.name().to_string();
+    Ok(result)
 }
 
-fn try_import_with_delimiter(
+// === Import Wizard ===
+
+/// Step 1: detect the delimiter and column mapping and return a few sample rows, using the profile
+/// the later steps will import with. Problems are reported in `error` rather than failing, so the
+/// user can fix the mapping; a number format that doesn't suit the file or the account is a warning.
+pub fn validate_csv(
     conn: &Connection,
     csv_content: &str,
     account_id: i64,
-    delimiter: u8,
+    profile: &ImportProfile,
+) -> CsvValidationResult {
+    let (layout, mut error) = match parse_csv(csv_content, profile) {
+        Ok((layout, _)) => (Some(layout), None),
+        Err(e) => (detect_layout(csv_content, profile).ok(), Some(e)),
+    };
+    let mut warnings = Vec::new();
+    if let Some(layout) = &layout {
+        match number_format_warning(conn, account_id, layout.number_format, layout.detected_format) {
+            Ok(warning) => warnings.extend(warning),
+            Err(e) => error = error.or(Some(e)),
+        }
+    }
+
+    let Some(layout) = layout else {
+        return CsvValidationResult {
+            detected_format: "unknown".to_string(),
+            headers: vec![],
+            column_map: ColumnMap::new(),
+            sample_rows: vec![],
+            ambiguous_columns: vec![],
+            skipped_preamble_lines: 0,
+            warnings,
+            error,
+        };
+    };
+
+    let sample_rows = csv_reader(skip_lines(csv_content, layout.preamble_lines), layout.delimiter)
+        .records()
+        .take(SAMPLE_ROWS)
+        .filter_map(|r| r.ok())
+        .map(|record| record.iter().map(|s| s.to_string()).collect())
+        .collect();
+
+    CsvValidationResult {
+        detected_format: delimiter_name(layout.delimiter).to_string(),
+        headers: layout.headers.iter().map(|s| s.to_string()).collect(),
+        column_map: layout.columns,
+        sample_rows,
+        ambiguous_columns: layout.ambiguous_columns,
+        skipped_preamble_lines: layout.preamble_lines,
+        warnings,
+        error,
+    }
+}
+
+/// Step 2: show what would be inserted, without writing anything
+pub fn preview_csv_import(
+    conn: &Connection,
+    csv_content: &str,
+    account_id: i64,
+    profile: &ImportProfile,
+) -> Result<ImportPreview, String> {
+    let (layout, rows) = parse_csv(csv_content, profile)?;
+    let rules = payee_rules::load_compiled(conn).map_err(|e| e.to_string())?;
+
+    let mut preview = Vec::new();
+    let mut duplicate_rows = 0;
+    for row in rows {
+        let is_duplicate = transactions::exists_by_hash(conn, &row.import_hash).map_err(|e| e.to_string())?;
+        if is_duplicate {
+            duplicate_rows += 1;
+        }
+        preview.push(PreviewRow {
+            date: row.date,
+            payee: payee_rules::apply(&rules, &row.raw_payee),
+            memo: row.memo,
+            amount: row.amount,
+            balance: row.balance,
+            category: row.category,
+            subcategory: row.subcategory,
+            is_duplicate,
+        });
+    }
+
+    Ok(ImportPreview {
+        account_id,
+        total_rows: preview.len(),
+        new_rows: preview.len() - duplicate_rows,
+        duplicate_rows,
+        rows: preview,
+        ambiguous_columns: layout.ambiguous_columns,
+    })
+}
+
+/// Step 3: import, with the profile's column overrides taking precedence over detected columns
+pub fn commit_csv_import(
+    conn: &Connection,
+    csv_content: &str,
+    account_id: i64,
+    filename: &str,
+    profile: &ImportProfile,
+) -> Result<ImportResult, String> {
+    let (layout, rows) = parse_csv(csv_content, profile)?;
+    let period = date_span(&rows);
+    let mut result = insert_rows(conn, rows, account_id, profile)?;
+    result.ambiguous_columns = layout.ambiguous_columns;
+    result.skipped_preamble_lines = layout.preamble_lines;
+    result.delimiter_used = layout.delimiter as char;
+    result.number_format = layout.number_format;
+    if let Some(warning) = number_format_warning(conn, account_id, layout.number_format, layout.detected_format)? {
+        result.warnings.push(warning);
+    }
+    log_import(conn, account_id, filename, result.imported, period)?;
+    Ok(result)
+}
+
+// === Apple Card ===
+
+/// Columns of an Apple Card export, in the order Apple writes them
+const APPLE_CARD_HEADERS: &[&str] = &[
+    "Transaction Date",
+    "Clearing Date",
+    "Description",
+    "Merchant",
+    "Category",
+    "Type",
+    "Amount (USD)",
+];
+
+/// Import an Apple Card CSV export. The merchant becomes the payee and the description the memo;
+/// Apple's category is created as a top-level category. Dates are MM/DD/YYYY and amounts use
+/// a decimal point, positive for payments and negative for charges.
+pub fn import_apple_card_csv(
+    conn: &Connection,
+    csv_content: &str,
+    account_id: i64,
+    filename: &str,
 ) -> Result<ImportResult, String> {
-    let mut reader = ReaderBuilder::new()
+    let mut reader = csv_reader(csv_content, b',');
+    let headers = reader.headers().map_err(|e| format!("Kunne ikke læse overskrifter: {}", e))?.clone();
+    let mut columns = ColumnMap::new();
+    for name in APPLE_CARD_HEADERS {
+        if let Some(idx) = headers.iter().position(|h| h.eq_ignore_ascii_case(name)) {
+            columns.insert(name.to_string(), idx);
+        }
+    }
+    for required in ["Transaction Date", "Merchant", "Amount (USD)"] {
+        if !columns.contains_key(required) {
+            return Err(format!(
+                "Kunne ikke finde kolonnen '{}'. Fundne overskrifter: {:?}",
+                required, headers
+            ));
+        }
+    }
+    let field = |record: &csv::StringRecord, name: &str| -> Option<String> {
+        columns
+            .get(name)
+            .and_then(|&i| record.get(i))
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
+
+    let mut rows = Vec::new();
+    for result in reader.records() {
+        let record = result.map_err(|e| format!("Fejl i CSV række {}: {}", rows.len() + 1, e))?;
+        let date = parse_us_date(&field(&record, "Transaction Date").unwrap_or_default())?;
+        let memo = field(&record, "Description");
+        let raw_payee = field(&record, "Merchant").or_else(|| memo.clone()).unwrap_or_default();
+        let amount = parse_amount(&field(&record, "Amount (USD)").unwrap_or_default(), NumberFormat::Us)?;
+        let import_hash = generate_import_hash(&date, &raw_payee, amount, None);
+
+        rows.push(ParsedRow {
+            date,
+            raw_payee,
+            memo,
+            amount,
+            balance: None,
+            status: None,
+            is_reconciled: false,
+            category: field(&record, "Category"),
+            subcategory: None,
+            import_hash,
+        });
+    }
+
+    let period = date_span(&rows);
+    let mut result = insert_rows(conn, rows, account_id, &ImportProfile::default())?;
+    result.delimiter_used = ',';
+    result.number_format = NumberFormat::Us;
+    log_import(conn, account_id, filename, result.imported, period)?;
+    Ok(result)
+}
+
+/// Parse an American MM/DD/YYYY date to YYYY-MM-DD
+fn parse_us_date(s: &str) -> Result<String, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Err("Dato mangler".to_string());
+    }
+    chrono::NaiveDate::parse_from_str(s, "%m/%d/%Y")
+        .map(|date| date.format("%Y-%m-%d").to_string())
+        .map_err(|_| format!("Ugyldigt datoformat: {}", s))
+}
+
+// === Import Gaps ===
+
+/// Months between the account's first and last transaction with suspiciously few transactions
+/// (under a quarter of the median month), which usually means a statement was never imported.
+/// Months that a logged import's file spans from start to end are left out: that statement was
+/// imported, so the month really was quiet.
+pub fn detect_gaps(conn: &Connection, account_id: i64) -> Result<Vec<ImportGap>, String> {
+    let mut stmt = conn
+        .prepare(
+            "SELECT substr(date, 1, 7) as month, COUNT(*) FROM transactions
+             WHERE account_id = ?1 GROUP BY month ORDER BY month",
+        )
+        .map_err(|e| e.to_string())?;
+    let counts: HashMap<String, i64> = stmt
+        .query_map(rusqlite::params![account_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .and_then(|rows| rows.collect())
+        .map_err(|e| e.to_string())?;
+
+    let (Some(first), Some(last)) = (counts.keys().min(), counts.keys().max()) else {
+        return Ok(Vec::new());
+    };
+    let parse_month = |month: &str| chrono::NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d").ok();
+    let (Some(mut month), Some(last)) = (parse_month(first), parse_month(last)) else {
+        return Err(format!("Ugyldig dato mellem {} og {}", first, last));
+    };
+    let mut stmt = conn
+        .prepare("SELECT first_date, last_date FROM import_log WHERE account_id = ?1 AND first_date IS NOT NULL")
+        .map_err(|e| e.to_string())?;
+    let imported: Vec<(String, String)> = stmt
+        .query_map(rusqlite::params![account_id], |row| Ok((row.get(0)?, row.get(1)?)))
+        .and_then(|rows| rows.collect())
+        .map_err(|e| e.to_string())?;
+
+    let mut months = Vec::new();
+    while month <= last {
+        let key = month.format("%Y-%m").to_string();
+        let next = month + chrono::Months::new(1);
+        let (start, end) = (
+            month.format("%Y-%m-%d").to_string(),
+            next.pred_opt().unwrap_or(month).format("%Y-%m-%d").to_string(),
+        );
+        let covered = imported.iter().any(|(first, last)| *first <= start && *last >= end);
+        months.push((counts.get(&key).copied().unwrap_or(0), key, covered));
+        month = next;
+    }
+
+    let mut sorted: Vec<i64> = months.iter().map(|(count, _, _)| *count).collect();
+    sorted.sort();
+    let median_count = sorted[sorted.len() / 2];
+    let threshold = median_count as f64 * IMPORT_GAP_FRACTION;
+
+    Ok(months
+        .into_iter()
+        .filter(|(count, _, covered)| (*count as f64) < threshold && !covered)
+        .map(|(transaction_count, month, _)| ImportGap {
+            month,
+            transaction_count,
+            median_count,
+        })
+        .collect())
+}
+
+// === Parsing ===
+
+/// Parse the whole file. Try semicolon delimiter first (common in Danish exports), fall back to comma.
+fn parse_csv(csv_content: &str, profile: &ImportProfile) -> Result<(CsvLayout, Vec<ParsedRow>), String> {
+    let result = parse_with_delimiter(csv_content, b';', profile);
+    if result.is_ok() {
+        return result;
+    }
+    parse_with_delimiter(csv_content, b',', profile).or(result)
+}
+
+fn parse_with_delimiter(
+    csv_content: &str,
+    delimiter: u8,
+    profile: &ImportProfile,
+) -> Result<(CsvLayout, Vec<ParsedRow>), String> {
+    let layout = read_layout(csv_content, delimiter, profile)?;
+    let rows = parse_rows(csv_content, &layout)?;
+    Ok((layout, rows))
+}
+
+/// Find a delimiter whose headers contain all required columns
+fn detect_layout(csv_content: &str, profile: &ImportProfile) -> Result<CsvLayout, String> {
+    read_layout(csv_content, b';', profile)
+        .or_else(|e| read_layout(csv_content, b',', profile).map_err(|_| e))
+}
+
+fn csv_reader(csv_content: &str, delimiter: u8) -> csv::Reader<&[u8]> {
+    ReaderBuilder::new()
         .delimiter(delimiter)
         .flexible(true) // Allow varying number of fields
         .trim(csv::Trim::All)
-        .from_reader(csv_content.as_bytes());
+        .from_reader(csv_content.as_bytes())
+}
+
+/// Some banks put metadata lines (account holder, export date) above the real header.
+/// Returns the number of lines before the first line that looks like a header (has both
+/// a date and an amount column), or 0 if no such line is found.
+fn find_header_line(csv_content: &str, delimiter: u8) -> usize {
+    let delimiter = delimiter as char;
+    csv_content
+        .lines()
+        .take(MAX_PREAMBLE_LINES)
+        .position(|line| {
+            let cells: Vec<String> = line
+                .split(delimiter)
+                .map(|cell| cell.trim().trim_matches('"').to_lowercase())
+                .collect();
+            let has = |names: &[&str]| cells.iter().any(|cell| names.iter().any(|name| cell.contains(name)));
+            has(DATE_NAMES) && has(AMOUNT_NAMES)
+        })
+        .unwrap_or(0)
+}
+
+/// The content after the first `n` lines
+fn skip_lines(csv_content: &str, n: usize) -> &str {
+    let mut rest = csv_content;
+    for _ in 0..n {
+        match rest.find('\n') {
+            Some(pos) => rest = &rest[pos + 1..],
+            None => return "",
+        }
+    }
+    rest
+}
+
+fn delimiter_name(delimiter: u8) -> &'static str {
+    match delimiter {
+        b';' => "semicolon",
+        b',' => "comma",
+        _ => "unknown",
+    }
+}
 
+fn read_layout(csv_content: &str, delimiter: u8, profile: &ImportProfile) -> Result<CsvLayout, String> {
+    let preamble_lines = find_header_line(csv_content, delimiter);
+    let mut reader = csv_reader(skip_lines(csv_content, preamble_lines), delimiter);
     let headers = reader.headers().map_err(|e| format!("Kunne ikke læse overskrifter: {}", e))?.clone();
 
-    // Find column indices by header name (case-insensitive)
-    let date_idx = find_column_index(&headers, &["dato", "date"]);
-    let category_idx = find_column_index(&headers, &["kategori", "category"]);
-    let subcategory_idx = find_column_index(&headers, &["underkategori", "subcategory"]);
-    let text_idx = find_column_index(&headers, &["tekst", "text", "description", "payee"]);
-    let amount_idx = find_column_index(&headers, &["beløb", "belob", "bel", "amount"]);
-    let balance_idx = find_column_index(&headers, &["saldo", "balance"]);
-    let status_idx = find_column_index(&headers, &["status"]);
-    let reconciled_idx = find_column_index(&headers, &["afstemt", "reconciled"]);
+    // Find column indices by header name (case-insensitive), unless the user mapped them
+    let mut ambiguous_columns = Vec::new();
+    let mut columns = ColumnMap::new();
+    for (field, names) in FIELDS {
+        let idx = match profile.column_overrides.get(*field) {
+            Some(&idx) => {
+                if idx >= headers.len() {
+                    return Err(format!("Kolonne {} for '{}' findes ikke. Fundne overskrifter: {:?}", idx, field, headers));
+                }
+                Some(idx)
+            }
+            None => find_column_index(&headers, field, names, &mut ambiguous_columns),
+        };
+        if let Some(idx) = idx {
+            columns.insert(field.to_string(), idx);
+        }
+    }
+
+    // Amount split over several columns: sum those present, the first one stands in as "amount"
+    let amount_columns: Vec<usize> = profile
+        .amount_columns
+        .iter()
+        .filter_map(|name| headers.iter().position(|h| h.eq_ignore_ascii_case(name.trim())))
+        .collect();
+    if !profile.amount_columns.is_empty() {
+        let Some(&first) = amount_columns.first() else {
+            return Err(format!(
+                "Ingen af beløbskolonnerne {:?} findes. Fundne overskrifter: {:?}",
+                profile.amount_columns, headers
+            ));
+        };
+        columns.insert("amount".to_string(), first);
+        ambiguous_columns.retain(|a| a.field != "amount");
+    }
 
     // Validate required columns
-    let date_idx = date_idx.ok_or_else(|| format!("Kunne ikke finde kolonnen 'Dato'. Fundne overskrifter: {:?}", headers))?;
-    let text_idx = text_idx.ok_or_else(|| format!("Kunne ikke finde kolonnen 'Tekst' eller 'Payee'. Fundne overskrifter: {:?}", headers))?;
-    let amount_idx = amount_idx.ok_or_else(|| format!("Kunne ikke finde kolonnen 'Beløb'. Fundne overskrifter: {:?}", headers))?;
+    if !columns.contains_key("date") {
+        return Err(format!("Kunne ikke finde kolonnen 'Dato'. Fundne overskrifter: {:?}", headers));
+    }
+    if !columns.contains_key("payee") {
+        return Err(format!("Kunne ikke finde kolonnen 'Tekst' eller 'Payee'. Fundne overskrifter: {:?}", headers));
+    }
+    if !columns.contains_key("amount") {
+        return Err(format!("Kunne ikke finde kolonnen 'Beløb'. Fundne overskrifter: {:?}", headers));
+    }
+    // With the wrong delimiter the whole header line is one column that "matches" everything
+    if columns["date"] == columns["payee"] || columns["date"] == columns["amount"] || columns["payee"] == columns["amount"] {
+        return Err(format!("Dato, tekst og beløb skal være forskellige kolonner. Fundne overskrifter: {:?}", headers));
+    }
 
-    let mut total_rows = 0;
-    let mut imported = 0;
-    let mut skipped = 0;
+    // Amounts are parsed as chosen in the profile, otherwise as Danish. The format guessed from the
+    // amount and balance cells is never used for parsing, since values like "1.234" are ambiguous.
+    let number_format = profile.number_format.unwrap_or_default();
+    let mut sample_columns = if amount_columns.is_empty() { vec![columns["amount"]] } else { amount_columns.clone() };
+    sample_columns.extend(columns.get("balance"));
+    let samples: Vec<String> = reader
+        .records()
+        .take(FORMAT_SAMPLE_ROWS)
+        .filter_map(|r| r.ok())
+        .flat_map(|record| {
+            sample_columns
+                .iter()
+                .filter_map(|&i| record.get(i).map(|s| s.to_string()))
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let detected_format = detect_number_format(&samples);
+
+    Ok(CsvLayout {
+        delimiter,
+        preamble_lines,
+        headers,
+        columns,
+        amount_columns,
+        number_format,
+        detected_format,
+        ambiguous_columns,
+    })
+}
+
+fn parse_rows(csv_content: &str, layout: &CsvLayout) -> Result<Vec<ParsedRow>, String> {
+    let mut reader = csv_reader(skip_lines(csv_content, layout.preamble_lines), layout.delimiter);
+    let field = |record: &csv::StringRecord, name: &str| -> Option<String> {
+        layout
+            .columns
+            .get(name)
+            .and_then(|&i| record.get(i))
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+    };
 
+    let mut rows = Vec::new();
     for result in reader.records() {
-        let record = result.map_err(|e| format!("Fejl i CSV række {}: {}", total_rows + 1, e))?;
-        total_rows += 1;
+        let record = result.map_err(|e| format!("Fejl i CSV række {}: {}", rows.len() + 1, e))?;
 
         // Parse required fields
-        let date = parse_danish_date(record.get(date_idx).unwrap_or(""))?;
-        let payee = record.get(text_idx).unwrap_or("").trim().to_string();
-        let amount = parse_danish_amount(record.get(amount_idx).unwrap_or(""))?;
+        let date = parse_danish_date(&field(&record, "date").unwrap_or_default())?;
+        let raw_payee = field(&record, "payee").unwrap_or_default();
+        let amount = if layout.amount_columns.is_empty() {
+            parse_amount(&field(&record, "amount").unwrap_or_default(), layout.number_format)?
+        } else {
+            sum_amount_columns(&record, &layout.amount_columns, layout.number_format)?
+        };
 
         // Parse optional fields
-        let kategori = category_idx
-            .and_then(|i| record.get(i))
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty());
-        let underkategori = subcategory_idx
-            .and_then(|i| record.get(i))
-            .map(|s| s.trim())
-            .filter(|s| !s.is_empty());
-        let balance = balance_idx
-            .and_then(|i| record.get(i))
-            .and_then(|s| parse_danish_amount(s).ok());
-        let status = status_idx
-            .and_then(|i| record.get(i))
-            .map(|s| s.trim().to_string())
-            .filter(|s| !s.is_empty());
-        let is_reconciled = reconciled_idx
-            .and_then(|i| record.get(i))
+        let balance = field(&record, "balance").and_then(|s| parse_amount(&s, layout.number_format).ok());
+        let is_reconciled = field(&record, "reconciled")
             .map(|s| {
-                let lower = s.trim().to_lowercase();
+                let lower = s.to_lowercase();
                 lower == "ja" || lower == "yes" || lower == "true" || lower == "1"
             })
             .unwrap_or(false);
 
-        // Generate import hash for deduplication
-        let hash = generate_import_hash(&date, &payee, amount, balance);
+        // Generate import hash for deduplication (from the raw payee so rule changes don't break it)
+        let import_hash = generate_import_hash(&date, &raw_payee, amount, balance);
+
+        rows.push(ParsedRow {
+            date,
+            raw_payee,
+            memo: field(&record, "memo"),
+            amount,
+            balance,
+            status: field(&record, "status"),
+            is_reconciled,
+            category: field(&record, "category"),
+            subcategory: field(&record, "subcategory"),
+            import_hash,
+        });
+    }
+    Ok(rows)
+}
+
+/// Sum the amounts in several columns. Empty cells count as zero, but at least one must be filled in.
+fn sum_amount_columns(record: &csv::StringRecord, amount_columns: &[usize], format: NumberFormat) -> Result<i64, String> {
+    let values: Vec<&str> = amount_columns
+        .iter()
+        .filter_map(|&i| record.get(i))
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty())
+        .collect();
+    if values.is_empty() {
+        return Err("Beløb mangler".to_string());
+    }
+    values.iter().map(|s| parse_amount(s, format)).sum()
+}
+
+fn insert_rows(
+    conn: &Connection,
+    rows: Vec<ParsedRow>,
+    account_id: i64,
+    profile: &ImportProfile,
+) -> Result<ImportResult, String> {
+    let rules = payee_rules::load_compiled(conn).map_err(|e| e.to_string())?;
+    let category_rules = categorization_rules::load_compiled(conn).map_err(|e| e.to_string())?;
+    let category_mapping = load_category_mapping(conn, profile)?;
 
+    let implausible = implausible_amounts(conn, &rows)?;
+
+    let total_rows = rows.len();
+    let mut imported = 0;
+    let mut skipped = 0;
+    let mut unmapped: Vec<String> = Vec::new();
+
+    for row in rows {
         // Skip if already imported
-        if transactions::exists_by_hash(conn, &hash).map_err(|e| e.to_string())? {
+        if transactions::exists_by_hash(conn, &row.import_hash).map_err(|e| e.to_string())? {
             skipped += 1;
             continue;
         }
 
-        // Find or create categories
-        let category_id = if let Some(cat_name) = kategori {
+        // Use the profile's mapping when there is one, otherwise find or create categories
+        let mapped_id = mapped_category(&category_mapping, &row);
+        let flag_unmapped = mapped_id.is_none()
+            && row.category.is_some()
+            && !category_mapping.is_empty()
+            && profile.unmapped_categories == UnmappedCategoryMode::Flag;
+        if flag_unmapped {
+            let name = row.category.clone().unwrap_or_default();
+            if !unmapped.contains(&name) {
+                unmapped.push(name);
+            }
+        }
+
+        let category_id = if mapped_id.is_some() || flag_unmapped {
+            mapped_id
+        } else if let Some(cat_name) = &row.category {
             let parent_id =
                 categories::find_or_create(conn, cat_name, None).map_err(|e| e.to_string())?;
-            if let Some(subcat_name) = underkategori {
+            if let Some(subcat_name) = &row.subcategory {
                 Some(
                     categories::find_or_create(conn, subcat_name, Some(parent_id))
                         .map_err(|e| e.to_string())?,
@@ -145,66 +656,176 @@ fn try_import_with_delimiter(
             None
         };
 
+        // Fall back to categorization rules when the bank didn't supply a category
+        let payee = payee_rules::apply(&rules, &row.raw_payee);
+        let rule = (category_id.is_none() && !flag_unmapped)
+            .then(|| categorization_rules::find_match(&category_rules, &payee))
+            .flatten();
+
         // Insert transaction
         let tx = Transaction {
             id: None,
             account_id,
-            category_id,
-            date,
+            category_id: category_id.or(rule.map(|r| r.category_id)),
+            date: row.date,
             payee,
-            amount,
-            balance_snapshot: balance,
-            status,
-            is_reconciled,
-            import_hash: Some(hash),
+            raw_payee: Some(row.raw_payee),
+            memo: row.memo,
+            amount: row.amount,
+            balance_snapshot: row.balance,
+            status: row.status,
+            is_reconciled: row.is_reconciled,
+            import_hash: Some(row.import_hash),
+            subscription_id: None,
+            needs_review: flag_unmapped || rule.is_some_and(categorization_rules::needs_review),
         };
 
         transactions::create(conn, &tx).map_err(|e| e.to_string())?;
         imported += 1;
     }
 
+    let mut warnings = vec![];
+    if !unmapped.is_empty() {
+        warnings.push(format!(
+            "Kategorier uden kortlægning blev importeret uden kategori og markeret til gennemsyn: {}",
+            unmapped.join(", ")
+        ));
+    }
+    if !implausible.is_empty() {
+        warnings.push(format!(
+            "Usandsynligt store beløb, måske læst forkert (fx saldo som beløb eller forkert decimaltegn): {}",
+            implausible.join(", ")
+        ));
+    }
+
     Ok(ImportResult {
         total_rows,
         imported,
         skipped_duplicates: skipped,
+        ambiguous_columns: vec![],
+        skipped_preamble_lines: 0,
+        delimiter_used: char::default(),
+        encoding_used: "UTF-8".to_string(),
+        number_format: NumberFormat::default(),
+        warnings,
     })
 }
 
-fn find_column_index(headers: &csv::StringRecord, names: &[&str]) -> Option<usize> {
-    for (i, header) in headers.iter().enumerate() {
-        let header_lower = header.to_lowercase();
-        for name in names {
-            if header_lower.contains(name) {
-                return Some(i);
-            }
+/// Rows whose amount exceeds the `import_amount_ceiling` setting (øre; 0 turns the check off),
+/// described as "date payee amount" for the import warnings. They are still imported.
+fn implausible_amounts(conn: &Connection, rows: &[ParsedRow]) -> Result<Vec<String>, String> {
+    let ceiling = settings::get_f64(conn, "import_amount_ceiling", DEFAULT_IMPORT_AMOUNT_CEILING)
+        .map_err(|e| e.to_string())?;
+    if ceiling <= 0.0 {
+        return Ok(vec![]);
+    }
+    Ok(rows
+        .iter()
+        .filter(|row| row.amount.unsigned_abs() as f64 > ceiling)
+        .map(|row| format!("{} {} {:.2} kr", row.date, row.raw_payee, row.amount as f64 / 100.0))
+        .collect())
+}
+
+/// The profile's category mapping keyed by lowercase bank name, after checking every id exists
+fn load_category_mapping(conn: &Connection, profile: &ImportProfile) -> Result<HashMap<String, i64>, String> {
+    let mut mapping = HashMap::new();
+    for (name, &category_id) in &profile.category_mapping {
+        if categories::get_by_id(conn, category_id).map_err(|e| e.to_string())?.is_none() {
+            return Err(format!("Kategorien for \"{}\" findes ikke (id {})", name, category_id));
         }
+        mapping.insert(name.trim().to_lowercase(), category_id);
     }
-    None
+    Ok(mapping)
+}
+
+/// Mapped category for a row; a mapped subcategory wins over its parent category
+fn mapped_category(mapping: &HashMap<String, i64>, row: &ParsedRow) -> Option<i64> {
+    [&row.subcategory, &row.category]
+        .into_iter()
+        .flatten()
+        .find_map(|name| mapping.get(&name.to_lowercase()).copied())
 }
 
-/// Parse Danish date format (DD-MM-YYYY, DD/MM/YYYY, DD.MM.YYYY) to ISO8601 (YYYY-MM-DD)
+/// Find the column for a field. An exact header match wins, the earlier keyword first when several
+/// headers match exactly (so "Dato" beats "Bogført"), otherwise the first header containing one of
+/// the keywords. Headers that are an exact match for a different field (e.g. "Underkategori" when
+/// looking for "kategori") are not considered. When several headers remain, the choice is recorded
+/// in `ambiguities`.
+fn find_column_index(
+    headers: &csv::StringRecord,
+    field: &str,
+    names: &[&str],
+    ambiguities: &mut Vec<AmbiguousColumn>,
+) -> Option<usize> {
+    let candidates: Vec<(usize, String)> = headers
+        .iter()
+        .enumerate()
+        .map(|(i, header)| (i, header.to_lowercase()))
+        .filter(|(_, header)| names.iter().any(|name| header.contains(name)))
+        .filter(|(_, header)| {
+            names.contains(&header.as_str())
+                || !FIELDS
+                    .iter()
+                    .filter(|(_, other)| *other != names)
+                    .any(|(_, other)| other.contains(&header.as_str()))
+        })
+        .collect();
+
+    let chosen = candidates
+        .iter()
+        .filter_map(|(i, header)| names.iter().position(|name| name == header).map(|rank| (rank, i)))
+        .min()
+        .map(|(_, i)| i)
+        .or(candidates.first().map(|(i, _)| i))
+        .copied()?;
+
+    if candidates.len() > 1 {
+        ambiguities.push(AmbiguousColumn {
+            field: field.to_string(),
+            candidates: candidates
+                .iter()
+                .map(|(i, _)| headers.get(*i).unwrap_or("").to_string())
+                .collect(),
+            chosen: headers.get(chosen).unwrap_or("").to_string(),
+        });
+    }
+    Some(chosen)
+}
+
+/// Parse Danish date format (DD-MM-YYYY, DD/MM/YYYY, DD.MM.YYYY) or ISO (YYYY-MM-DD) to ISO8601 (YYYY-MM-DD).
+/// A time after the date ("01-03-2024 14:32", "2024-03-01T14:32:00") is ignored.
 fn parse_danish_date(s: &str) -> Result<String, String> {
     let s = s.trim();
     if s.is_empty() {
         return Err("Dato mangler".to_string());
     }
 
+    // Some banks export date and time in one column; only the date is kept
+    let date_part = s.split_once([' ', 'T']).map_or(s, |(date, _)| date);
+
     // Split by common separators
-    let parts: Vec<&str> = s.split(['-', '/', '.'].as_ref()).collect();
+    let parts: Vec<&str> = date_part.split(['-', '/', '.'].as_ref()).collect();
 
     if parts.len() != 3 {
         return Err(format!("Ugyldigt datoformat: {}", s));
     }
 
-    let day: u32 = parts[0]
+    // ISO dates start with the four-digit year
+    let (day_part, month_part, year_part) = if parts[0].len() == 4 {
+        (parts[2], parts[1], parts[0])
+    } else {
+        (parts[0], parts[1], parts[2])
+    };
+
+    let day: u32 = day_part
         .parse()
-        .map_err(|_| format!("Ugyldig dag: {}", parts[0]))?;
-    let month: u32 = parts[1]
+        .map_err(|_| format!("Ugyldig dag: {}", day_part))?;
+    let month: u32 = month_part
         .parse()
-        .map_err(|_| format!("Ugyldig måned: {}", parts[1]))?;
-    let year: u32 = parts[2]
+        .map_err(|_| format!("Ugyldig måned: {}", month_part))?;
+    let year: u32 = year_part
         .parse()
-        .map_err(|_| format!("Ugyldigt år: {}", parts[2]))?;
+        .map_err(|_| format!("Ugyldigt år: {}", year_part))?;
 
     // Basic validation
     if month < 1 || month > 12 {
@@ -217,28 +838,111 @@ fn parse_danish_date(s: &str) -> Result<String, String> {
     Ok(format!("{:04}-{:02}-{:02}", year, month, day))
 }
 
-/// Parse Danish amount format (uses comma as decimal separator) to øre (integer cents)
-fn parse_danish_amount(s: &str) -> Result<i64, String> {
+/// Parse an amount to øre (integer cents). Danish format uses comma as decimal separator, US format a dot.
+fn parse_amount(s: &str, format: NumberFormat) -> Result<i64, String> {
     let s = s.trim();
     if s.is_empty() {
         return Err("Beløb mangler".to_string());
     }
 
-    // Remove thousand separators (.) and replace decimal comma with dot
-    let cleaned = s
-        .replace(".", "") // Remove thousand separators
-        .replace(",", "."); // Convert decimal comma to dot
+    // Remove thousand separators (., or , in US format, and spaces) and make the decimal separator a dot
+    let cleaned = match format {
+        NumberFormat::Danish => s
+            .replace(['.', ' ', '\u{00A0}', '\u{202F}'], "") // Remove thousand separators
+            .replace(",", "."), // Convert decimal comma to dot
+        NumberFormat::Us => s.replace([',', ' ', '\u{00A0}', '\u{202F}'], ""),
+    };
 
-    let amount: f64 = cleaned
-        .parse()
-        .map_err(|_| format!("Ugyldigt beløb: {}", s))?;
+    // Convert to øre from the integer and fractional digits separately, avoiding float rounding errors
+    let invalid = || format!("Ugyldigt beløb: {}", s);
+    let (negative, unsigned) = match cleaned.strip_prefix('-') {
+        Some(rest) => (true, rest),
+        None => (false, cleaned.strip_prefix('+').unwrap_or(&cleaned)),
+    };
+    let (whole, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    let is_digits = |part: &str| part.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(invalid());
+    }
+
+    let digit = |i: usize| fraction.as_bytes().get(i).map_or(0, |b| (b - b'0') as i64);
+    // Digits beyond øre are rounded half away from zero
+    let ore = digit(0) * 10 + digit(1) + i64::from(digit(2) >= 5);
+    let kroner: i64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| invalid())? };
+    let amount = kroner.checked_mul(100).and_then(|a| a.checked_add(ore)).ok_or_else(invalid)?;
+    Ok(if negative { -amount } else { amount })
+}
+
+/// Guess the number format from sample amounts. The last separator in a value is the decimal one
+/// unless it is followed by exactly three digits, which could be either (e.g. "1.234"); those
+/// only count when the separator repeats. Falls back to Danish when the samples don't tell.
+fn detect_number_format(samples: &[String]) -> NumberFormat {
+    let (mut danish, mut us) = (0, 0);
+    for sample in samples {
+        let Some(pos) = sample.rfind(['.', ',']) else {
+            continue;
+        };
+        let separator = sample[pos..].chars().next().unwrap_or('.');
+        let digits_after = sample[pos + 1..].chars().filter(|c| c.is_ascii_digit()).count();
+        let other = if separator == '.' { ',' } else { '.' };
+
+        let decimal = if sample.contains(other) || digits_after != 3 {
+            Some(separator)
+        } else if sample.matches(separator).count() > 1 {
+            Some(other) // Repeated, so it separates thousands
+        } else {
+            None
+        };
+        match decimal {
+            Some(',') => danish += 1,
+            Some(_) => us += 1,
+            None => {}
+        }
+    }
+    if us > danish { NumberFormat::Us } else { NumberFormat::Danish }
+}
+
+/// Warning when the amounts look like they are in another format than they were parsed with,
+/// or when the format is unusual for the account's currency
+fn number_format_warning(
+    conn: &Connection,
+    account_id: i64,
+    format: NumberFormat,
+    detected: NumberFormat,
+) -> Result<Option<String>, String> {
+    let describe = |f: NumberFormat| match f {
+        NumberFormat::Danish => "dansk format (1.234,56)",
+        NumberFormat::Us => "amerikansk format (1,234.56)",
+    };
+    if detected != format {
+        return Ok(Some(format!(
+            "Beløbene ser ud til at være i {}, men er læst i {}. Kontroller beløbene, eller vælg talformatet i importprofilen.",
+            describe(detected),
+            describe(format)
+        )));
+    }
 
-    // Convert to øre (cents) - multiply by 100 and round
-    Ok((amount * 100.0).round() as i64)
+    let Some(account) = accounts::get_by_id(conn, account_id).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let expected = if US_FORMAT_CURRENCIES.contains(&account.currency.to_uppercase().as_str()) {
+        NumberFormat::Us
+    } else {
+        NumberFormat::Danish
+    };
+    if format == expected {
+        return Ok(None);
+    }
+    Ok(Some(format!(
+        "Beløbene er i {}, men kontoen bruger {}, som normalt skrives i {}. Kontroller at beløbene er læst korrekt.",
+        describe(format),
+        account.currency,
+        describe(expected)
+    )))
 }
 
 /// Generate SHA-256 hash of transaction fields for deduplication
-fn generate_import_hash(date: &str, payee: &str, amount: i64, balance: Option<i64>) -> String {
+pub fn generate_import_hash(date: &str, payee: &str, amount: i64, balance: Option<i64>) -> String {
     let mut hasher = Sha256::new();
     hasher.update(date.as_bytes());
     hasher.update(payee.as_bytes());
@@ -249,11 +953,354 @@ fn generate_import_hash(date: &str, payee: &str, amount: i64, balance: Option<i6
     format!("{:x}", hasher.finalize())
 }
 
-fn log_import(conn: &Connection, filename: &str, records_added: usize) -> Result<(), String> {
+fn log_import(
+    conn: &Connection,
+    account_id: i64,
+    filename: &str,
+    records_added: usize,
+    period: Option<(String, String)>,
+) -> Result<(), String> {
+    let (first_date, last_date) = period.unzip();
     conn.execute(
-        "INSERT INTO import_log (filename, records_added) VALUES (?1, ?2)",
-        rusqlite::params![filename, records_added as i64],
+        "INSERT INTO import_log (filename, records_added, account_id, first_date, last_date) VALUES (?1, ?2, ?3, ?4, ?5)",
+        rusqlite::params![filename, records_added as i64, account_id, first_date, last_date],
     )
     .map_err(|e| e.to_string())?;
     Ok(())
 }
+
+/// First and last date of the parsed rows, None for an empty file
+fn date_span(rows: &[ParsedRow]) -> Option<(String, String)> {
+    let first = rows.iter().map(|row| &row.date).min()?;
+    let last = rows.iter().map(|row| &row.date).max()?;
+    Some((first.clone(), last.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::models::Account;
+    use crate::db::schema;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn
+    }
+
+    fn account(conn: &Connection, currency: &str) -> i64 {
+        accounts::create(
+            conn,
+            &Account { name: format!("Konto {}", currency), currency: currency.to_string(), ..Default::default() },
+        )
+        .unwrap()
+    }
+
+    fn amounts(conn: &Connection, account_id: i64) -> Vec<i64> {
+        transactions::get_by_account(conn, account_id, None)
+            .unwrap()
+            .iter()
+            .map(|t| t.transaction.amount)
+            .collect()
+    }
+
+    const US_CSV: &str = "Date;Payee;Amount\n01-03-2024;Whole Foods;-1,234.56\n02-03-2024;Amazon;-12.50\n";
+
+    #[test]
+    fn us_numbers_in_usd_account_with_us_profile_import_cleanly() {
+        let conn = setup();
+        let usd = account(&conn, "USD");
+        let profile = ImportProfile { number_format: Some(NumberFormat::Us), ..Default::default() };
+        let result = commit_csv_import(&conn, US_CSV, usd, "us.csv", &profile).unwrap();
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+        assert_eq!(result.number_format, NumberFormat::Us);
+        let mut parsed = amounts(&conn, usd);
+        parsed.sort();
+        assert_eq!(parsed, vec![-123456, -1250]);
+    }
+
+    #[test]
+    fn us_numbers_in_dkk_account_warn_without_changing_the_format() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        let result = import_csv(&conn, US_CSV, dkk, "us.csv").unwrap();
+        assert_eq!(result.number_format, NumberFormat::Danish);
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("amerikansk format"));
+    }
+
+    #[test]
+    fn ambiguous_thousands_stay_danish() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        let result = import_csv(&conn, "Dato;Tekst;Beløb\n01-03-2024;Husleje;-1.234\n", dkk, "dk.csv").unwrap();
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+        assert_eq!(amounts(&conn, dkk), vec![-123400]);
+    }
+
+    #[test]
+    fn missing_middle_month_is_reported() {
+        let conn = setup();
+        let account_id = account(&conn, "DKK");
+        for month in ["2024-01", "2024-03"] {
+            for day in 1..=10 {
+                conn.execute(
+                    "INSERT INTO transactions (account_id, date, payee, amount) VALUES (?1, ?2, 'Netto', -4500)",
+                    rusqlite::params![account_id, format!("{}-{:02}", month, day)],
+                )
+                .unwrap();
+            }
+        }
+
+        let gaps = detect_gaps(&conn, account_id).unwrap();
+        assert_eq!(gaps.len(), 1);
+        assert_eq!((gaps[0].month.as_str(), gaps[0].transaction_count, gaps[0].median_count), ("2024-02", 0, 10));
+    }
+
+    #[test]
+    fn validation_uses_the_account_and_profile() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        let usd = account(&conn, "USD");
+        let us_profile = ImportProfile { number_format: Some(NumberFormat::Us), ..Default::default() };
+
+        let result = validate_csv(&conn, US_CSV, usd, &us_profile);
+        assert!(result.error.is_none() && result.warnings.is_empty(), "{:?}", result);
+        let result = validate_csv(&conn, US_CSV, dkk, &us_profile);
+        assert_eq!(result.warnings.len(), 1);
+        assert!(result.warnings[0].contains("DKK"));
+
+        let csv = "Dato;Tekst;Beløb i valuta;Beløb\n01-02-2024;Netto;-1,00;-12,50\n";
+        let mut overrides = ImportProfile::default();
+        overrides.column_overrides.insert("amount".to_string(), 2);
+        let result = validate_csv(&conn, csv, dkk, &overrides);
+        assert_eq!(result.column_map.get("amount"), Some(&2));
+    }
+
+    #[test]
+    fn two_amount_columns_are_reported_as_ambiguous() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        let csv = "Dato;Tekst;Beløb i valuta;Beløb\n01-03-2024;Hotel Berlin;-100,00;-745,50\n";
+
+        let result = import_csv(&conn, csv, dkk, "marts.csv").unwrap();
+        assert_eq!(result.ambiguous_columns.len(), 1);
+        let ambiguity = &result.ambiguous_columns[0];
+        assert_eq!(ambiguity.field, "amount");
+        assert_eq!(ambiguity.candidates, ["Beløb i valuta", "Beløb"]);
+        assert_eq!(ambiguity.chosen, "Beløb");
+        assert_eq!(amounts(&conn, dkk), vec![-74550]);
+    }
+
+    #[test]
+    fn text_and_reference_columns_are_both_kept() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        let csv = "Dato;Tekst;Meddelelse;Beløb\n01-03-2024;Overførsel;Husleje marts 2024;-8.500,00\n";
+        import_csv(&conn, csv, dkk, "marts.csv").unwrap();
+
+        let rows = transactions::get_by_account(&conn, dkk, None).unwrap();
+        assert_eq!(rows[0].transaction.payee, "Overførsel");
+        assert_eq!(rows[0].transaction.memo.as_deref(), Some("Husleje marts 2024"));
+        assert!(transactions::search(&conn, dkk, "husleje", false).unwrap().is_empty());
+        assert_eq!(transactions::search(&conn, dkk, "husleje", true).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn preamble_lines_before_the_header_are_skipped() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        let csv = "Kontoudtog for Lønkonto\nKontohaver: Jens Hansen\nEksporteret 01-04-2024\n\
+                   Dato;Tekst;Beløb\n01-03-2024;Netto;-125,00\n02-03-2024;Løn;30.000,00\n";
+
+        let result = import_csv(&conn, csv, dkk, "marts.csv").unwrap();
+        assert_eq!((result.imported, result.skipped_preamble_lines), (2, 3));
+        let mut parsed = amounts(&conn, dkk);
+        parsed.sort();
+        assert_eq!(parsed, vec![-12500, 3000000]);
+    }
+
+    #[test]
+    fn amount_and_fee_columns_are_summed() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        let csv = "Dato;Tekst;Beløb;Gebyr\n01-03-2024;Hæveautomat Berlin;-745,50;-15,00\n02-03-2024;Netto;-125,00;\n";
+        let profile = ImportProfile {
+            amount_columns: vec!["Beløb".to_string(), "gebyr".to_string()],
+            ..Default::default()
+        };
+
+        let result = commit_csv_import(&conn, csv, dkk, "marts.csv", &profile).unwrap();
+        assert_eq!(result.imported, 2);
+        let mut parsed = amounts(&conn, dkk);
+        parsed.sort();
+        assert_eq!(parsed, vec![-76050, -12500]);
+
+        let missing = ImportProfile { amount_columns: vec!["Provision".to_string()], ..Default::default() };
+        assert!(commit_csv_import(&conn, csv, dkk, "marts.csv", &missing).is_err());
+    }
+
+    #[test]
+    fn result_reports_the_delimiter_and_encoding_used() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+
+        let result = import_csv_bytes(&conn, "Date,Payee,Amount\n01-03-2024,Netto,-125\n".as_bytes(), dkk, "a.csv").unwrap();
+        assert_eq!((result.delimiter_used, result.encoding_used.as_str()), (',', "UTF-8"));
+
+        // "Beløb" and "Føtex" in Windows-1252, where ø is the single byte 0xF8
+        let latin1 = b"Dato;Tekst;Bel\xf8b\n02-03-2024;F\xf8tex;-45,00\n";
+        let result = import_csv_bytes(&conn, latin1, dkk, "b.csv").unwrap();
+        assert_eq!((result.delimiter_used, result.encoding_used.as_str()), (';', "windows-1252"));
+        assert_eq!(result.imported, 1);
+    }
+
+    #[test]
+    fn space_thousands_separators_are_removed() {
+        assert_eq!(parse_amount("1 234,56", NumberFormat::Danish), Ok(123456));
+        assert_eq!(parse_amount("1\u{00A0}234,56", NumberFormat::Danish), Ok(123456));
+        assert_eq!(parse_amount("-1\u{00A0}234\u{00A0}567,89", NumberFormat::Danish), Ok(-123456789));
+    }
+
+    #[test]
+    fn mapped_bank_categories_use_the_existing_category() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        let groceries = categories::find_or_create(&conn, "Dagligvarer", None).unwrap();
+        let csv = "Dato;Tekst;Beløb;Kategori\n01-03-2024;Netto;-45,00;SUPERMARKED\n02-03-2024;Kiosk;-20,00;Ukendt\n";
+        let categories_by_payee = |conn: &Connection| -> Vec<(String, Option<String>, bool)> {
+            let mut rows: Vec<_> = transactions::get_by_account(conn, dkk, None)
+                .unwrap()
+                .into_iter()
+                .map(|t| (t.transaction.payee, t.category_name, t.transaction.needs_review))
+                .collect();
+            rows.sort();
+            rows
+        };
+
+        let mut profile = ImportProfile {
+            category_mapping: HashMap::from([("Supermarked".to_string(), groceries)]),
+            ..Default::default()
+        };
+        let result = commit_csv_import(&conn, csv, dkk, "mapped.csv", &profile).unwrap();
+        assert!(result.warnings.is_empty(), "{:?}", result.warnings);
+        assert_eq!(
+            categories_by_payee(&conn),
+            [
+                ("Kiosk".to_string(), Some("Ukendt".to_string()), false),
+                ("Netto".to_string(), Some("Dagligvarer".to_string()), false),
+            ]
+        );
+
+        let flagged = account(&conn, "EUR");
+        profile.unmapped_categories = UnmappedCategoryMode::Flag;
+        let result = commit_csv_import(&conn, &csv.replace("Kiosk", "Bager"), flagged, "flag.csv", &profile).unwrap();
+        assert_eq!(result.warnings.len(), 1);
+        let bakery = transactions::get_by_account(&conn, flagged, None)
+            .unwrap()
+            .into_iter()
+            .find(|t| t.transaction.payee == "Bager")
+            .unwrap();
+        assert_eq!((bakery.transaction.category_id, bakery.transaction.needs_review), (None, true));
+
+        profile.category_mapping.insert("Ukendt".to_string(), 999);
+        assert!(commit_csv_import(&conn, csv, dkk, "missing.csv", &profile).is_err());
+    }
+
+    #[test]
+    fn amounts_are_parsed_exactly() {
+        // 1.005 * 100.0 and friends come out just below .5 as floats and round down
+        for (input, expected) in [
+            ("1,005", 101),
+            ("1,015", 102),
+            ("8,675", 868),
+            ("-2,675", -268),
+            ("12.345.678.901.234,56", 1_234_567_890_123_456),
+            ("0,29", 29),
+            ("5", 500),
+            ("5,5", 550),
+            (",07", 7),
+            ("1,2349", 123),
+        ] {
+            assert_eq!(parse_amount(input, NumberFormat::Danish), Ok(expected), "{}", input);
+        }
+        assert_eq!(parse_amount("1.005", NumberFormat::Us), Ok(101));
+        assert!(parse_amount("1,2,3", NumberFormat::Danish).is_err());
+        assert!(parse_amount("-", NumberFormat::Danish).is_err());
+    }
+
+    #[test]
+    fn booking_date_column_with_time_is_read_as_the_date() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        let dates = |conn: &Connection, account_id: i64| -> Vec<String> {
+            transactions::get_by_account(conn, account_id, None)
+                .unwrap()
+                .into_iter()
+                .map(|t| t.transaction.date)
+                .collect()
+        };
+
+        let csv = "Bogført;Tekst;Beløb\n2024-03-01 14:32;Netto;-45,00\n";
+        commit_csv_import(&conn, csv, dkk, "bogfort.csv", &ImportProfile::default()).unwrap();
+        assert_eq!(dates(&conn, dkk), ["2024-03-01"]);
+
+        // An exact "Dato" header wins over "Bogført"
+        let both = account(&conn, "EUR");
+        let csv = "Bogført;Dato;Tekst;Beløb\n2024-03-05 09:15;04-03-2024;Irma;-30,00\n";
+        let result = commit_csv_import(&conn, csv, both, "begge.csv", &ImportProfile::default()).unwrap();
+        assert_eq!(result.imported, 1);
+        assert_eq!(dates(&conn, both), ["2024-03-04"]);
+    }
+
+    #[test]
+    fn implausible_amounts_warn_but_are_still_imported() {
+        let conn = setup();
+        let dkk = account(&conn, "DKK");
+        // The dot in "-2500000.00" is read as a Danish thousands separator: 250 mio. kr
+        let csv = "Dato;Tekst;Beløb\n01-03-2024;Netto;-45,00\n02-03-2024;Husleje;-2500000.00\n03-03-2024;Irma;-30,00\n";
+        let result = commit_csv_import(&conn, csv, dkk, "stor.csv", &ImportProfile::default()).unwrap();
+        assert_eq!(result.imported, 3);
+        assert!(
+            result.warnings.iter().any(|w| w.starts_with("Usandsynligt") && w.contains("2024-03-02 Husleje -250000000.00 kr")),
+            "{:?}",
+            result.warnings
+        );
+        let mut parsed = amounts(&conn, dkk);
+        parsed.sort();
+        assert_eq!(parsed, vec![-25_000_000_000, -4500, -3000]);
+
+        // A ceiling of zero turns the check off
+        let other = account(&conn, "EUR");
+        settings::set_setting(&conn, "import_amount_ceiling", &serde_json::json!(0)).unwrap();
+        let result = commit_csv_import(&conn, csv, other, "stor.csv", &ImportProfile::default()).unwrap();
+        assert!(!result.warnings.iter().any(|w| w.starts_with("Usandsynligt")), "{:?}", result.warnings);
+    }
+
+    #[test]
+    fn months_inside_one_imported_statement_are_not_gaps() {
+        let conn = setup();
+        let csv = |payee: &str, months: &[&str]| {
+            let mut csv = "Dato;Tekst;Beløb\n".to_string();
+            for month in months {
+                for day in 1..=10 {
+                    csv.push_str(&format!("{:02}-{}-2024;{};-45,00\n", day, month, payee));
+                }
+            }
+            csv
+        };
+
+        let separate = account(&conn, "DKK");
+        commit_csv_import(&conn, &csv("Netto", &["01"]), separate, "januar.csv", &ImportProfile::default()).unwrap();
+        commit_csv_import(&conn, &csv("Netto", &["03"]), separate, "marts.csv", &ImportProfile::default()).unwrap();
+        let gaps = detect_gaps(&conn, separate).unwrap();
+        assert_eq!(gaps.iter().map(|g| g.month.as_str()).collect::<Vec<_>>(), ["2024-02"]);
+
+        // A single statement running from January to March shows February was imported, just quiet
+        let combined = account(&conn, "EUR");
+        let result =
+            commit_csv_import(&conn, &csv("Irma", &["01", "03"]), combined, "kvartal.csv", &ImportProfile::default()).unwrap();
+        assert_eq!(result.imported, 20);
+        assert!(detect_gaps(&conn, combined).unwrap().is_empty());
+    }
+}
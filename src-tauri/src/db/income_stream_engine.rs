@@ -0,0 +1,101 @@
+use rusqlite::{params, Connection};
+use super::models::PaycheckPattern;
+use super::subscription_engine::{calculate_intervals, detect_frequency, normalize_payee};
+use chrono::{Datelike, NaiveDate};
+use std::collections::HashMap;
+
+/// (id, payee, amount, date)
+type IncomeRow = (i64, String, i64, String);
+
+/// Days either side of the typical pay day that still count as "on time"
+const PAYDAY_TOLERANCE_DAYS: u32 = 3;
+
+/// Find the most regular monthly income on an account (usually salary) and how
+/// consistently it lands on the same day of the month
+pub fn get_paycheck_pattern(conn: &Connection, account_id: i64) -> Result<Option<PaycheckPattern>, rusqlite::Error> {
+    // 1. Get all incoming transactions for the account
+    let mut stmt = conn.prepare(
+        r#"SELECT id, payee, amount, date
+           FROM transactions
+           WHERE account_id = ?1 AND amount > 0
+           ORDER BY date ASC"#,
+    )?;
+    let transactions: Vec<IncomeRow> = stmt
+        .query_map(params![account_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // 2. Group by normalized payee only - salaries vary slightly from month to month
+    let mut groups: HashMap<String, Vec<IncomeRow>> = HashMap::new();
+    for tx in transactions {
+        groups.entry(normalize_payee(&tx.1)).or_default().push(tx);
+    }
+
+    // 3. Pick the monthly pattern with the highest confidence (larger total breaks ties)
+    let mut best: Option<(f64, i64, Vec<IncomeRow>)> = None;
+    for (_, occurrences) in groups {
+        if occurrences.len() < 2 {
+            continue;
+        }
+        let dated: Vec<(i64, String)> = occurrences.iter().map(|(id, _, _, date)| (*id, date.clone())).collect();
+        let Some((frequency, confidence)) = detect_frequency(&calculate_intervals(&dated)) else {
+            continue;
+        };
+        if frequency != "monthly" {
+            continue;
+        }
+        let total: i64 = occurrences.iter().map(|(_, _, amount, _)| amount).sum();
+        let better = match &best {
+            Some((best_conf, best_total, _)) => {
+                confidence > *best_conf || (confidence == *best_conf && total > *best_total)
+            }
+            None => true,
+        };
+        if better {
+            best = Some((confidence, total, occurrences));
+        }
+    }
+
+    let Some((_, _, occurrences)) = best else {
+        return Ok(None);
+    };
+
+    // 4. Work out the typical pay day and how many payments landed near it
+    let days: Vec<u32> = occurrences
+        .iter()
+        .filter_map(|(_, _, _, date)| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok())
+        .map(|d| d.day())
+        .collect();
+    let typical_day_of_month = median(days.iter().map(|&d| d as i64).collect()) as u32;
+    let on_time = days
+        .iter()
+        .filter(|&&day| day_distance(day, typical_day_of_month) <= PAYDAY_TOLERANCE_DAYS)
+        .count();
+    let consistency_score = if days.is_empty() { 0.0 } else { on_time as f64 / days.len() as f64 };
+
+    let typical_amount = median(occurrences.iter().map(|(_, _, amount, _)| *amount).collect());
+    let (_, payee, _, last_received) = occurrences.last().cloned().unwrap_or_default();
+
+    Ok(Some(PaycheckPattern {
+        payee,
+        typical_amount,
+        typical_day_of_month,
+        consistency_score,
+        last_received,
+    }))
+}
+
+/// Distance between two days of the month, wrapping around month-end (e.g. the 30th and the 1st are close)
+fn day_distance(a: u32, b: u32) -> u32 {
+    let diff = a.abs_diff(b);
+    diff.min(31 - diff.min(31))
+}
+
+fn median(mut values: Vec<i64>) -> i64 {
+    if values.is_empty() {
+        return 0;
+    }
+    values.sort();
+    values[values.len() / 2]
+}
@@ -1,17 +1,21 @@
 use rusqlite::{params, Connection, OptionalExtension};
-use super::models::IncomeStream;
+use std::collections::HashMap;
+use super::models::{IncomeStream, IncomeStreamHistoryEntry, IncomeStreamWithLastReceived, MonthCoverage, Transaction, UpcomingIncome};
+use super::{reports, transactions};
+use chrono::{Duration, Months, NaiveDate};
 
 pub fn create(conn: &Connection, stream: &IncomeStream) -> Result<i64, rusqlite::Error> {
+    validate(stream)?;
     conn.execute(
-        "INSERT INTO income_streams (name, expected_amount, frequency, category_id, is_active) 
-         VALUES (?1, ?2, ?3, ?4, ?5)",
-        params![stream.name, stream.expected_amount, stream.frequency, stream.category_id, stream.is_active as i32],
+        "INSERT INTO income_streams (name, expected_amount, frequency, category_id, is_active, direction, anchor_date) 
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+        params![stream.name, stream.expected_amount, stream.frequency, stream.category_id, stream.is_active as i32, stream.direction, stream.anchor_date],
     )?;
     Ok(conn.last_insert_rowid())
 }
 
 pub fn get_all(conn: &Connection) -> Result<Vec<IncomeStream>, rusqlite::Error> {
-    let mut stmt = conn.prepare("SELECT id, name, expected_amount, frequency, category_id, is_active FROM income_streams ORDER BY name")?;
+    let mut stmt = conn.prepare("SELECT id, name, expected_amount, frequency, category_id, is_active, direction, anchor_date FROM income_streams ORDER BY name")?;
     let rows = stmt.query_map([], |row| {
         Ok(IncomeStream {
             id: Some(row.get(0)?),
@@ -20,18 +24,376 @@ pub fn get_all(conn: &Connection) -> Result<Vec<IncomeStream>, rusqlite::Error>
             frequency: row.get(3)?,
             category_id: row.get(4)?,
             is_active: row.get::<_, i32>(5)? != 0,
+            direction: row.get(6)?,
+            anchor_date: row.get(7)?,
         })
     })?;
     rows.collect()
 }
 
-pub fn update(conn: &Connection, stream: &IncomeStream) -> Result<usize, rusqlite::Error> {
-    conn.execute(
-        "UPDATE income_streams SET name = ?1, expected_amount = ?2, frequency = ?3, category_id = ?4, is_active = ?5 WHERE id = ?6",
-        params![stream.name, stream.expected_amount, stream.frequency, stream.category_id, stream.is_active as i32, stream.id],
+pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<IncomeStream>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT id, name, expected_amount, frequency, category_id, is_active, direction, anchor_date FROM income_streams WHERE id = ?1",
+        params![id],
+        |row| {
+            Ok(IncomeStream {
+                id: Some(row.get(0)?),
+                name: row.get(1)?,
+                expected_amount: row.get(2)?,
+                frequency: row.get(3)?,
+                category_id: row.get(4)?,
+                is_active: row.get::<_, i32>(5)? != 0,
+                direction: row.get(6)?,
+                anchor_date: row.get(7)?,
+            })
+        },
     )
+    .optional()
+}
+
+/// Update a stream. When the expected amount changes, the previous amount is kept in the history.
+pub fn update(conn: &Connection, stream: &IncomeStream) -> Result<usize, rusqlite::Error> {
+    validate(stream)?;
+    let db_tx = conn.unchecked_transaction()?;
+    db_tx.execute(
+        "INSERT INTO income_stream_history (stream_id, expected_amount)
+         SELECT id, expected_amount FROM income_streams WHERE id = ?1 AND expected_amount != ?2",
+        params![stream.id, stream.expected_amount],
+    )?;
+    let updated = db_tx.execute(
+        "UPDATE income_streams SET name = ?1, expected_amount = ?2, frequency = ?3, category_id = ?4, is_active = ?5, direction = ?6, anchor_date = ?7 WHERE id = ?8",
+        params![stream.name, stream.expected_amount, stream.frequency, stream.category_id, stream.is_active as i32, stream.direction, stream.anchor_date, stream.id],
+    )?;
+    db_tx.commit()?;
+    Ok(updated)
+}
+
+/// Earlier expected amounts of a stream, most recent change first
+pub fn get_amount_history(conn: &Connection, stream_id: i64) -> Result<Vec<IncomeStreamHistoryEntry>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, stream_id, expected_amount, changed_at FROM income_stream_history
+         WHERE stream_id = ?1 ORDER BY changed_at DESC, id DESC",
+    )?;
+    let rows = stmt.query_map(params![stream_id], |row| {
+        Ok(IncomeStreamHistoryEntry {
+            id: row.get(0)?,
+            stream_id: row.get(1)?,
+            expected_amount: row.get(2)?,
+            changed_at: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Percentage change from the stream's first recorded amount to its current one,
+/// or None if the amount has never been changed
+pub fn get_income_growth_from_streams(conn: &Connection, stream_id: i64) -> Result<Option<f64>, rusqlite::Error> {
+    let stream = get_by_id(conn, stream_id)?
+        .ok_or_else(|| rusqlite::Error::InvalidParameterName("Income stream not found".to_string()))?;
+    let Some(first) = get_amount_history(conn, stream_id)?.last().map(|entry| entry.expected_amount) else {
+        return Ok(None);
+    };
+    if first == 0 {
+        return Ok(None);
+    }
+    Ok(Some((stream.expected_amount - first) as f64 / first.abs() as f64 * 100.0))
+}
+
+/// Inflows expect a positive amount and outflows a negative one; an anchor date must be YYYY-MM-DD
+fn validate(stream: &IncomeStream) -> Result<(), rusqlite::Error> {
+    let consistent = match stream.direction.as_str() {
+        "inflow" => stream.expected_amount >= 0,
+        "outflow" => stream.expected_amount <= 0,
+        other => {
+            return Err(rusqlite::Error::InvalidParameterName(format!(
+                "Invalid direction '{}', expected 'inflow' or 'outflow'",
+                other
+            )))
+        }
+    };
+    if !consistent {
+        return Err(rusqlite::Error::InvalidParameterName(format!(
+            "An {} stream needs a {} expected amount",
+            stream.direction,
+            if stream.direction == "inflow" { "positive" } else { "negative" }
+        )));
+    }
+    if let Some(anchor) = &stream.anchor_date {
+        NaiveDate::parse_from_str(anchor, "%Y-%m-%d")
+            .map_err(|_| rusqlite::Error::InvalidParameterName(format!("Invalid anchor date '{}', expected YYYY-MM-DD", anchor)))?;
+    }
+    Ok(())
+}
+
+/// 1 for inflows and -1 for outflows, so `amount * sign > 0` matches transactions in the stream's direction
+fn direction_sign(stream: &IncomeStream) -> i64 {
+    if stream.direction == "outflow" { -1 } else { 1 }
 }
 
 pub fn delete(conn: &Connection, id: i64) -> Result<usize, rusqlite::Error> {
     conn.execute("DELETE FROM income_streams WHERE id = ?1", params![id])
 }
+
+/// Convert an amount at the given frequency into its rough monthly equivalent
+pub fn monthly_equivalent(amount: i64, frequency: &str) -> i64 {
+    match frequency {
+        "weekly" => (amount as f64 * 4.33).round() as i64,
+        "biweekly" => (amount as f64 * 2.17).round() as i64,
+        "quarterly" => amount / 3,
+        "yearly" => amount / 12,
+        _ => amount,
+    }
+}
+
+/// Total expected monthly income across all active inflow streams
+pub fn get_expected_monthly_total(conn: &Connection) -> Result<i64, rusqlite::Error> {
+    Ok(get_all(conn)?
+        .iter()
+        .filter(|s| s.is_active && s.direction == "inflow")
+        .map(|s| monthly_equivalent(s.expected_amount, &s.frequency))
+        .sum())
+}
+
+/// Find the most recent transaction matching a stream: incoming for inflows, outgoing for outflows.
+/// Matches on the stream's category when set, otherwise on an amount within 10% of expected.
+pub fn get_last_received(conn: &Connection, stream_id: i64) -> Result<Option<Transaction>, rusqlite::Error> {
+    match get_by_id(conn, stream_id)? {
+        Some(stream) => find_last_received(conn, &stream),
+        None => Ok(None),
+    }
+}
+
+/// Pair every income stream with its most recently received transaction, as found by `get_last_received`
+pub fn get_all_with_last_received(conn: &Connection) -> Result<Vec<IncomeStreamWithLastReceived>, rusqlite::Error> {
+    get_all(conn)?
+        .into_iter()
+        .map(|stream| {
+            let last_received = get_last_received(conn, stream.id.unwrap_or_default())?;
+            Ok(IncomeStreamWithLastReceived { stream, last_received })
+        })
+        .collect()
+}
+
+/// Expected deposits from active inflow streams in the next `days_ahead` days, sorted by date
+pub fn next_occurrences(conn: &Connection, days_ahead: i64) -> Result<Vec<UpcomingIncome>, rusqlite::Error> {
+    next_occurrences_from(conn, reports::current_date(conn)?, days_ahead)
+}
+
+/// Same as `next_occurrences`, counting the window from `today`.
+/// Dates are projected from the stream's anchor date when set, otherwise from the last matching
+/// transaction. Months are stepped on the calendar from the anchor day, so a salary on the 31st
+/// lands on the last day of shorter months. Streams with neither can't be projected and are left out;
+/// a frequency that can't be projected is an error rather than a silently missing stream.
+pub fn next_occurrences_from(
+    conn: &Connection,
+    today: NaiveDate,
+    days_ahead: i64,
+) -> Result<Vec<UpcomingIncome>, rusqlite::Error> {
+    let end = today + Duration::days(days_ahead);
+
+    let mut upcoming = Vec::new();
+    for entry in get_all_with_last_received(conn)? {
+        let stream = entry.stream;
+        if !stream.is_active || stream.direction != "inflow" {
+            continue;
+        }
+        // An anchor is itself an expected deposit; a received transaction is already in the past
+        let (anchor, first_step) = match (&stream.anchor_date, &entry.last_received) {
+            (Some(anchor), _) => (anchor.as_str(), 0),
+            (None, Some(last)) => (last.date.as_str(), 1),
+            (None, None) => continue,
+        };
+        let anchor = NaiveDate::parse_from_str(anchor, "%Y-%m-%d")
+            .map_err(|_| rusqlite::Error::InvalidParameterName(format!("Invalid date '{}' for income stream '{}'", anchor, stream.name)))?;
+
+        for step in first_step.. {
+            let date = occurrence(anchor, &stream.frequency, step).ok_or_else(|| {
+                rusqlite::Error::InvalidParameterName(format!(
+                    "Can't project income stream '{}' with frequency '{}'",
+                    stream.name, stream.frequency
+                ))
+            })?;
+            if date > end {
+                break;
+            }
+            if date >= today {
+                upcoming.push(UpcomingIncome {
+                    date: date.format("%Y-%m-%d").to_string(),
+                    amount: stream.expected_amount,
+                    name: stream.name.clone(),
+                    stream_id: stream.id.unwrap_or_default(),
+                });
+            }
+        }
+    }
+
+    upcoming.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name)));
+    Ok(upcoming)
+}
+
+/// The `step`-th occurrence after `anchor`. Month-based frequencies count calendar months from the
+/// anchor itself, so clamping to a short month doesn't carry over into the following ones.
+fn occurrence(anchor: NaiveDate, frequency: &str, step: u32) -> Option<NaiveDate> {
+    match frequency {
+        "weekly" => Some(anchor + Duration::weeks(step as i64)),
+        "biweekly" => Some(anchor + Duration::weeks(2 * step as i64)),
+        "monthly" => anchor.checked_add_months(Months::new(step)),
+        "quarterly" => anchor.checked_add_months(Months::new(3 * step)),
+        "yearly" => anchor.checked_add_months(Months::new(12 * step)),
+        _ => None,
+    }
+}
+
+/// For each month of `year`, whether a transaction matching the stream arrived and how much.
+/// Matching follows `get_last_received`: the stream's category when set, otherwise an amount within 10%.
+/// Outflow streams match outgoing transactions, with the amounts kept negative.
+pub fn get_monthly_coverage(conn: &Connection, stream_id: i64, year: i32) -> Result<Vec<MonthCoverage>, rusqlite::Error> {
+    let stream = get_by_id(conn, stream_id)?
+        .ok_or_else(|| rusqlite::Error::InvalidParameterName("Income stream not found".to_string()))?;
+
+    let mut stmt = conn.prepare(
+        "SELECT substr(date, 1, 7) as month, SUM(amount) FROM transactions
+         WHERE amount * ?4 > 0 AND strftime('%Y', date) = ?1
+           AND ((?2 IS NOT NULL AND category_id = ?2)
+             OR (?2 IS NULL AND ?3 != 0 AND ABS(amount - ?3) * 1.0 / ABS(?3) < 0.1))
+         GROUP BY month",
+    )?;
+    let sign = direction_sign(&stream);
+    let received: HashMap<String, i64> = stmt
+        .query_map(
+            params![format!("{:04}", year), stream.category_id, stream.expected_amount, sign],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?
+        .collect::<Result<_, _>>()?;
+
+    Ok((1..=12)
+        .map(|m| {
+            let month = format!("{:04}-{:02}", year, m);
+            let actual_amount = received.get(&month).copied().filter(|amount| amount * sign > 0);
+            MonthCoverage {
+                received: actual_amount.is_some(),
+                actual_amount,
+                expected_amount: stream.expected_amount,
+                month,
+            }
+        })
+        .collect())
+}
+
+fn find_last_received(conn: &Connection, stream: &IncomeStream) -> Result<Option<Transaction>, rusqlite::Error> {
+    let sign = direction_sign(stream);
+    let tx_id: Option<i64> = if let Some(category_id) = stream.category_id {
+        conn.query_row(
+            "SELECT id FROM transactions WHERE amount * ?2 > 0 AND category_id = ?1 ORDER BY date DESC, id DESC LIMIT 1",
+            params![category_id, sign],
+            |row| row.get(0),
+        )
+        .optional()?
+    } else if stream.expected_amount != 0 {
+        conn.query_row(
+            "SELECT id FROM transactions
+             WHERE amount * ?2 > 0 AND ABS(amount - ?1) * 1.0 / ABS(?1) < 0.1
+             ORDER BY date DESC, id DESC LIMIT 1",
+            params![stream.expected_amount, sign],
+            |row| row.get(0),
+        )
+        .optional()?
+    } else {
+        None
+    };
+
+    match tx_id {
+        Some(id) => transactions::get_by_id(conn, id),
+        None => Ok(None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        conn
+    }
+
+    fn stream(name: &str, frequency: &str, anchor_date: Option<&str>) -> IncomeStream {
+        IncomeStream {
+            id: None,
+            name: name.to_string(),
+            expected_amount: 3_000_000,
+            frequency: frequency.to_string(),
+            category_id: None,
+            is_active: true,
+            direction: "inflow".to_string(),
+            anchor_date: anchor_date.map(str::to_string),
+        }
+    }
+
+    fn dates(upcoming: &[UpcomingIncome]) -> Vec<&str> {
+        upcoming.iter().map(|u| u.date.as_str()).collect()
+    }
+
+    fn day(date: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(date, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn monthly_salary_stays_on_its_day_of_the_month() {
+        let conn = setup();
+        create(&conn, &stream("Løn", "monthly", None)).unwrap();
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-01-25', 'Arbejdsgiver A/S', 3000000)",
+            [],
+        )
+        .unwrap();
+
+        let upcoming = next_occurrences_from(&conn, day("2024-02-01"), 120).unwrap();
+        assert_eq!(dates(&upcoming), ["2024-02-25", "2024-03-25", "2024-04-25", "2024-05-25"]);
+        assert!(upcoming.iter().all(|u| u.amount == 3_000_000 && u.name == "Løn"));
+    }
+
+    #[test]
+    fn anchor_date_is_used_and_clamped_to_short_months() {
+        let conn = setup();
+        create(&conn, &stream("Løn", "monthly", Some("2024-01-31"))).unwrap();
+
+        let upcoming = next_occurrences_from(&conn, day("2024-01-01"), 100).unwrap();
+        assert_eq!(dates(&upcoming), ["2024-01-31", "2024-02-29", "2024-03-31"]);
+    }
+
+    #[test]
+    fn quarterly_streams_are_projected_and_unknown_frequencies_fail() {
+        let conn = setup();
+        create(&conn, &stream("Udbytte", "quarterly", Some("2024-03-15"))).unwrap();
+        let upcoming = next_occurrences_from(&conn, day("2024-04-01"), 200).unwrap();
+        assert_eq!(dates(&upcoming), ["2024-06-15", "2024-09-15"]);
+
+        create(&conn, &stream("Honorar", "fortnightly", Some("2024-04-05"))).unwrap();
+        assert!(next_occurrences_from(&conn, day("2024-04-01"), 200).is_err());
+    }
+
+    #[test]
+    fn loan_payment_stream_matches_outgoing_transactions() {
+        let conn = setup();
+        let loan = IncomeStream { expected_amount: -250_000, direction: "outflow".to_string(), ..stream("Billån", "monthly", None) };
+        assert!(create(&conn, &IncomeStream { expected_amount: 250_000, ..loan.clone() }).is_err());
+        let id = create(&conn, &loan).unwrap();
+        conn.execute_batch(
+            r#"INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-02-01', 'Nordea Billån', -250000);
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-02-15', 'Tilbagebetaling', 250000);"#,
+        )
+        .unwrap();
+
+        let last = get_all_with_last_received(&conn).unwrap().remove(0).last_received.unwrap();
+        assert_eq!((last.date.as_str(), last.amount), ("2024-02-01", -250_000));
+
+        let coverage = get_monthly_coverage(&conn, id, 2024).unwrap();
+        assert!(coverage[1].received);
+        assert_eq!(coverage[1].actual_amount, Some(-250_000));
+        assert!(!coverage[2].received);
+    }
+}
@@ -4,10 +4,21 @@ pub mod accounts;
 pub mod categories;
 pub mod transactions;
 pub mod import;
+pub mod export;
 pub mod subscriptions;
 pub mod subscription_engine;
+pub mod income_stream_engine;
 pub mod budgets;
 pub mod income_streams;
+pub mod reports;
+pub mod payee_rules;
+pub mod categorization_rules;
+pub mod settings;
+pub mod transfers;
+pub mod summary;
+pub mod savings_goals;
+pub mod reconciliation;
+pub mod audit;
 
 use rusqlite::Connection;
 use std::path::PathBuf;
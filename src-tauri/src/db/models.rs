@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Account {
@@ -6,6 +7,14 @@ pub struct Account {
     pub name: String,
     pub account_number: Option<String>,
     pub currency: String,
+    #[serde(default = "default_account_type")]
+    pub account_type: String,      // 'checking', 'savings', 'credit', ...
+    #[serde(default)]
+    pub credit_limit: Option<i64>, // Only meaningful for 'credit' accounts
+    #[serde(default = "default_account_color")]
+    pub color: String,
+    #[serde(default)]
+    pub icon: Option<String>,
 }
 
 impl Default for Account {
@@ -15,15 +24,72 @@ impl Default for Account {
             name: String::new(),
             account_number: None,
             currency: "DKK".to_string(),
+            account_type: default_account_type(),
+            credit_limit: None,
+            color: default_account_color(),
+            icon: None,
         }
     }
 }
 
+fn default_account_type() -> String {
+    "checking".to_string()
+}
+
+fn default_account_color() -> String {
+    "#6366f1".to_string()
+}
+
+/// Balance of a credit account relative to its limit. `current_balance` is negative while money is owed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreditUtilization {
+    pub account: Account,
+    pub current_balance: i64,
+    pub credit_limit: i64,
+    pub utilization_pct: f64,
+    pub available_credit: i64,
+    pub is_high_utilization: bool,  // Above 30%
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Category {
     pub id: Option<i64>,
     pub name: String,
     pub parent_id: Option<i64>,
+    #[serde(default = "default_spending_type")]
+    pub spending_type: String,     // 'essential', 'discretionary', 'savings'
+    #[serde(default)]
+    pub is_hidden: bool,           // Left out of pickers, still counted in reports
+}
+
+/// What deleting a category removed, so the deletion can be undone.
+/// Handed to the frontend as an opaque string by `categories::delete`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorySnapshot {
+    pub categories: Vec<Category>,           // The category and its subcategories, parents first
+    pub transactions: Vec<(i64, i64)>,       // (transaction_id, category_id) before deletion
+    pub budget_links: Vec<(i64, i64)>,       // (budget_id, category_id)
+    pub rules: Vec<CategorizationRule>,
+}
+
+/// A category and how deep it sits in the hierarchy (0 = top-level)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryWithDepth {
+    pub category: Category,
+    pub depth: u32,
+}
+
+/// Categories sharing a name under the same parent, with how many transactions use each
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DuplicateCategoryGroup {
+    pub name: String,
+    pub parent_id: Option<i64>,
+    pub category_ids: Vec<i64>,
+    pub transaction_counts: Vec<i64>,  // Same order as category_ids
+}
+
+fn default_spending_type() -> String {
+    "discretionary".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,11 +99,120 @@ pub struct Transaction {
     pub category_id: Option<i64>,
     pub date: String,              // ISO8601: YYYY-MM-DD
     pub payee: String,
+    #[serde(default)]
+    pub raw_payee: Option<String>, // Payee before cleanup rules were applied
+    #[serde(default)]
+    pub memo: Option<String>,      // Longer message/reference text from the bank
     pub amount: i64,               // In øre (cents)
     pub balance_snapshot: Option<i64>,
     pub status: Option<String>,
     pub is_reconciled: bool,
     pub import_hash: Option<String>,
+    #[serde(default)]
+    pub subscription_id: Option<i64>,
+    #[serde(default)]
+    pub needs_review: bool,        // Auto-categorized by a low-confidence rule
+}
+
+/// Regex replacement applied to payees at import (capture groups like `$1` are allowed)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayeeRule {
+    pub id: Option<i64>,
+    pub pattern: String,
+    pub replacement: String,
+    pub order: i64,
+}
+
+/// Assigns a category to transactions whose payee matches the pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorizationRule {
+    pub id: Option<i64>,
+    pub pattern: String,
+    pub category_id: i64,
+    #[serde(default)]
+    pub is_regex: bool,
+    #[serde(default)]
+    pub priority: i64,
+    pub metadata: Option<String>,
+}
+
+/// Outcome of turning category history into categorization rules
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationResult {
+    pub rules_created: usize,
+    pub payees_analyzed: usize,
+}
+
+/// Outcome of running the categorization rules over existing transactions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApplyRulesResult {
+    pub transactions_categorized: usize,
+    pub transactions_unchanged: usize,
+    pub rules_applied: HashMap<i64, usize>,    // Rule ID -> number of transactions it matched
+}
+
+/// A transaction whose recomputed import hash already belongs to `duplicate_of`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HashCollision {
+    pub transaction_id: i64,
+    pub duplicate_of: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecomputeHashesResult {
+    pub updated: usize,
+    pub unchanged: usize,
+    pub collisions: Vec<HashCollision>,
+}
+
+/// A recorded transfer: the outflow on one account and the matching inflow on another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Transfer {
+    pub id: Option<i64>,
+    pub debit_transaction_id: i64,
+    pub credit_transaction_id: i64,
+}
+
+/// An amount to have saved by a date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsGoal {
+    pub id: Option<i64>,
+    pub name: String,
+    pub target_amount: i64,
+    pub target_date: String,
+    #[serde(default)]
+    pub current_amount: i64,       // Saved towards the goal so far
+    #[serde(default)]
+    pub account_id: Option<i64>,   // None counts savings across all accounts
+}
+
+/// What it takes to reach a savings goal on time and where the money could come from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsPlan {
+    pub goal: SavingsGoal,
+    pub required_monthly_savings: i64,
+    pub current_monthly_savings: i64,
+    pub monthly_gap: i64,          // 0 when current savings already cover the requirement
+    pub suggested_cuts: Vec<CategoryCutSuggestion>,
+}
+
+/// A discretionary category to spend less on, with the share of its monthly spending to cut
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryCutSuggestion {
+    pub category_name: String,
+    pub current_monthly: i64,
+    pub suggested_reduction_pct: f64,
+    pub estimated_savings: i64,
+}
+
+/// Both legs of a recorded transfer. `is_balanced` is false when the amounts aren't equal and
+/// opposite, the legs are on the same account, or they are more than 3 business days apart.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransferReconciliation {
+    pub debit: TransactionWithCategory,
+    pub credit: TransactionWithCategory,
+    pub is_balanced: bool,
+    pub date_difference_days: i64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -55,6 +230,29 @@ pub struct TransactionWithCategory {
     pub transaction: Transaction,
     pub category_name: Option<String>,
     pub parent_category_name: Option<String>,
+    #[serde(default)]
+    pub matched_subscription_id: Option<i64>,           // Saved subscription this looks like a charge of
+    #[serde(default)]
+    pub matched_subscription_pattern: Option<String>,   // Its normalized payee pattern; only filled in when subscriptions are tagged
+}
+
+/// Transaction with category information and the name of its account, for cross-account views
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionWithAccount {
+    #[serde(flatten)]
+    pub transaction: TransactionWithCategory,
+    pub account_name: String,
+}
+
+/// One recorded edit to a transaction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditLogEntry {
+    pub id: i64,
+    pub transaction_id: i64,
+    pub field_changed: String,     // 'category_id' or 'payee'
+    pub old_value: Option<String>,
+    pub new_value: Option<String>,
+    pub changed_at: String,
 }
 
 /// Result of a CSV import operation
@@ -63,6 +261,292 @@ pub struct ImportResult {
     pub total_rows: usize,
     pub imported: usize,
     pub skipped_duplicates: usize,
+    #[serde(default)]
+    pub ambiguous_columns: Vec<AmbiguousColumn>,
+    #[serde(default)]
+    pub skipped_preamble_lines: usize,
+    #[serde(default)]
+    pub delimiter_used: char,
+    #[serde(default)]
+    pub encoding_used: String,     // 'UTF-8' or 'windows-1252'
+    #[serde(default)]
+    pub number_format: NumberFormat,
+    #[serde(default)]
+    pub warnings: Vec<String>,     // Problems that didn't stop the import, e.g. a number format that doesn't match the account currency
+}
+
+/// How amounts are written: `Danish` is 1.234,56 and `Us` is 1,234.56
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NumberFormat {
+    #[default]
+    Danish,
+    Us,
+}
+
+/// User-supplied settings for importing a particular bank's CSV files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportProfile {
+    /// Column index per field ("date", "payee", ...), taking precedence over detection
+    #[serde(default)]
+    pub column_overrides: HashMap<String, usize>,
+    /// Headers whose amounts are summed into the transaction amount (e.g. principal + fee).
+    /// Empty means the single detected amount column is used.
+    #[serde(default)]
+    pub amount_columns: Vec<String>,
+    /// Number format of the amounts. None reads them in Danish format; a file that looks
+    /// like another format is imported with a warning rather than parsed differently.
+    #[serde(default)]
+    pub number_format: Option<NumberFormat>,
+    /// Bank category (or subcategory) name -> existing category id, matched case-insensitively.
+    /// Mapped rows use that category instead of creating one from the bank's names.
+    #[serde(default)]
+    pub category_mapping: HashMap<String, i64>,
+    /// What to do with bank categories missing from a non-empty `category_mapping`
+    #[serde(default)]
+    pub unmapped_categories: UnmappedCategoryMode,
+}
+
+/// Handling of bank categories that the import profile's category mapping doesn't cover
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UnmappedCategoryMode {
+    /// Create the category from the bank's names, as without a mapping
+    #[default]
+    Create,
+    /// Import the row uncategorized and flag it for review
+    Flag,
+}
+
+/// Detected layout of a CSV file, for the user to confirm before importing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvValidationResult {
+    pub detected_format: String,   // Delimiter: 'semicolon', 'comma' or 'unknown'
+    pub headers: Vec<String>,
+    pub column_map: HashMap<String, usize>,
+    pub sample_rows: Vec<Vec<String>>,
+    pub ambiguous_columns: Vec<AmbiguousColumn>,
+    pub skipped_preamble_lines: usize,
+    pub warnings: Vec<String>,     // e.g. amounts that look like another number format than the profile's
+    pub error: Option<String>,
+}
+
+/// What an import would do, without writing anything
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportPreview {
+    pub account_id: i64,
+    pub total_rows: usize,
+    pub new_rows: usize,
+    pub duplicate_rows: usize,
+    pub rows: Vec<PreviewRow>,
+    pub ambiguous_columns: Vec<AmbiguousColumn>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreviewRow {
+    pub date: String,
+    pub payee: String,
+    pub memo: Option<String>,
+    pub amount: i64,
+    pub balance: Option<i64>,
+    pub category: Option<String>,
+    pub subcategory: Option<String>,
+    pub is_duplicate: bool,
+}
+
+/// A field where several CSV headers matched, so the chosen column may be wrong
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmbiguousColumn {
+    pub field: String,
+    pub candidates: Vec<String>,
+    pub chosen: String,
+}
+
+/// Income vs expense totals for a period, with the resulting savings rate in percent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsRate {
+    pub income: i64,
+    pub expense: i64,
+    pub savings_rate: f64,
+}
+
+/// Average expense in a category (subcategories included), as a positive amount
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryAverage {
+    pub category_id: i64,
+    pub category_name: String,
+    pub average_amount: i64,
+    pub transaction_count: i64,
+}
+
+/// A payee filed under several categories, with the transaction count in each
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayeeCategoryInconsistency {
+    pub payee: String,
+    pub categories: Vec<(Category, i64)>,
+}
+
+/// When a payee first showed up in the transactions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayeeFirstSeen {
+    pub payee: String,
+    pub first_seen: String,
+    pub total_visits: i64,
+}
+
+/// Spending so far in a month and the extrapolated month-end total
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthSpendProjection {
+    pub month: String,
+    pub actual_to_date: i64,       // Positive sum of outflows
+    pub projected_total: i64,
+    pub percent_month_elapsed: f64,
+}
+
+/// Spending so far in a year scaled up to a full-year estimate
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnnualSpendingProjection {
+    pub year: i32,
+    pub actual_to_date: i64,       // Positive sum of outflows
+    pub projected_total: i64,
+    pub percent_year_elapsed: f64,
+    pub categories: Vec<CategoryAnnualProjection>,
+}
+
+/// One top-level category's share of an `AnnualSpendingProjection`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryAnnualProjection {
+    pub category_name: String,     // Subcategories are counted in their parent
+    pub actual_to_date: i64,
+    pub projected_total: i64,
+}
+
+/// Figures for the home screen, gathered in one call
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HomeSummary {
+    pub month: String,
+    pub net_worth: i64,                // Sum of all account balances today
+    pub income: i64,                   // This month, positive
+    pub expense: i64,                  // This month, positive
+    pub net: i64,
+    pub overspent_budget_count: usize,
+    pub upcoming_charges_total: i64,   // Subscription charges due in the next 30 days, positive
+}
+
+/// Spending in a top-level category with its subcategories broken out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HierarchicalSpending {
+    pub parent_category: String,
+    pub parent_total: i64,                 // Including subcategories, negative like the transactions
+    pub subcategories: Vec<(String, i64)>, // Direct spending on the parent itself is parent_total minus these
+}
+
+/// Things across all accounts that need the user's action, for the startup notification badge
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttentionItems {
+    pub uncategorized_count: i64,
+    pub unreconciled_count: i64,
+    pub overdue_subscriptions: Vec<Subscription>,       // Next charge date has passed
+    pub upcoming_subscriptions_7d: Vec<Subscription>,
+    pub missed_income_streams: Vec<IncomeStream>,       // Expected deposit is more than a few days late
+    pub budget_over_90pct: Vec<BudgetWithSpending>,      // This month
+    pub transaction_gaps: Vec<TransactionGap>,
+}
+
+/// A stretch with no transactions between two consecutive transaction dates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionGap {
+    pub start_date: String,        // Last transaction before the gap
+    pub end_date: String,          // First transaction after the gap
+    pub gap_days: u64,
+}
+
+/// A month with far fewer transactions than usual, likely a forgotten import
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportGap {
+    pub month: String,             // YYYY-MM
+    pub transaction_count: i64,
+    pub median_count: i64,         // Median monthly count over the account's data range
+}
+
+/// Income and expense totals for one calendar quarter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuarterlySummary {
+    pub quarter: u32,              // 1-4
+    pub income: i64,
+    pub expense: i64,              // Positive sum of outflows
+    pub net: i64,
+}
+
+/// Transaction counts and øre totals split by reconciliation state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationSummary {
+    pub reconciled_count: i64,
+    pub reconciled_total: i64,
+    pub unreconciled_count: i64,
+    pub unreconciled_total: i64,
+    #[serde(default)]
+    pub last_reconciled_date: Option<String>,
+}
+
+/// One completed reconciliation of an account against a statement
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReconciliationSession {
+    pub id: i64,
+    pub account_id: i64,
+    pub reconciled_date: String,
+    pub ending_balance: i64,
+    pub transaction_count: i64,
+    pub created_at: String,
+}
+
+/// A transaction whose balance snapshot doesn't follow from the previous balance plus its amount,
+/// which points at missing or duplicated imports
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BalanceDiscrepancy {
+    pub transaction_id: i64,
+    pub date: String,
+    pub payee: String,
+    pub amount: i64,
+    pub expected_balance: i64,
+    pub actual_balance: i64,
+    pub difference: i64,    // actual - expected
+}
+
+/// Spending and income for all accounts of one type
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountTypeSpending {
+    pub account_type: String,
+    pub total_spent: i64,      // Positive sum of outflows
+    pub total_income: i64,
+    pub account_count: i64,    // Accounts of this type with transactions in the range
+}
+
+/// One-page summary of an account over a period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccountStatement {
+    pub account: Account,
+    pub period_start: String,
+    pub period_end: String,
+    pub opening_balance: i64,      // Balance at the end of the day before period_start
+    pub closing_balance: i64,
+    pub total_credits: i64,
+    pub total_debits: i64,         // Positive sum of outflows
+    pub transaction_count: i64,
+    pub category_breakdown: Vec<(String, i64)>,
+    pub largest_expense: Option<TransactionWithCategory>,
+    pub transactions: Vec<TransactionWithCategory>,
+}
+
+/// An expected recurring cash flow on a specific date
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarEvent {
+    pub date: String,
+    pub event_type: String,    // 'charge' or 'income'
+    pub name: String,
+    pub amount: i64,
+    pub source_id: i64,
+    pub source_type: String,   // 'subscription' or 'income_stream'
 }
 
 /// Detected recurring payment / subscription
@@ -82,6 +566,40 @@ pub struct Subscription {
     pub transaction_ids: Vec<i64>,
 }
 
+/// How much a subscription is used, judged by other purchases at the same merchant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionEngagement {
+    pub subscription: Subscription,
+    pub engagement_score: f64,     // Related transactions per month
+    pub related_transactions: Vec<Transaction>,
+}
+
+/// One transaction that made up a detected subscription pattern
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionOccurrence {
+    pub transaction_id: i64,
+    pub date: String,
+    pub amount: i64,
+    pub payee: String,
+}
+
+/// A detected subscription together with the transactions it was detected from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectedSubscription {
+    #[serde(flatten)]
+    pub subscription: Subscription,
+    pub occurrences: Vec<SubscriptionOccurrence>,
+}
+
+/// Monthly subscription cost compared with a (discounted) annual plan
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BillingComparison {
+    pub subscription: Subscription,
+    pub monthly_equivalent: i64,   // Monthly cost as a positive amount
+    pub annual_estimate: i64,      // Estimated annual price after the discount
+    pub potential_savings: i64,    // 12 x monthly minus the annual estimate
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Budget {
     pub id: Option<i64>,
@@ -90,6 +608,15 @@ pub struct Budget {
     pub icon: Option<String>,
 }
 
+/// A category linked to several budgets, with the number of transactions (including subcategories) counted twice or more
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlapReport {
+    pub category: Category,
+    pub budget_ids: Vec<i64>,
+    pub budget_names: Vec<String>,
+    pub transaction_count: i64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BudgetAllocation {
     pub id: Option<i64>,
@@ -104,6 +631,41 @@ pub struct BudgetWithSpending {
     pub category_ids: Vec<i64>,
     pub allocated_amount: i64,
     pub spent_amount: i64,
+    #[serde(default)]
+    pub percent_spent: f64,
+    #[serde(default)]
+    pub percent_month_elapsed: Option<f64>,    // Only set when viewing the current month
+    #[serde(default)]
+    pub pace_status: Option<PaceStatus>,
+    #[serde(default)]
+    pub is_prorated: bool,                     // allocated_amount scaled down for a mid-month start
+}
+
+/// How much of a month's expected income has been given a budget, for zero-based budgeting
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetFundingStatus {
+    pub month: String,
+    pub total_allocated: i64,
+    pub expected_income: i64,      // Monthly equivalent of the active income streams
+    pub unallocated: i64,          // Left to budget; negative when more is allocated than expected
+}
+
+/// Average and variance of a budget's monthly utilization (spent / allocated, in percent)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetEfficiency {
+    pub budget: Budget,
+    pub avg_utilization: f64,
+    pub variance: f64,
+    pub suggestion: String, // e.g. "Reduce allocation by 30%"
+}
+
+/// Spending pace compared with how far through the month we are
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PaceStatus {
+    OnTrack,
+    Ahead,     // Spending faster than the month is passing
+    Behind,    // Spending slower than the month is passing
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -114,4 +676,302 @@ pub struct IncomeStream {
     pub frequency: String,
     pub category_id: Option<i64>,
     pub is_active: bool,
+    #[serde(default = "default_stream_direction")]
+    pub direction: String,         // 'inflow', or 'outflow' for obligations like loan payments (negative amount)
+    #[serde(default)]
+    pub anchor_date: Option<String>, // a known deposit date (YYYY-MM-DD) to project from instead of the last matched transaction
+}
+
+/// An income stream's expected amount before it was changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncomeStreamHistoryEntry {
+    pub id: i64,
+    pub stream_id: i64,
+    pub expected_amount: i64,
+    pub changed_at: String,
+}
+
+fn default_stream_direction() -> String {
+    "inflow".to_string()
+}
+
+/// The most regular monthly income on an account, typically salary
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PaycheckPattern {
+    pub payee: String,
+    pub typical_amount: i64,
+    pub typical_day_of_month: u32,
+    pub consistency_score: f64,    // Share of payments within ±3 days of the typical day
+    pub last_received: String,
+}
+
+/// Whether an income stream was received in a given month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthCoverage {
+    pub month: String,             // YYYY-MM
+    pub received: bool,
+    pub actual_amount: Option<i64>,
+    pub expected_amount: i64,
+}
+
+/// A projected income deposit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpcomingIncome {
+    pub date: String,
+    pub amount: i64,
+    pub name: String,
+    pub stream_id: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncomeStreamWithLastReceived {
+    pub stream: IncomeStream,
+    pub last_received: Option<Transaction>,
+}
+
+/// Spending total for a single month (YYYY-MM)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlyAmount {
+    pub month: String,
+    pub total: i64,
+    pub count: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayeeTrendSummary {
+    pub avg_monthly: i64,
+    pub trend_direction: String, // 'increasing', 'decreasing', 'stable'
+}
+
+/// Transactions whose absolute amount falls within [lower_bound, upper_bound] øre
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AmountBucket {
+    pub lower_bound: i64,
+    pub upper_bound: i64,
+    pub count: i64,
+    pub total: i64,
+}
+
+/// Monthly spending history for a single payee
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PayeeTrend {
+    pub months: Vec<MonthlyAmount>,
+    pub summary: PayeeTrendSummary,
+}
+
+/// Projected spending for one future month
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForecastPoint {
+    pub month: String,
+    pub predicted: i64,
+    pub confidence_interval: (i64, i64), // (low, high)
+}
+
+/// Monthly category spending history and its linear-trend forecast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingForecast {
+    pub historical: Vec<MonthlyAmount>,
+    pub forecast: Vec<ForecastPoint>,
+}
+
+/// Expense-to-income ratio for one month; `None` when there was no income
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EIRatioPoint {
+    pub month: String,
+    pub expense_ratio: Option<f64>,
+    pub trend: String,             // 'improving', 'worsening' or 'stable'
+}
+
+/// How long savings would cover expenses after losing an income stream
+/// How long the balance on non-credit accounts would last with no income at the recent spending pace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CashRunway {
+    pub total_balance: i64,
+    pub avg_daily_spend: i64,      // Positive, expenses only; transfers between accounts excluded
+    pub runway_days: f64,
+    pub runway_months: f64,
+    pub is_emergency_fund_adequate: bool, // At least 90 days of runway
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StressTestResult {
+    pub monthly_deficit: i64,      // Expenses minus income per month; negative is a surplus
+    pub months_survivable: f64,    // Capped at 120 when income still covers expenses
+    pub risk_level: String,        // 'high', 'medium' or 'low'
+}
+
+/// Savings rate so far this month and extrapolated to month-end (rates in percent)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavingsRateProjection {
+    pub current_savings_rate: f64,
+    pub projected_savings_rate: f64,
+    pub days_remaining: u32,
+    pub spending_pace: i64,    // Average daily spending so far, in øre
+}
+
+/// Daily spending pace in the first vs second half of a month.
+/// An `acceleration_factor > 1.0` means spending speeds up through the month.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingVelocity {
+    pub first_half_daily_avg: i64,
+    pub second_half_daily_avg: i64,
+    pub full_month_projected: i64,
+    pub acceleration_factor: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DayOfMonthSpending {
+    pub day: u32,
+    pub avg_spent: i64,
+    pub sample_count: u32, // Months in which this day has occurred
+}
+
+/// Average spending by day of month. An `early_month_bias > 1.0` means more is spent in days 1-15.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingCadence {
+    pub by_day_of_month: Vec<DayOfMonthSpending>,
+    pub early_month_bias: f64,
+}
+
+/// A category where the average weekend expense is far above the weekday average
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WeekendAnomaly {
+    pub category_name: String,
+    pub weekday_avg: i64,
+    pub weekend_avg: i64,
+    pub ratio: f64,                // weekend_avg / weekday_avg
+}
+
+/// Spending in one calendar quarter, averaged over the years with data for it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeasonalPattern {
+    pub quarter: u32,
+    pub avg_quarterly_spend: i64,
+    pub years_of_data: u32,
+    pub max_year_spend: i64,
+    pub min_year_spend: i64,
+}
+
+/// Income for one calendar year; the current year is projected from year-to-date income
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncomeGrowth {
+    pub year: i32,
+    pub total_income: i64,
+    pub growth_pct: Option<f64>,   // None for the first year
+    pub is_projected: bool,
+}
+
+/// Spending for a month split by the spending type of each transaction's category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingTypeBreakdown {
+    pub essential: i64,
+    pub discretionary: i64,
+    pub savings: i64,
+    pub uncategorized: i64,
+}
+
+/// A month's spending split by whether the charge is linked to a subscription
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommittedSplit {
+    pub month: String,
+    pub committed_total: i64,      // Positive, transactions linked to a subscription
+    pub discretionary_total: i64,  // Positive, every other expense
+    pub committed_pct: f64,        // Share of all spending, 0 when nothing was spent
+}
+
+/// A month's summary rendered for sending by email or copying to the clipboard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonthlySummary {
+    pub subject: String,
+    pub body_plain_text: String,
+    pub body_html: String,
+}
+
+/// Where a month's spending in a category falls among all months so far
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpendingPercentile {
+    pub category_name: String,
+    pub month_spending: i64,
+    pub historical_mean: i64,
+    pub percentile: f64,           // 0-100, share of months with lower spending
+    pub label: String,             // 'very low', 'below average', 'average', 'above average', 'very high'
+}
+
+/// A month's needs/wants/savings split as a share of income, compared with a benchmark profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkReport {
+    pub month: String,
+    pub profile: String,
+    pub actual_needs_pct: f64,     // Essential spending
+    pub target_needs_pct: f64,
+    pub actual_wants_pct: f64,     // Discretionary and uncategorized spending
+    pub target_wants_pct: f64,
+    pub actual_savings_pct: f64,   // Spending in savings categories
+    pub target_savings_pct: f64,
+    pub overall_score: f64,        // 100 = exactly on target, 0 = completely off
+}
+
+/// Spending at a single payee with the categories it was spread over
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerchantBreakdown {
+    pub payee: String,
+    pub total_spent: i64,
+    pub category_breakdown: Vec<(String, i64)>,
+    pub last_visit: String,
+    pub visit_count: i64,
+}
+
+/// A payee's spending history from the first to the latest expense
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MerchantTenure {
+    pub payee: String,
+    pub first_date: String,
+    pub last_date: String,
+    pub tenure_days: i64,
+    pub total_spent: i64,
+    pub visit_count: i64,
+}
+
+/// Net worth at the end of two months and what the difference is made of.
+/// `expenses` excludes subscription payments, which are reported separately.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetWorthChangeAttribution {
+    pub from: i64,
+    pub to: i64,
+    pub change: i64,
+    pub income: i64,
+    pub expenses: i64,
+    pub subscriptions_total: i64,
+    pub transfers_net: i64,
+}
+
+/// Category spending in a month compared with the month before
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryMoMChange {
+    pub category_id: Option<i64>,  // None for uncategorized spending
+    pub category_name: String,
+    pub current: i64,
+    pub prior: i64,
+    pub absolute_change: i64,
+    pub pct_change: f64,
+}
+
+/// How much a category's monthly spending varies; `cv` is std_dev / mean
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryConsistency {
+    pub category_id: i64,
+    pub category_name: String,
+    pub mean_monthly: i64,
+    pub std_dev: i64,
+    pub cv: f64,
+    pub consistency_label: String, // 'very consistent', 'consistent', 'variable' or 'unpredictable'
+}
+
+/// Consecutive months with spending in a category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategoryStreak {
+    pub category_id: i64,
+    pub category_name: String,
+    pub current_streak_months: u32,    // Ending in the current month
+    pub longest_streak_months: u32,
 }
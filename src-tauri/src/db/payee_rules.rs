@@ -0,0 +1,149 @@
+use regex::Regex;
+use rusqlite::{params, Connection};
+use super::audit;
+use super::models::PayeeRule;
+
+pub fn create(conn: &Connection, rule: &PayeeRule) -> Result<i64, rusqlite::Error> {
+    compile(&rule.pattern)?;
+    conn.execute(
+        "INSERT INTO payee_rules (pattern, replacement, rule_order) VALUES (?1, ?2, ?3)",
+        params![rule.pattern, rule.replacement, rule.order],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_all(conn: &Connection) -> Result<Vec<PayeeRule>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, pattern, replacement, rule_order FROM payee_rules ORDER BY rule_order, id",
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(PayeeRule {
+            id: Some(row.get(0)?),
+            pattern: row.get(1)?,
+            replacement: row.get(2)?,
+            order: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn update(conn: &Connection, rule: &PayeeRule) -> Result<usize, rusqlite::Error> {
+    let id = rule.id.ok_or(rusqlite::Error::InvalidParameterName(
+        "Payee rule ID is required for update".to_string(),
+    ))?;
+    compile(&rule.pattern)?;
+    conn.execute(
+        "UPDATE payee_rules SET pattern = ?1, replacement = ?2, rule_order = ?3 WHERE id = ?4",
+        params![rule.pattern, rule.replacement, rule.order, id],
+    )
+}
+
+pub fn delete(conn: &Connection, id: i64) -> Result<usize, rusqlite::Error> {
+    conn.execute("DELETE FROM payee_rules WHERE id = ?1", params![id])
+}
+
+/// Load all rules in order, compiled and ready to apply
+pub fn load_compiled(conn: &Connection) -> Result<Vec<(Regex, String)>, rusqlite::Error> {
+    get_all(conn)?
+        .into_iter()
+        .map(|rule| Ok((compile(&rule.pattern)?, rule.replacement)))
+        .collect()
+}
+
+/// Apply rules in order to a raw payee string
+pub fn apply(rules: &[(Regex, String)], payee: &str) -> String {
+    let mut cleaned = payee.to_string();
+    for (regex, replacement) in rules {
+        cleaned = regex.replace_all(&cleaned, replacement.as_str()).into_owned();
+    }
+    cleaned.trim().to_string()
+}
+
+/// Re-apply the current rules to every stored transaction, starting from its raw payee.
+/// Returns the number of transactions whose payee changed.
+pub fn clean_existing_payees(conn: &Connection) -> Result<usize, rusqlite::Error> {
+    let rules = load_compiled(conn)?;
+
+    let mut stmt = conn.prepare("SELECT id, payee, raw_payee FROM transactions")?;
+    let rows: Vec<(i64, String, Option<String>)> = stmt
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut update = conn.prepare("UPDATE transactions SET payee = ?1, raw_payee = ?2 WHERE id = ?3")?;
+    let mut count = 0;
+    for (id, payee, raw_payee) in rows {
+        // Transactions imported before rules existed have no raw payee yet
+        let raw = raw_payee.unwrap_or(payee.clone());
+        let cleaned = apply(&rules, &raw);
+        if cleaned != payee {
+            audit::log_change(conn, id, "payee", Some(&payee), Some(&cleaned))?;
+            update.execute(params![cleaned, raw, id])?;
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn compile(pattern: &str) -> Result<Regex, rusqlite::Error> {
+    Regex::new(pattern)
+        .map_err(|e| rusqlite::Error::InvalidParameterName(format!("Invalid payee rule pattern: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{import, schema};
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        conn
+    }
+
+    fn strip_dankort_prefix(conn: &Connection) {
+        create(
+            conn,
+            &PayeeRule {
+                id: None,
+                pattern: r"^Dankort-nota \d{6} ".to_string(),
+                replacement: String::new(),
+                order: 0,
+            },
+        )
+        .unwrap();
+    }
+
+    fn payees(conn: &Connection) -> Vec<(String, Option<String>)> {
+        let mut stmt = conn.prepare("SELECT payee, raw_payee FROM transactions ORDER BY id").unwrap();
+        let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?))).unwrap();
+        rows.collect::<Result<_, _>>().unwrap()
+    }
+
+    #[test]
+    fn prefix_is_stripped_at_import_and_raw_payee_kept() {
+        let conn = setup();
+        strip_dankort_prefix(&conn);
+        import::import_csv(&conn, "Dato;Tekst;Beløb\n01-03-2024;Dankort-nota 290224 Netto;-125,00\n", 1, "marts.csv").unwrap();
+
+        assert_eq!(payees(&conn), [("Netto".to_string(), Some("Dankort-nota 290224 Netto".to_string()))]);
+    }
+
+    #[test]
+    fn existing_payees_are_cleaned_from_the_raw_payee() {
+        let conn = setup();
+        import::import_csv(&conn, "Dato;Tekst;Beløb\n01-03-2024;Dankort-nota 290224 Netto;-125,00\n", 1, "marts.csv").unwrap();
+        strip_dankort_prefix(&conn);
+
+        assert_eq!(clean_existing_payees(&conn).unwrap(), 1);
+        assert_eq!(clean_existing_payees(&conn).unwrap(), 0);
+        assert_eq!(payees(&conn), [("Netto".to_string(), Some("Dankort-nota 290224 Netto".to_string()))]);
+    }
+
+    #[test]
+    fn invalid_patterns_are_rejected() {
+        let conn = setup();
+        let rule = PayeeRule { id: None, pattern: "(unclosed".to_string(), replacement: String::new(), order: 0 };
+        assert!(create(&conn, &rule).is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use rusqlite::{params, Connection};
+use super::models::ReconciliationSession;
+
+/// Close a reconciliation: mark the listed transactions as reconciled and record the session.
+/// The session is dated by the latest transaction it covers (today when none are listed).
+pub fn create_session(
+    conn: &Connection,
+    account_id: i64,
+    ending_balance: i64,
+    transaction_ids: &[i64],
+) -> Result<ReconciliationSession, rusqlite::Error> {
+    let db_tx = conn.unchecked_transaction()?;
+
+    let mut reconciled_date: Option<String> = None;
+    for &id in transaction_ids {
+        let date: String = db_tx
+            .query_row(
+                "SELECT date FROM transactions WHERE id = ?1 AND account_id = ?2",
+                params![id, account_id],
+                |row| row.get(0),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => rusqlite::Error::InvalidParameterName(format!(
+                    "Transaction {} does not belong to this account",
+                    id
+                )),
+                other => other,
+            })?;
+        if reconciled_date.as_deref() < Some(date.as_str()) {
+            reconciled_date = Some(date);
+        }
+        db_tx.execute("UPDATE transactions SET is_reconciled = 1 WHERE id = ?1", params![id])?;
+    }
+
+    db_tx.execute(
+        r#"INSERT INTO reconciliation_sessions (account_id, reconciled_date, ending_balance, transaction_count)
+           VALUES (?1, COALESCE(?2, date('now')), ?3, ?4)"#,
+        params![account_id, reconciled_date, ending_balance, transaction_ids.len() as i64],
+    )?;
+    let id = db_tx.last_insert_rowid();
+    let session = db_tx.query_row(
+        &format!("{} WHERE id = ?1", SELECT_SESSION),
+        params![id],
+        map_session,
+    )?;
+    db_tx.commit()?;
+    Ok(session)
+}
+
+/// Past reconciliations for an account, most recent first
+pub fn get_history(conn: &Connection, account_id: i64) -> Result<Vec<ReconciliationSession>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        "{} WHERE account_id = ?1 ORDER BY reconciled_date DESC, id DESC",
+        SELECT_SESSION
+    ))?;
+    let rows = stmt.query_map(params![account_id], map_session)?;
+    rows.collect()
+}
+
+/// Date the account was last balanced up to, if it has ever been reconciled
+pub fn last_reconciled_date(conn: &Connection, account_id: i64) -> Result<Option<String>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT MAX(reconciled_date) FROM reconciliation_sessions WHERE account_id = ?1",
+        params![account_id],
+        |row| row.get(0),
+    )
+}
+
+const SELECT_SESSION: &str = "SELECT id, account_id, reconciled_date, ending_balance, transaction_count, created_at FROM reconciliation_sessions";
+
+fn map_session(row: &rusqlite::Row) -> Result<ReconciliationSession, rusqlite::Error> {
+    Ok(ReconciliationSession {
+        id: row.get(0)?,
+        account_id: row.get(1)?,
+        reconciled_date: row.get(2)?,
+        ending_balance: row.get(3)?,
+        transaction_count: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
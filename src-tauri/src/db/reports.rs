@@ -0,0 +1,2252 @@
+use rusqlite::{params, Connection};
+use super::{accounts, budgets, categories, income_streams, savings_goals, settings, subscription_engine, subscriptions, transactions, transfers};
+use super::models::{AccountStatement, AccountTypeSpending, AttentionItems, AmountBucket, BenchmarkReport, BillingComparison, Budget, BudgetEfficiency, CalendarEvent, CashRunway, CategoryConsistency, CategoryCutSuggestion, CategoryMoMChange, CategoryStreak, CommittedSplit, DayOfMonthSpending, EIRatioPoint, ForecastPoint, HierarchicalSpending, IncomeGrowth, MerchantBreakdown, MerchantTenure, MonthlyAmount, MonthlySummary, NetWorthChangeAttribution, PayeeTrend, PayeeTrendSummary, SavingsPlan, SavingsRateProjection, SeasonalPattern, SpendingCadence, SpendingForecast, SpendingPercentile, SpendingTypeBreakdown, SpendingVelocity, StressTestResult, SubscriptionEngagement, Transaction, TransferReconciliation, WeekendAnomaly};
+use chrono::{Datelike, NaiveDate};
+use std::collections::{HashMap, HashSet};
+
+/// Spending trend for a single payee over the last `months` months (including the current one).
+/// The payee is matched case-insensitively; `%` wildcards are honoured, otherwise it is a substring match.
+pub fn get_payee_trend(
+    conn: &Connection,
+    payee: String,
+    months: u32,
+    account_id: Option<i64>,
+) -> Result<PayeeTrend, rusqlite::Error> {
+    let pattern = if payee.contains('%') {
+        payee.to_lowercase()
+    } else {
+        format!("%{}%", payee.to_lowercase())
+    };
+
+    let month_keys = last_n_months(conn, months)?;
+    let start = month_keys.first().cloned().unwrap_or_default();
+
+    let mut stmt = conn.prepare(
+        r#"SELECT substr(date, 1, 7) as month, SUM(ABS(amount)), COUNT(*)
+           FROM transactions
+           WHERE LOWER(payee) LIKE ?1 AND amount < 0 AND date >= ?2
+             AND (?3 IS NULL OR account_id = ?3)
+           GROUP BY month"#,
+    )?;
+    let totals: HashMap<String, (i64, i64)> = stmt
+        .query_map(params![pattern, format!("{}-01", start), account_id], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?)))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let months: Vec<MonthlyAmount> = month_keys
+        .into_iter()
+        .map(|month| {
+            let (total, count) = totals.get(&month).copied().unwrap_or((0, 0));
+            MonthlyAmount { month, total, count }
+        })
+        .collect();
+
+    let values: Vec<i64> = months.iter().map(|m| m.total).collect();
+    let avg_monthly = if values.is_empty() {
+        0
+    } else {
+        values.iter().sum::<i64>() / values.len() as i64
+    };
+
+    Ok(PayeeTrend {
+        months,
+        summary: PayeeTrendSummary {
+            avg_monthly,
+            trend_direction: trend_direction(&values),
+        },
+    })
+}
+
+/// Histogram of a payee's outflows in buckets of `bucket_size` øre, e.g. 0-9999, 10000-19999, ...
+/// The payee is matched like in `get_payee_trend`. Only buckets containing transactions are returned.
+pub fn get_payee_amount_distribution(
+    conn: &Connection,
+    payee: String,
+    bucket_size: i64,
+) -> Result<Vec<AmountBucket>, rusqlite::Error> {
+    if bucket_size <= 0 {
+        return Err(rusqlite::Error::InvalidParameterName("Bucket size must be positive".to_string()));
+    }
+    let pattern = if payee.contains('%') {
+        payee.to_lowercase()
+    } else {
+        format!("%{}%", payee.to_lowercase())
+    };
+
+    let mut stmt = conn.prepare(
+        r#"SELECT (ABS(amount) / ?2) * ?2 as lower_bound, COUNT(*), SUM(ABS(amount))
+           FROM transactions
+           WHERE LOWER(payee) LIKE ?1 AND amount < 0
+           GROUP BY lower_bound
+           ORDER BY lower_bound"#,
+    )?;
+    let rows = stmt.query_map(params![pattern, bucket_size], |row| {
+        let lower_bound: i64 = row.get(0)?;
+        Ok(AmountBucket {
+            lower_bound,
+            upper_bound: lower_bound + bucket_size - 1,
+            count: row.get(1)?,
+            total: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Project this month's savings rate from spending so far, extrapolated to month-end
+/// and compared against the expected income from active income streams.
+pub fn project_savings_rate(
+    conn: &Connection,
+    account_id: Option<i64>,
+) -> Result<SavingsRateProjection, rusqlite::Error> {
+    let (day, days_in_month): (u32, u32) = conn.query_row(
+        r#"SELECT CAST(strftime('%d', 'now') AS INTEGER),
+                  CAST(strftime('%d', date('now', 'start of month', '+1 month', '-1 day')) AS INTEGER)"#,
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let (income, spending): (i64, i64) = conn.query_row(
+        r#"SELECT
+            COALESCE(SUM(CASE WHEN amount > 0 THEN amount ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN amount < 0 THEN -amount ELSE 0 END), 0)
+           FROM transactions
+           WHERE date >= date('now', 'start of month') AND date <= date('now')
+             AND (?1 IS NULL OR account_id = ?1)"#,
+        params![account_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let spending_pace = spending / day.max(1) as i64;
+    let projected_spending = spending_pace * days_in_month as i64;
+
+    // Income tends to arrive in lump sums, so prefer what the streams say we should receive
+    let expected_income = income_streams::get_expected_monthly_total(conn)?;
+    let projected_income = expected_income.max(income);
+
+    Ok(SavingsRateProjection {
+        current_savings_rate: transactions::savings_rate_percent(income, spending),
+        projected_savings_rate: transactions::savings_rate_percent(projected_income, projected_spending),
+        days_remaining: days_in_month - day,
+        spending_pace,
+    })
+}
+
+/// Completed months averaged for expenses (and income, without income streams) in a stress test
+const STRESS_TEST_HISTORY_MONTHS: i32 = 6;
+/// Upper bound for `months_survivable`, used when income still covers expenses
+const STRESS_TEST_MAX_MONTHS: f64 = 120.0;
+
+/// How long the current balance across all accounts would last if `remove_stream_id` stopped paying.
+/// Expenses are the monthly average over the last 6 completed months. Income is the expected total of
+/// the active income streams, or the average actual income when no streams are set up. Removing an
+/// outflow stream, such as a paid-off loan, lowers expenses instead of income. An emergency fund of
+/// `emergency_fund_months` × monthly expenses is added to the balance.
+/// Risk is "high" under 3 months, "medium" under 6 and "low" otherwise.
+pub fn cashflow_stress_test(
+    conn: &Connection,
+    remove_stream_id: Option<i64>,
+    emergency_fund_months: f64,
+) -> Result<StressTestResult, rusqlite::Error> {
+    let today = current_date(conn)?;
+    let history_start = first_of_month_offset(today, -STRESS_TEST_HISTORY_MONTHS).format("%Y-%m-%d").to_string();
+    let history_end = first_of_month_offset(today, 0).format("%Y-%m-%d").to_string();
+    let (income, expenses): (i64, i64) = conn.query_row(
+        r#"SELECT
+            COALESCE(SUM(CASE WHEN amount > 0 THEN amount ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN amount < 0 THEN -amount ELSE 0 END), 0)
+           FROM transactions
+           WHERE date >= ?1 AND date < ?2"#,
+        params![history_start, history_end],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    let monthly_expenses = expenses / STRESS_TEST_HISTORY_MONTHS as i64;
+
+    let expected_income = income_streams::get_expected_monthly_total(conn)?;
+    let mut monthly_income = if expected_income > 0 {
+        expected_income
+    } else {
+        income / STRESS_TEST_HISTORY_MONTHS as i64
+    };
+    let mut remaining_expenses = monthly_expenses;
+    if let Some(stream_id) = remove_stream_id {
+        let stream = income_streams::get_by_id(conn, stream_id)?
+            .ok_or_else(|| rusqlite::Error::InvalidParameterName("Income stream not found".to_string()))?;
+        if stream.is_active {
+            let monthly = income_streams::monthly_equivalent(stream.expected_amount, &stream.frequency);
+            if stream.direction == "outflow" {
+                // Outflow amounts are negative
+                remaining_expenses += monthly;
+            } else {
+                monthly_income -= monthly;
+            }
+        }
+    }
+    let monthly_income = monthly_income.max(0);
+    let remaining_expenses = remaining_expenses.max(0);
+
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let mut balance = 0;
+    for account in accounts::get_all(conn)? {
+        balance += transactions::balance_at(conn, account.id.unwrap_or_default(), &today_str)?;
+    }
+    let cushion = balance as f64 + emergency_fund_months.max(0.0) * monthly_expenses as f64;
+
+    let monthly_deficit = remaining_expenses - monthly_income;
+    let months_survivable = if monthly_deficit <= 0 {
+        STRESS_TEST_MAX_MONTHS
+    } else {
+        (cushion / monthly_deficit as f64).clamp(0.0, STRESS_TEST_MAX_MONTHS)
+    };
+    let risk_level = if months_survivable < 3.0 {
+        "high"
+    } else if months_survivable < 6.0 {
+        "medium"
+    } else {
+        "low"
+    };
+
+    Ok(StressTestResult {
+        monthly_deficit,
+        months_survivable,
+        risk_level: risk_level.to_string(),
+    })
+}
+
+/// Days of spending averaged for the cash runway, counting back from today
+const RUNWAY_HISTORY_DAYS: i64 = 90;
+/// Runway needed for the emergency fund to count as adequate
+const RUNWAY_ADEQUATE_DAYS: f64 = 90.0;
+/// Upper bound for `runway_days`, used when nothing has been spent recently
+const RUNWAY_MAX_DAYS: f64 = 3650.0;
+
+/// How many days the current balance on all non-credit accounts would last if income stopped today.
+/// Spending is the daily average of expenses over the last 90 days on every account; income and
+/// transfers (recorded ones and pairs that look like transfers) are left out.
+pub fn get_cash_runway(conn: &Connection) -> Result<CashRunway, rusqlite::Error> {
+    let today = current_date(conn)?;
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let history_start = (today - chrono::Duration::days(RUNWAY_HISTORY_DAYS - 1)).format("%Y-%m-%d").to_string();
+
+    let mut total_balance = 0;
+    for account in accounts::get_all(conn)? {
+        if account.account_type != "credit" {
+            total_balance += transactions::balance_at(conn, account.id.unwrap_or_default(), &today_str)?;
+        }
+    }
+
+    let mut transfer_ids: HashSet<i64> = transactions::find_transfer_pairs(conn, &history_start, &today_str)?
+        .into_iter()
+        .flat_map(|(out_id, in_id)| [out_id, in_id])
+        .collect();
+    transfer_ids.extend(transfers::get_all(conn)?.iter().map(|t| t.debit_transaction_id));
+
+    let mut stmt = conn.prepare(
+        "SELECT id, amount FROM transactions WHERE amount < 0 AND date >= ?1 AND date <= ?2",
+    )?;
+    let spent: i64 = stmt
+        .query_map(params![history_start, today_str], |row| Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?)))?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|(id, _)| !transfer_ids.contains(id))
+        .map(|(_, amount)| -amount)
+        .sum();
+    let daily_spend = spent as f64 / RUNWAY_HISTORY_DAYS as f64;
+
+    let runway_days = if total_balance <= 0 {
+        0.0
+    } else if daily_spend <= 0.0 {
+        RUNWAY_MAX_DAYS
+    } else {
+        (total_balance as f64 / daily_spend).min(RUNWAY_MAX_DAYS)
+    };
+
+    Ok(CashRunway {
+        total_balance,
+        avg_daily_spend: daily_spend.round() as i64,
+        runway_days,
+        runway_months: runway_days / (365.25 / 12.0),
+        is_emergency_fund_adequate: runway_days >= RUNWAY_ADEQUATE_DAYS,
+    })
+}
+
+/// Expense / income per month over the last `months` months (including the current one).
+/// Months without income have no ratio. Each point's trend looks at the last 3 ratios up to it:
+/// steadily falling is "improving", steadily rising is "worsening", anything else "stable".
+pub fn get_expense_income_ratio_trend(
+    conn: &Connection,
+    account_id: Option<i64>,
+    months: u32,
+) -> Result<Vec<EIRatioPoint>, rusqlite::Error> {
+    let month_keys = last_n_months(conn, months)?;
+    let Some(start) = month_keys.first() else {
+        return Ok(Vec::new());
+    };
+
+    let mut stmt = conn.prepare(
+        r#"SELECT substr(date, 1, 7) as month,
+            COALESCE(SUM(CASE WHEN amount > 0 THEN amount ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN amount < 0 THEN -amount ELSE 0 END), 0)
+           FROM transactions
+           WHERE date >= ?1 AND (?2 IS NULL OR account_id = ?2)
+           GROUP BY month"#,
+    )?;
+    let totals: HashMap<String, (i64, i64)> = stmt
+        .query_map(params![format!("{}-01", start), account_id], |row| {
+            Ok((row.get(0)?, (row.get(1)?, row.get(2)?)))
+        })?
+        .collect::<Result<_, _>>()?;
+
+    let mut recent: Vec<f64> = Vec::new();
+    Ok(month_keys
+        .into_iter()
+        .map(|month| {
+            let (income, expense) = totals.get(&month).copied().unwrap_or((0, 0));
+            let expense_ratio = (income > 0).then(|| expense as f64 / income as f64);
+            if let Some(ratio) = expense_ratio {
+                recent.push(ratio);
+            }
+            let last_three = &recent[recent.len().saturating_sub(3)..];
+            let trend = if last_three.len() < 3 {
+                "stable"
+            } else if last_three.windows(2).all(|w| w[1] < w[0]) {
+                "improving"
+            } else if last_three.windows(2).all(|w| w[1] > w[0]) {
+                "worsening"
+            } else {
+                "stable"
+            };
+            EIRatioPoint { month, expense_ratio, trend: trend.to_string() }
+        })
+        .collect())
+}
+
+/// Compare the daily spending pace of days 1-15 against the rest of the month.
+/// For the current month, the unelapsed part of the month is projected at the month-to-date pace.
+pub fn get_spending_velocity(
+    conn: &Connection,
+    account_id: Option<i64>,
+    month: String,
+) -> Result<SpendingVelocity, rusqlite::Error> {
+    const FIRST_HALF_DAYS: u32 = 15;
+
+    let (start, end, days_in_month) = month_bounds(&month)?;
+    let today = current_date(conn)?;
+
+    // Number of days of this month that have data (0 for future months)
+    let elapsed_days = if today > end {
+        days_in_month
+    } else if today < start {
+        0
+    } else {
+        today.day()
+    };
+
+    let mut stmt = conn.prepare(
+        r#"SELECT
+            COALESCE(SUM(CASE WHEN CAST(strftime('%d', date) AS INTEGER) <= ?4 THEN -amount ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN CAST(strftime('%d', date) AS INTEGER) > ?4 THEN -amount ELSE 0 END), 0)
+           FROM transactions
+           WHERE date >= ?1 AND date <= ?2 AND amount < 0
+             AND (?3 IS NULL OR account_id = ?3)"#,
+    )?;
+    let (first_actual, second_actual): (i64, i64) = stmt.query_row(
+        params![
+            start.format("%Y-%m-%d").to_string(),
+            end.format("%Y-%m-%d").to_string(),
+            account_id,
+            FIRST_HALF_DAYS
+        ],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let second_half_days = days_in_month - FIRST_HALF_DAYS;
+    let elapsed_first = elapsed_days.min(FIRST_HALF_DAYS);
+    let elapsed_second = elapsed_days.saturating_sub(FIRST_HALF_DAYS);
+
+    // Month-to-date pace, used to fill in days that haven't happened yet
+    let pace = if elapsed_days > 0 {
+        (first_actual + second_actual) / elapsed_days as i64
+    } else {
+        0
+    };
+
+    let first_total = first_actual + pace * (FIRST_HALF_DAYS - elapsed_first) as i64;
+    let second_total = second_actual + pace * (second_half_days - elapsed_second) as i64;
+
+    let first_half_daily_avg = first_total / FIRST_HALF_DAYS as i64;
+    let second_half_daily_avg = second_total / second_half_days as i64;
+
+    // Without first-half spending there is no baseline to accelerate from
+    let acceleration_factor = if first_half_daily_avg > 0 {
+        second_half_daily_avg as f64 / first_half_daily_avg as f64
+    } else {
+        1.0
+    };
+
+    Ok(SpendingVelocity {
+        first_half_daily_avg,
+        second_half_daily_avg,
+        full_month_projected: first_total + second_total,
+        acceleration_factor,
+    })
+}
+
+/// Average spending on each day of the month (1-31) over the last `months` months, including the current one.
+/// Each day is averaged over the months in which it has occurred so far, so day 31 only counts 31-day months.
+pub fn get_spending_cadence(
+    conn: &Connection,
+    account_id: i64,
+    months: u32,
+) -> Result<SpendingCadence, rusqlite::Error> {
+    const FIRST_HALF_DAYS: u32 = 15;
+
+    let today = current_date(conn)?;
+    let month_keys = last_n_months(conn, months)?;
+    let Some(first_month) = month_keys.first() else {
+        return Ok(SpendingCadence { by_day_of_month: Vec::new(), early_month_bias: 1.0 });
+    };
+
+    // How many times each day of the month has occurred in the window
+    let mut sample_counts = [0u32; 32];
+    for month in &month_keys {
+        let (_, end, _) = month_bounds(month)?;
+        for day in 1..=end.min(today).day() {
+            sample_counts[day as usize] += 1;
+        }
+    }
+
+    let mut stmt = conn.prepare(
+        r#"SELECT CAST(strftime('%d', date) AS INTEGER) as day, SUM(-amount)
+           FROM transactions
+           WHERE account_id = ?1 AND amount < 0 AND date >= ?2 AND date <= ?3
+           GROUP BY day"#,
+    )?;
+    let totals: HashMap<u32, i64> = stmt
+        .query_map(
+            params![account_id, format!("{}-01", first_month), today.format("%Y-%m-%d").to_string()],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )?
+        .collect::<Result<_, _>>()?;
+
+    let by_day_of_month: Vec<DayOfMonthSpending> = (1..=31u32)
+        .map(|day| {
+            let sample_count = sample_counts[day as usize];
+            let total = totals.get(&day).copied().unwrap_or(0);
+            DayOfMonthSpending {
+                day,
+                avg_spent: if sample_count > 0 { total / sample_count as i64 } else { 0 },
+                sample_count,
+            }
+        })
+        .collect();
+
+    // Average daily spending per half, weighted by how often each day occurred
+    let half_avg = |days: &[DayOfMonthSpending]| {
+        let samples: u32 = days.iter().map(|d| d.sample_count).sum();
+        let total: i64 = days.iter().map(|d| totals.get(&d.day).copied().unwrap_or(0)).sum();
+        if samples > 0 { total as f64 / samples as f64 } else { 0.0 }
+    };
+    let (first_half, second_half) = by_day_of_month.split_at(FIRST_HALF_DAYS as usize);
+    let first_avg = half_avg(first_half);
+    let second_avg = half_avg(second_half);
+
+    // Without second-half spending there is nothing to compare against
+    let early_month_bias = if second_avg > 0.0 { first_avg / second_avg } else { 1.0 };
+
+    Ok(SpendingCadence { by_day_of_month, early_month_bias })
+}
+
+/// Weekend spending above this multiple of the weekday average counts as unusual
+const WEEKEND_ANOMALY_RATIO: f64 = 2.0;
+
+/// Categories whose average expense on Saturdays and Sundays is more than twice their weekday
+/// average, over the last `months` months (including the current one). Biggest ratio first.
+pub fn get_weekend_spending_anomalies(
+    conn: &Connection,
+    account_id: i64,
+    months: u32,
+) -> Result<Vec<WeekendAnomaly>, rusqlite::Error> {
+    let month_keys = last_n_months(conn, months)?;
+    let Some(start) = month_keys.first() else {
+        return Ok(Vec::new());
+    };
+
+    // strftime('%w') is 0 for Sunday and 6 for Saturday
+    let mut stmt = conn.prepare(
+        r#"SELECT c.name,
+                  AVG(CASE WHEN strftime('%w', t.date) NOT IN ('0', '6') THEN -t.amount END),
+                  AVG(CASE WHEN strftime('%w', t.date) IN ('0', '6') THEN -t.amount END)
+           FROM transactions t
+           JOIN categories c ON t.category_id = c.id
+           WHERE t.account_id = ?1 AND t.amount < 0 AND t.date >= ?2
+           GROUP BY c.id"#,
+    )?;
+    let rows = stmt.query_map(params![account_id, format!("{}-01", start)], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, Option<f64>>(1)?, row.get::<_, Option<f64>>(2)?))
+    })?;
+
+    let mut anomalies = Vec::new();
+    for row in rows {
+        let (category_name, weekday_avg, weekend_avg) = row?;
+        let (Some(weekday_avg), Some(weekend_avg)) = (weekday_avg, weekend_avg) else {
+            continue;
+        };
+        let ratio = weekend_avg / weekday_avg;
+        if ratio > WEEKEND_ANOMALY_RATIO {
+            anomalies.push(WeekendAnomaly {
+                category_name,
+                weekday_avg: weekday_avg.round() as i64,
+                weekend_avg: weekend_avg.round() as i64,
+                ratio,
+            });
+        }
+    }
+    anomalies.sort_by(|a, b| b.ratio.total_cmp(&a.ratio));
+    Ok(anomalies)
+}
+
+/// Spending per calendar quarter (Q1-Q4) across all years, optionally for one category and its subcategories.
+/// Each quarter is averaged over the years that have spending in it; quarters without any data are all zeros.
+pub fn get_seasonal_spending(
+    conn: &Connection,
+    account_id: Option<i64>,
+    category_id: Option<i64>,
+) -> Result<Vec<SeasonalPattern>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"WITH RECURSIVE subtree(id) AS (
+               SELECT ?2
+               UNION
+               SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+           )
+           SELECT (CAST(strftime('%m', t.date) AS INTEGER) - 1) / 3 + 1 as quarter,
+                  strftime('%Y', t.date) as year, SUM(-t.amount)
+           FROM transactions t
+           WHERE t.amount < 0
+             AND (?1 IS NULL OR t.account_id = ?1)
+             AND (?2 IS NULL OR t.category_id IN (SELECT id FROM subtree))
+           GROUP BY quarter, year"#,
+    )?;
+    let mut by_quarter: HashMap<u32, Vec<i64>> = HashMap::new();
+    let rows = stmt.query_map(params![account_id, category_id], |row| {
+        Ok((row.get::<_, u32>(0)?, row.get::<_, i64>(2)?))
+    })?;
+    for row in rows {
+        let (quarter, total) = row?;
+        by_quarter.entry(quarter).or_default().push(total);
+    }
+
+    Ok((1..=4)
+        .map(|quarter| {
+            let totals = by_quarter.get(&quarter).map(|t| t.as_slice()).unwrap_or_default();
+            let avg_quarterly_spend = if totals.is_empty() {
+                0
+            } else {
+                totals.iter().sum::<i64>() / totals.len() as i64
+            };
+            SeasonalPattern {
+                quarter,
+                avg_quarterly_spend,
+                years_of_data: totals.len() as u32,
+                max_year_spend: totals.iter().copied().max().unwrap_or(0),
+                min_year_spend: totals.iter().copied().min().unwrap_or(0),
+            }
+        })
+        .collect())
+}
+
+/// Income per calendar year with growth over the year before, oldest first. The current year is
+/// projected to a full year from its income so far (× 12 / months elapsed) and marked as such.
+pub fn get_income_growth(conn: &Connection, account_id: Option<i64>) -> Result<Vec<IncomeGrowth>, rusqlite::Error> {
+    let today = current_date(conn)?;
+    let mut stmt = conn.prepare(
+        r#"SELECT CAST(strftime('%Y', date) AS INTEGER) as year, SUM(amount)
+           FROM transactions
+           WHERE amount > 0 AND (?1 IS NULL OR account_id = ?1)
+           GROUP BY year
+           ORDER BY year"#,
+    )?;
+    let rows = stmt.query_map(params![account_id], |row| Ok((row.get::<_, i32>(0)?, row.get::<_, i64>(1)?)))?;
+
+    let mut result: Vec<IncomeGrowth> = Vec::new();
+    for row in rows {
+        let (year, total) = row?;
+        let is_projected = year == today.year();
+        let total_income = if is_projected {
+            total * 12 / today.month() as i64
+        } else {
+            total
+        };
+        let growth_pct = result.last().map(|prior| percent_change(prior.total_income, total_income));
+        result.push(IncomeGrowth {
+            year,
+            total_income,
+            growth_pct,
+            is_projected,
+        });
+    }
+    Ok(result)
+}
+
+/// Split a month's spending into essential, discretionary and savings by category spending type
+pub fn get_essential_vs_discretionary(
+    conn: &Connection,
+    account_id: Option<i64>,
+    month: String,
+) -> Result<SpendingTypeBreakdown, rusqlite::Error> {
+    let month = budgets::validate_month(&month)?;
+    let mut stmt = conn.prepare(
+        r#"SELECT COALESCE(c.spending_type, 'uncategorized') as spending_type, SUM(ABS(t.amount))
+           FROM transactions t
+           LEFT JOIN categories c ON t.category_id = c.id
+           WHERE t.date LIKE ?1 || '%' AND t.amount < 0
+             AND (?2 IS NULL OR t.account_id = ?2)
+           GROUP BY spending_type"#,
+    )?;
+    let rows = stmt.query_map(params![month, account_id], |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)?))
+    })?;
+
+    let mut breakdown = SpendingTypeBreakdown {
+        essential: 0,
+        discretionary: 0,
+        savings: 0,
+        uncategorized: 0,
+    };
+    for row in rows {
+        let (spending_type, total) = row?;
+        match spending_type.as_str() {
+            "essential" => breakdown.essential += total,
+            "savings" => breakdown.savings += total,
+            "uncategorized" => breakdown.uncategorized += total,
+            _ => breakdown.discretionary += total,
+        }
+    }
+    Ok(breakdown)
+}
+
+/// Split a month's spending into committed (charges linked to a subscription) and everything else.
+/// Unlike `get_essential_vs_discretionary` this goes by actual subscription links, not categories.
+pub fn get_committed_vs_discretionary(
+    conn: &Connection,
+    account_id: i64,
+    month: String,
+) -> Result<CommittedSplit, rusqlite::Error> {
+    let month = budgets::validate_month(&month)?;
+    let (committed_total, discretionary_total): (i64, i64) = conn.query_row(
+        r#"SELECT
+            COALESCE(SUM(CASE WHEN linked THEN -amount ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN linked THEN 0 ELSE -amount END), 0)
+           FROM (
+               SELECT t.amount,
+                      EXISTS(SELECT 1 FROM subscription_transactions st WHERE st.transaction_id = t.id) AS linked
+               FROM transactions t
+               WHERE t.account_id = ?1 AND t.date LIKE ?2 || '%' AND t.amount < 0
+           )"#,
+        params![account_id, month],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let total = committed_total + discretionary_total;
+    Ok(CommittedSplit {
+        month,
+        committed_total,
+        discretionary_total,
+        committed_pct: if total > 0 { committed_total as f64 / total as f64 * 100.0 } else { 0.0 },
+    })
+}
+
+/// Number of categories listed in the monthly summary
+const SUMMARY_TOP_CATEGORIES: i64 = 5;
+
+/// How far ahead the monthly summary lists subscription charges
+const SUMMARY_UPCOMING_DAYS: i64 = 30;
+
+/// A titled section of the monthly summary, as (label, value) rows
+type SummarySection = (&'static str, Vec<(String, String)>);
+
+/// End-of-month report as plain text and HTML: account balances, income vs expenses, top
+/// categories, budget performance, subscription charges in the next 30 days and net savings.
+/// Budgets cover all accounts, so they are included even when `account_id` is set.
+pub fn format_monthly_summary(
+    conn: &Connection,
+    account_id: Option<i64>,
+    month: String,
+) -> Result<MonthlySummary, rusqlite::Error> {
+    let month = budgets::validate_month(&month)?;
+    let (start, end, _) = month_bounds(&month)?;
+    let (start_str, end_str) = (start.format("%Y-%m-%d").to_string(), end.format("%Y-%m-%d").to_string());
+    let accounts: Vec<_> = accounts::get_all(conn)?
+        .into_iter()
+        .filter(|a| account_id.is_none() || a.id == account_id)
+        .collect();
+
+    let mut balances = Vec::new();
+    for account in &accounts {
+        let balance = transactions::balance_at(conn, account.id.unwrap_or_default(), &end_str)?;
+        balances.push((account.name.clone(), format_kr(balance)));
+    }
+
+    let totals = match account_id {
+        Some(id) => transactions::savings_rate(conn, id, &start_str, &end_str)?,
+        None => transactions::savings_rate_all_accounts(conn, &start_str, &end_str)?,
+    };
+    let income_vs_expenses = vec![
+        ("Income".to_string(), format_kr(totals.income)),
+        ("Expenses".to_string(), format_kr(totals.expense)),
+    ];
+
+    let mut stmt = conn.prepare(
+        r#"SELECT COALESCE(c.name, 'Uncategorized'), SUM(-t.amount) as total
+           FROM transactions t
+           LEFT JOIN categories c ON t.category_id = c.id
+           WHERE t.amount < 0 AND t.date >= ?1 AND t.date <= ?2
+             AND (?3 IS NULL OR t.account_id = ?3)
+           GROUP BY t.category_id
+           ORDER BY total DESC
+           LIMIT ?4"#,
+    )?;
+    let top_categories = stmt
+        .query_map(params![start_str, end_str, account_id, SUMMARY_TOP_CATEGORIES], |row| {
+            Ok((row.get::<_, String>(0)?, format_kr(row.get::<_, i64>(1)?)))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let budget_rows = budgets::get_budgets_with_spending(conn, &month, None)?
+        .into_iter()
+        .filter(|b| b.allocated_amount > 0)
+        .map(|b| {
+            let value = format!(
+                "{} of {} ({:.0}%)",
+                format_kr(b.spent_amount),
+                format_kr(b.allocated_amount),
+                b.percent_spent
+            );
+            (b.budget.name, value)
+        })
+        .collect();
+
+    let today = current_date(conn)?;
+    let (upcoming_start, upcoming_end) = (
+        today.format("%Y-%m-%d").to_string(),
+        (today + chrono::Duration::days(SUMMARY_UPCOMING_DAYS)).format("%Y-%m-%d").to_string(),
+    );
+    // Outflow streams are shared by all accounts, so they're added once rather than per account
+    let mut charges = Vec::new();
+    for account in &accounts {
+        charges.extend(get_subscription_calendar(conn, account.id.unwrap_or_default(), &upcoming_start, &upcoming_end)?);
+    }
+    charges.extend(
+        get_income_stream_calendar(conn, &upcoming_start, &upcoming_end)?
+            .into_iter()
+            .filter(|event| event.event_type == "charge"),
+    );
+    charges.sort_by(|a, b| a.date.cmp(&b.date));
+    let upcoming = charges
+        .into_iter()
+        .map(|event| (format!("{} {}", event.date, event.name), format_kr(event.amount.abs())))
+        .collect();
+
+    let net_savings = vec![
+        ("Saved".to_string(), format_kr(totals.income - totals.expense)),
+        ("Savings rate".to_string(), format!("{:.1}%", totals.savings_rate)),
+    ];
+
+    let sections: Vec<SummarySection> = vec![
+        ("Account balances", balances),
+        ("Income vs expenses", income_vs_expenses),
+        ("Top categories", top_categories),
+        ("Budgets", budget_rows),
+        ("Upcoming subscriptions (next 30 days)", upcoming),
+        ("Net savings", net_savings),
+    ];
+    Ok(MonthlySummary {
+        subject: format!("Monthly summary for {}", start.format("%B %Y")),
+        body_plain_text: summary_plain_text(&sections),
+        body_html: summary_html(&sections),
+    })
+}
+
+fn summary_plain_text(sections: &[SummarySection]) -> String {
+    let mut text = String::new();
+    for (title, rows) in sections {
+        text.push_str(&format!("{}\n{}\n", title, "-".repeat(title.chars().count())));
+        if rows.is_empty() {
+            text.push_str("None\n");
+        }
+        for (label, value) in rows {
+            text.push_str(&format!("{}: {}\n", label, value));
+        }
+        text.push('\n');
+    }
+    text.trim_end().to_string()
+}
+
+fn summary_html(sections: &[SummarySection]) -> String {
+    let mut html = String::new();
+    for (title, rows) in sections {
+        html.push_str(&format!("<h2>{}</h2>\n", escape_html(title)));
+        if rows.is_empty() {
+            html.push_str("<p>None</p>\n");
+            continue;
+        }
+        html.push_str("<table>\n");
+        for (label, value) in rows {
+            html.push_str(&format!(
+                "<tr><td>{}</td><td style=\"text-align: right\">{}</td></tr>\n",
+                escape_html(label),
+                escape_html(value)
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html
+}
+
+/// Engagement with a subscription: purchases at the same merchant over the last `months` months
+/// (including the current one) that are not the subscription charge itself, e.g. smoothies at the gym.
+/// The score is the number of such purchases per month; zero says nothing about value for charges
+/// like streaming services where there is nothing else to buy.
+pub fn get_subscription_engagement(
+    conn: &Connection,
+    subscription_id: i64,
+    months: u32,
+) -> Result<SubscriptionEngagement, rusqlite::Error> {
+    let subscription = subscriptions::get_by_id(conn, subscription_id)?
+        .ok_or_else(|| rusqlite::Error::InvalidParameterName("Subscription not found".to_string()))?;
+    let month_keys = last_n_months(conn, months)?;
+    let Some(start) = month_keys.first() else {
+        return Ok(SubscriptionEngagement {
+            subscription,
+            engagement_score: 0.0,
+            related_transactions: Vec::new(),
+        });
+    };
+
+    let end = current_date(conn)?.format("%Y-%m-%d").to_string();
+    let related_transactions: Vec<Transaction> =
+        transactions::get_by_date_range(conn, subscription.account_id, &format!("{}-01", start), &end)?
+            .into_iter()
+            .map(|t| t.transaction)
+            .filter(|t| {
+                t.amount != subscription.amount
+                    && t.subscription_id != subscription.id
+                    && !t.id.is_some_and(|id| subscription.transaction_ids.contains(&id))
+                    && subscription_engine::normalize_payee(&t.payee) == subscription.payee_pattern
+            })
+            .collect();
+
+    Ok(SubscriptionEngagement {
+        engagement_score: related_transactions.len() as f64 / month_keys.len() as f64,
+        subscription,
+        related_transactions,
+    })
+}
+
+/// Built-in benchmark profiles as (name, needs %, wants %, savings %)
+const BENCHMARK_PROFILES: [(&str, f64, f64, f64); 1] = [("50-30-20", 50.0, 30.0, 20.0)];
+
+/// Target percentages for a benchmark profile. Custom profiles are stored in the
+/// `benchmark_profile:{name}` setting as `{"needs": 60, "wants": 25, "savings": 15}`.
+fn benchmark_targets(conn: &Connection, profile: &str) -> Result<(f64, f64, f64), rusqlite::Error> {
+    if let Some(&(_, needs, wants, savings)) = BENCHMARK_PROFILES.iter().find(|(name, ..)| *name == profile) {
+        return Ok((needs, wants, savings));
+    }
+    let invalid = |reason: &str| {
+        rusqlite::Error::InvalidParameterName(format!("Benchmark profile '{}' {}", profile, reason))
+    };
+    let value = settings::get_setting(conn, &format!("benchmark_profile:{}", profile))?
+        .ok_or_else(|| invalid("does not exist"))?;
+    let target = |key: &str| value.get(key).and_then(|v| v.as_f64()).filter(|pct| *pct >= 0.0);
+    match (target("needs"), target("wants"), target("savings")) {
+        (Some(needs), Some(wants), Some(savings)) if ((needs + wants + savings) - 100.0).abs() < 0.01 => {
+            Ok((needs, wants, savings))
+        }
+        _ => Err(invalid("must define needs, wants and savings percentages adding up to 100")),
+    }
+}
+
+/// Compare a month's spending split against a benchmark such as 50/30/20 (needs/wants/savings).
+/// Shares are of the month's income, or of total spending when there was no income.
+/// The score drops by half the total percentage-point deviation from the targets.
+pub fn get_spending_benchmark(
+    conn: &Connection,
+    account_id: Option<i64>,
+    month: String,
+    benchmark_profile: String,
+) -> Result<BenchmarkReport, rusqlite::Error> {
+    let month = budgets::validate_month(&month)?;
+    let (target_needs_pct, target_wants_pct, target_savings_pct) = benchmark_targets(conn, &benchmark_profile)?;
+
+    let spending = get_essential_vs_discretionary(conn, account_id, month.clone())?;
+    let income: i64 = conn.query_row(
+        r#"SELECT COALESCE(SUM(amount), 0) FROM transactions
+           WHERE date LIKE ?1 || '%' AND amount > 0
+             AND (?2 IS NULL OR account_id = ?2)"#,
+        params![month, account_id],
+        |row| row.get(0),
+    )?;
+    let wants = spending.discretionary + spending.uncategorized;
+    let base = if income > 0 {
+        income
+    } else {
+        spending.essential + wants + spending.savings
+    };
+    let share = |amount: i64| if base > 0 { amount as f64 / base as f64 * 100.0 } else { 0.0 };
+    let (actual_needs_pct, actual_wants_pct, actual_savings_pct) =
+        (share(spending.essential), share(wants), share(spending.savings));
+
+    let deviation = (actual_needs_pct - target_needs_pct).abs()
+        + (actual_wants_pct - target_wants_pct).abs()
+        + (actual_savings_pct - target_savings_pct).abs();
+    Ok(BenchmarkReport {
+        month,
+        profile: benchmark_profile,
+        actual_needs_pct,
+        target_needs_pct,
+        actual_wants_pct,
+        target_wants_pct,
+        actual_savings_pct,
+        target_savings_pct,
+        overall_score: (100.0 - deviation / 2.0).clamp(0.0, 100.0),
+    })
+}
+
+/// Per-payee spending with a category split, for payees with at least two purchases in the range
+pub fn get_merchant_breakdown(
+    conn: &Connection,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<MerchantBreakdown>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT payee, SUM(ABS(amount)) as total, MAX(date), COUNT(*)
+           FROM transactions
+           WHERE account_id = ?1 AND date >= ?2 AND date <= ?3 AND amount < 0
+           GROUP BY payee
+           HAVING COUNT(*) >= 2
+           ORDER BY total DESC"#,
+    )?;
+    let merchants: Vec<(String, i64, String, i64)> = stmt
+        .query_map(params![account_id, start_date, end_date], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut category_stmt = conn.prepare(
+        r#"SELECT COALESCE(c.name, 'Uncategorized') as category, SUM(ABS(t.amount)) as total
+           FROM transactions t
+           LEFT JOIN categories c ON t.category_id = c.id
+           WHERE t.account_id = ?1 AND t.date >= ?2 AND t.date <= ?3 AND t.amount < 0 AND t.payee = ?4
+           GROUP BY category
+           ORDER BY total DESC"#,
+    )?;
+
+    let mut result = Vec::new();
+    for (payee, total_spent, last_visit, visit_count) in merchants {
+        let category_breakdown = category_stmt
+            .query_map(params![account_id, start_date, end_date, payee], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        result.push(MerchantBreakdown {
+            payee,
+            total_spent,
+            category_breakdown,
+            last_visit,
+            visit_count,
+        });
+    }
+    Ok(result)
+}
+
+/// How long each payee has been a merchant the user spends with: first and last expense, the days
+/// between them, and totals. Only payees with at least `min_visits` expenses; longest relationship first.
+pub fn get_merchant_tenure(
+    conn: &Connection,
+    account_id: Option<i64>,
+    min_visits: u32,
+) -> Result<Vec<MerchantTenure>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT payee, MIN(date), MAX(date),
+                  CAST(julianday(MAX(date)) - julianday(MIN(date)) AS INTEGER) as tenure_days,
+                  SUM(-amount), COUNT(*)
+           FROM transactions
+           WHERE amount < 0 AND (?1 IS NULL OR account_id = ?1)
+           GROUP BY payee
+           HAVING COUNT(*) >= ?2
+           ORDER BY tenure_days DESC, payee"#,
+    )?;
+    let rows = stmt.query_map(params![account_id, min_visits], |row| {
+        Ok(MerchantTenure {
+            payee: row.get(0)?,
+            first_date: row.get(1)?,
+            last_date: row.get(2)?,
+            tenure_days: row.get(3)?,
+            total_spent: row.get(4)?,
+            visit_count: row.get(5)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Per-category spending change from the prior month to `current_month`, largest change first.
+/// New spending counts as +100% and spending that stopped as -100%.
+pub fn get_category_mom_changes(
+    conn: &Connection,
+    account_id: Option<i64>,
+    current_month: String,
+) -> Result<Vec<CategoryMoMChange>, rusqlite::Error> {
+    let current_month = budgets::validate_month(&current_month)?;
+    let (start, _, _) = month_bounds(&current_month)?;
+    let prior_month = first_of_month_offset(start, -1).format("%Y-%m").to_string();
+
+    let mut stmt = conn.prepare(
+        r#"SELECT c.id, COALESCE(c.name, 'Uncategorized'),
+                  COALESCE(SUM(CASE WHEN t.date LIKE ?1 || '%' THEN -t.amount ELSE 0 END), 0),
+                  COALESCE(SUM(CASE WHEN t.date LIKE ?2 || '%' THEN -t.amount ELSE 0 END), 0)
+           FROM transactions t
+           LEFT JOIN categories c ON t.category_id = c.id
+           WHERE (t.date LIKE ?1 || '%' OR t.date LIKE ?2 || '%') AND t.amount < 0
+             AND (?3 IS NULL OR t.account_id = ?3)
+           GROUP BY c.id"#,
+    )?;
+    let mut changes: Vec<CategoryMoMChange> = stmt
+        .query_map(params![current_month, prior_month, account_id], |row| {
+            let current: i64 = row.get(2)?;
+            let prior: i64 = row.get(3)?;
+            Ok(CategoryMoMChange {
+                category_id: row.get(0)?,
+                category_name: row.get(1)?,
+                current,
+                prior,
+                absolute_change: current - prior,
+                pct_change: percent_change(prior, current),
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    changes.sort_by_key(|c| std::cmp::Reverse(c.absolute_change.abs()));
+    Ok(changes)
+}
+
+/// Current and longest runs of consecutive months with spending in a category (including its subcategories).
+/// The current streak counts back from the current month, so it is 0 if nothing has been spent yet this month.
+pub fn get_category_spending_streak(
+    conn: &Connection,
+    category_id: i64,
+    account_id: Option<i64>,
+) -> Result<CategoryStreak, rusqlite::Error> {
+    let category = categories::get_by_id(conn, category_id)?
+        .ok_or_else(|| rusqlite::Error::InvalidParameterName("Category not found".to_string()))?;
+
+    let mut stmt = conn.prepare(
+        r#"WITH RECURSIVE subtree(id) AS (
+               SELECT ?1
+               UNION
+               SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+           )
+           SELECT DISTINCT substr(t.date, 1, 7) as month
+           FROM transactions t
+           WHERE t.category_id IN (SELECT id FROM subtree) AND t.amount < 0
+             AND (?2 IS NULL OR t.account_id = ?2)
+           ORDER BY month ASC"#,
+    )?;
+    let months: Vec<i32> = stmt
+        .query_map(params![category_id, account_id], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .filter_map(|m| month_index(m))
+        .collect();
+
+    // Longest run of consecutive months, scanning the full history
+    let mut longest = 0u32;
+    let mut run = 0u32;
+    let mut previous: Option<i32> = None;
+    for &month in &months {
+        run = if previous == Some(month - 1) { run + 1 } else { 1 };
+        longest = longest.max(run);
+        previous = Some(month);
+    }
+
+    // Current run: walk back from this month until a month without spending
+    let today = current_date(conn)?;
+    let mut expected = today.year() * 12 + today.month0() as i32;
+    let mut current = 0u32;
+    for &month in months.iter().rev() {
+        if month > expected {
+            continue;
+        }
+        if month != expected {
+            break;
+        }
+        current += 1;
+        expected -= 1;
+    }
+
+    Ok(CategoryStreak {
+        category_id,
+        category_name: category.name,
+        current_streak_months: current,
+        longest_streak_months: longest,
+    })
+}
+
+/// Coefficient of variation below these marks the labels "very consistent", "consistent" and
+/// "variable"; anything higher is "unpredictable"
+const CONSISTENCY_THRESHOLDS: [(f64, &str); 3] = [(0.1, "very consistent"), (0.3, "consistent"), (0.6, "variable")];
+
+/// How predictable each category's spending on an account was over the last `months` completed
+/// months, as the coefficient of variation (std dev / mean) of its monthly totals. Months without
+/// spending count as zero; categories with no spending at all are left out. Most consistent first.
+pub fn get_category_consistency(
+    conn: &Connection,
+    account_id: i64,
+    months: u32,
+) -> Result<Vec<CategoryConsistency>, rusqlite::Error> {
+    // The current month is still in progress, so it would always look like an outlier
+    let mut month_keys = last_n_months(conn, months + 1)?;
+    let current_month = month_keys.pop().unwrap_or_default();
+    let Some(start) = month_keys.first().cloned() else {
+        return Ok(Vec::new());
+    };
+
+    let mut stmt = conn.prepare(
+        r#"SELECT c.id, c.name, substr(t.date, 1, 7) as month, SUM(-t.amount)
+           FROM transactions t
+           JOIN categories c ON t.category_id = c.id
+           WHERE t.account_id = ?1 AND t.amount < 0 AND t.date >= ?2 AND t.date < ?3
+           GROUP BY c.id, month"#,
+    )?;
+    let rows = stmt.query_map(
+        params![account_id, format!("{}-01", start), format!("{}-01", current_month)],
+        |row| Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, String>(2)?, row.get::<_, i64>(3)?)),
+    )?;
+    // Keyed by id: two subcategories can share a name under different parents
+    let mut by_category: HashMap<i64, (String, HashMap<String, i64>)> = HashMap::new();
+    for row in rows {
+        let (id, name, month, total) = row?;
+        by_category.entry(id).or_insert_with(|| (name, HashMap::new())).1.insert(month, total);
+    }
+
+    let n = month_keys.len() as f64;
+    let mut result: Vec<CategoryConsistency> = by_category
+        .into_iter()
+        .map(|(category_id, (category_name, totals))| {
+            let values: Vec<f64> = month_keys
+                .iter()
+                .map(|m| totals.get(m).copied().unwrap_or(0) as f64)
+                .collect();
+            let mean = values.iter().sum::<f64>() / n;
+            let std_dev = (values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n).sqrt();
+            let cv = if mean > 0.0 { std_dev / mean } else { 0.0 };
+            let consistency_label = CONSISTENCY_THRESHOLDS
+                .iter()
+                .find(|(limit, _)| cv < *limit)
+                .map(|(_, label)| *label)
+                .unwrap_or("unpredictable");
+            CategoryConsistency {
+                category_id,
+                category_name,
+                mean_monthly: mean.round() as i64,
+                std_dev: std_dev.round() as i64,
+                cv,
+                consistency_label: consistency_label.to_string(),
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| {
+        a.cv.total_cmp(&b.cv)
+            .then_with(|| a.category_name.cmp(&b.category_name))
+            .then_with(|| a.category_id.cmp(&b.category_id))
+    });
+    Ok(result)
+}
+
+/// Upper percentile bounds of the quintile labels; anything higher is "very high"
+const PERCENTILE_LABELS: [(f64, &str); 4] =
+    [(20.0, "very low"), (40.0, "below average"), (60.0, "average"), (80.0, "above average")];
+
+/// How a month's spending in a category (subcategories included) compares with every month from
+/// the first transaction up to and including `month`. Months without spending count as zero; ties
+/// count half, so a month equal to all others sits at the 50th percentile.
+pub fn get_spending_percentile(
+    conn: &Connection,
+    category_id: i64,
+    month: String,
+    account_id: Option<i64>,
+) -> Result<SpendingPercentile, rusqlite::Error> {
+    let month = budgets::validate_month(&month)?;
+    let category = categories::get_by_id(conn, category_id)?
+        .ok_or_else(|| rusqlite::Error::InvalidParameterName("Category not found".to_string()))?;
+
+    let first_month: Option<String> = conn.query_row(
+        "SELECT MIN(substr(date, 1, 7)) FROM transactions WHERE ?1 IS NULL OR account_id = ?1",
+        params![account_id],
+        |row| row.get(0),
+    )?;
+    let end = month_index(&month).unwrap_or_default();
+    let start = first_month.as_deref().and_then(month_index).unwrap_or(end).min(end);
+
+    let mut stmt = conn.prepare(
+        r#"WITH RECURSIVE subtree(id) AS (
+               SELECT ?1
+               UNION
+               SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+           )
+           SELECT substr(t.date, 1, 7) as month, SUM(-t.amount)
+           FROM transactions t
+           WHERE t.category_id IN (SELECT id FROM subtree) AND t.amount < 0
+             AND (?2 IS NULL OR t.account_id = ?2)
+           GROUP BY month"#,
+    )?;
+    let totals: HashMap<String, i64> = stmt
+        .query_map(params![category_id, account_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<Result<_, _>>()?;
+
+    let mut values: Vec<i64> = (start..=end)
+        .map(|index| {
+            let key = format!("{:04}-{:02}", index.div_euclid(12), index.rem_euclid(12) + 1);
+            totals.get(&key).copied().unwrap_or(0)
+        })
+        .collect();
+    values.sort();
+    let month_spending = totals.get(&month).copied().unwrap_or(0);
+    let below = values.iter().filter(|v| **v < month_spending).count() as f64;
+    let equal = values.iter().filter(|v| **v == month_spending).count() as f64;
+    let percentile = (below + equal / 2.0) / values.len() as f64 * 100.0;
+    let label = PERCENTILE_LABELS
+        .iter()
+        .find(|(limit, _)| percentile < *limit)
+        .map(|(_, label)| *label)
+        .unwrap_or("very high");
+
+    Ok(SpendingPercentile {
+        category_name: category.name,
+        month_spending,
+        historical_mean: (values.iter().sum::<i64>() as f64 / values.len() as f64).round() as i64,
+        percentile,
+        label: label.to_string(),
+    })
+}
+
+/// Spending and income between two dates (inclusive), grouped by account type
+pub fn get_spending_by_account_type(
+    conn: &Connection,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<AccountTypeSpending>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT a.account_type,
+                  COALESCE(SUM(CASE WHEN t.amount < 0 THEN -t.amount ELSE 0 END), 0),
+                  COALESCE(SUM(CASE WHEN t.amount > 0 THEN t.amount ELSE 0 END), 0),
+                  COUNT(DISTINCT a.id)
+           FROM transactions t
+           JOIN accounts a ON t.account_id = a.id
+           WHERE t.date >= ?1 AND t.date <= ?2
+           GROUP BY a.account_type
+           ORDER BY 2 DESC"#,
+    )?;
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        Ok(AccountTypeSpending {
+            account_type: row.get(0)?,
+            total_spent: row.get(1)?,
+            total_income: row.get(2)?,
+            account_count: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Expected subscription charges and income payments between two dates (inclusive), sorted by date.
+/// Subscriptions are projected from their next charge date; active income streams from the last
+/// time they were received (streams never received yet are left out).
+pub fn get_recurring_calendar(
+    conn: &Connection,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<CalendarEvent>, rusqlite::Error> {
+    let mut events = get_subscription_calendar(conn, account_id, &start_date, &end_date)?;
+    events.extend(get_income_stream_calendar(conn, &start_date, &end_date)?);
+    events.sort_by(|a, b| a.date.cmp(&b.date).then_with(|| a.name.cmp(&b.name)));
+    Ok(events)
+}
+
+/// The subscription charges of `get_recurring_calendar`, for one account
+pub fn get_subscription_calendar(
+    conn: &Connection,
+    account_id: i64,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<CalendarEvent>, rusqlite::Error> {
+    let mut events = Vec::new();
+    for sub in subscriptions::get_by_account(conn, account_id)? {
+        let anchor = sub.next_charge_date.clone().or_else(|| {
+            sub.last_charge_date
+                .as_deref()
+                .and_then(|last| subscription_engine::predict_next_date(last, &sub.frequency))
+        });
+        for date in recurring_dates(anchor, &sub.frequency, start_date, end_date) {
+            events.push(CalendarEvent {
+                date,
+                event_type: "charge".to_string(),
+                name: sub.payee_pattern.clone(),
+                amount: sub.amount,
+                source_id: sub.id.unwrap_or_default(),
+                source_type: "subscription".to_string(),
+            });
+        }
+    }
+    Ok(events)
+}
+
+/// The income stream payments of `get_recurring_calendar`. Streams aren't tied to an account,
+/// so these are the same for every account; outflow streams come back as charges.
+pub fn get_income_stream_calendar(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<CalendarEvent>, rusqlite::Error> {
+    let mut events = Vec::new();
+    for entry in income_streams::get_all_with_last_received(conn)? {
+        let stream = entry.stream;
+        if !stream.is_active {
+            continue;
+        }
+        let anchor = entry
+            .last_received
+            .and_then(|tx| subscription_engine::predict_next_date(&tx.date, &stream.frequency));
+        for date in recurring_dates(anchor, &stream.frequency, start_date, end_date) {
+            events.push(CalendarEvent {
+                date,
+                event_type: if stream.direction == "outflow" { "charge" } else { "income" }.to_string(),
+                name: stream.name.clone(),
+                amount: stream.expected_amount,
+                source_id: stream.id.unwrap_or_default(),
+                source_type: "income_stream".to_string(),
+            });
+        }
+    }
+    Ok(events)
+}
+
+/// Statement for one account between two dates (inclusive): balances, totals, spending per
+/// top-level category and every transaction (newest first)
+pub fn get_account_statement(
+    conn: &Connection,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<AccountStatement, rusqlite::Error> {
+    let account = accounts::get_by_id(conn, account_id)?
+        .ok_or_else(|| rusqlite::Error::InvalidParameterName("Account not found".to_string()))?;
+    let start = NaiveDate::parse_from_str(&start_date, "%Y-%m-%d")
+        .map_err(|e| rusqlite::Error::InvalidParameterName(format!("Invalid start date: {}", e)))?;
+    let day_before = start.pred_opt().unwrap_or(start).format("%Y-%m-%d").to_string();
+
+    let opening_balance = transactions::balance_at(conn, account_id, &day_before)?;
+    let closing_balance = transactions::balance_at(conn, account_id, &end_date)?;
+    let category_breakdown = transactions::get_spending_by_category(conn, account_id, &start_date, &end_date)?;
+    let period = transactions::get_by_date_range(conn, account_id, &start_date, &end_date)?;
+
+    let amounts = period.iter().map(|t| t.transaction.amount);
+    let total_credits = amounts.clone().filter(|a| *a > 0).sum();
+    let total_debits = -amounts.filter(|a| *a < 0).sum::<i64>();
+    let largest_expense = period
+        .iter()
+        .filter(|t| t.transaction.amount < 0)
+        .min_by_key(|t| t.transaction.amount)
+        .cloned();
+
+    Ok(AccountStatement {
+        account,
+        period_start: start_date,
+        period_end: end_date,
+        opening_balance,
+        closing_balance,
+        total_credits,
+        total_debits,
+        transaction_count: period.len() as i64,
+        category_breakdown,
+        largest_expense,
+        transactions: period,
+    })
+}
+
+/// Forecast monthly spending in a category (including its subcategories) by fitting a least-squares
+/// line through the last `history_months` completed months and extending it `forecast_months` ahead.
+/// The confidence interval is the prediction ± 1.96 residual standard deviations, floored at zero.
+pub fn forecast_category_spending(
+    conn: &Connection,
+    category_id: i64,
+    forecast_months: u32,
+    history_months: u32,
+) -> Result<SpendingForecast, rusqlite::Error> {
+    categories::get_by_id(conn, category_id)?
+        .ok_or_else(|| rusqlite::Error::InvalidParameterName("Category not found".to_string()))?;
+
+    // The current month is still in progress, so history ends with last month
+    let mut month_keys = last_n_months(conn, history_months + 1)?;
+    let current_month = month_keys.pop().unwrap_or_default();
+    let start = month_keys.first().cloned().unwrap_or_else(|| current_month.clone());
+
+    let mut stmt = conn.prepare(
+        r#"WITH RECURSIVE subtree(id) AS (
+               SELECT ?1
+               UNION
+               SELECT c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+           )
+           SELECT substr(t.date, 1, 7) as month, SUM(ABS(t.amount)), COUNT(*)
+           FROM transactions t
+           WHERE t.category_id IN (SELECT id FROM subtree) AND t.amount < 0
+             AND t.date >= ?2 AND t.date < ?3
+           GROUP BY month"#,
+    )?;
+    let totals: HashMap<String, (i64, i64)> = stmt
+        .query_map(
+            params![category_id, format!("{}-01", start), format!("{}-01", current_month)],
+            |row| Ok((row.get(0)?, (row.get(1)?, row.get(2)?))),
+        )?
+        .collect::<Result<_, _>>()?;
+
+    let historical: Vec<MonthlyAmount> = month_keys
+        .into_iter()
+        .map(|month| {
+            let (total, count) = totals.get(&month).copied().unwrap_or((0, 0));
+            MonthlyAmount { month, total, count }
+        })
+        .collect();
+
+    // y = mx + b with x = month index into the history
+    let values: Vec<i64> = historical.iter().map(|m| m.total).collect();
+    let n = values.len() as f64;
+    let slope = linear_slope(&values);
+    let mean_y = if values.is_empty() { 0.0 } else { values.iter().sum::<i64>() as f64 / n };
+    let intercept = mean_y - slope * (n - 1.0).max(0.0) / 2.0;
+
+    let residual_std = if values.len() > 2 {
+        let sum_sq: f64 = values
+            .iter()
+            .enumerate()
+            .map(|(i, &y)| (y as f64 - (slope * i as f64 + intercept)).powi(2))
+            .sum();
+        (sum_sq / (n - 2.0)).sqrt()
+    } else {
+        0.0
+    };
+    let margin = 1.96 * residual_std;
+
+    let today = current_date(conn)?;
+    let forecast = (0..forecast_months as i32)
+        .map(|offset| {
+            let x = n + offset as f64 + 1.0; // The current month sits at x = n
+            let predicted = (slope * x + intercept).max(0.0);
+            ForecastPoint {
+                month: first_of_month_offset(today, offset + 1).format("%Y-%m").to_string(),
+                predicted: predicted.round() as i64,
+                confidence_interval: (
+                    (predicted - margin).max(0.0).round() as i64,
+                    (predicted + margin).round() as i64,
+                ),
+            }
+        })
+        .collect();
+
+    Ok(SpendingForecast { historical, forecast })
+}
+
+/// Check every recorded transfer: both legs must be on different accounts, for equal and opposite
+/// amounts, and no more than 3 business days apart. Newest transfers first.
+pub fn get_transfer_reconciliation(conn: &Connection) -> Result<Vec<TransferReconciliation>, rusqlite::Error> {
+    const MAX_BUSINESS_DAYS_APART: i64 = 3;
+
+    let mut result = Vec::new();
+    for transfer in transfers::get_all(conn)? {
+        let debit = transactions::get_with_category_by_id(conn, transfer.debit_transaction_id)?;
+        let credit = transactions::get_with_category_by_id(conn, transfer.credit_transaction_id)?;
+        let (Some(debit), Some(credit)) = (debit, credit) else {
+            continue;
+        };
+
+        let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+        let (date_difference_days, business_days) =
+            match (parse(&debit.transaction.date), parse(&credit.transaction.date)) {
+                (Some(d), Some(c)) => ((c - d).num_days().abs(), business_days_between(d.min(c), d.max(c))),
+                _ => (0, 0),
+            };
+
+        let is_balanced = debit.transaction.amount == -credit.transaction.amount
+            && debit.transaction.account_id != credit.transaction.account_id
+            && business_days <= MAX_BUSINESS_DAYS_APART;
+
+        result.push(TransferReconciliation {
+            debit,
+            credit,
+            is_balanced,
+            date_difference_days,
+        });
+    }
+    Ok(result)
+}
+
+/// Resolve a named date range preset to inclusive (start_date, end_date) ISO dates, relative to today
+pub fn date_range(conn: &Connection, preset: &str) -> Result<(String, String), String> {
+    date_range_from(preset, current_date(conn).map_err(|e| e.to_string())?)
+}
+
+/// Resolve a date range preset relative to a given day.
+/// Supported presets: this_month, last_month, last_3_months, last_12_months, ytd, this_year.
+pub fn date_range_from(preset: &str, today: NaiveDate) -> Result<(String, String), String> {
+    let month_start = |offset: i32| first_of_month_offset(today, offset);
+    let month_end = |offset: i32| first_of_month_offset(today, offset + 1).pred_opt().unwrap_or(today);
+    let year_start = NaiveDate::from_ymd_opt(today.year(), 1, 1).unwrap_or(today);
+
+    let (start, end) = match preset {
+        "this_month" => (month_start(0), month_end(0)),
+        "last_month" => (month_start(-1), month_end(-1)),
+        "last_3_months" => (month_start(-2), month_end(0)),
+        "last_12_months" => (month_start(-11), month_end(0)),
+        "ytd" => (year_start, today),
+        "this_year" => (
+            year_start,
+            NaiveDate::from_ymd_opt(today.year(), 12, 31).unwrap_or(today),
+        ),
+        _ => return Err(format!("Unknown date range preset: {}", preset)),
+    };
+
+    Ok((
+        start.format("%Y-%m-%d").to_string(),
+        end.format("%Y-%m-%d").to_string(),
+    ))
+}
+
+/// Explain the change in net worth between the end of `from_month` and the end of `to_month`
+pub fn get_net_worth_change_attribution(
+    conn: &Connection,
+    from_month: String,
+    to_month: String,
+) -> Result<NetWorthChangeAttribution, rusqlite::Error> {
+    let (_, from_end, _) = month_bounds(&from_month)?;
+    let (_, to_end, _) = month_bounds(&to_month)?;
+    let from_date = from_end.format("%Y-%m-%d").to_string();
+    let to_date = to_end.format("%Y-%m-%d").to_string();
+
+    let mut from = 0;
+    let mut to = 0;
+    for account in accounts::get_all(conn)? {
+        let account_id = account.id.unwrap_or_default();
+        from += transactions::balance_at(conn, account_id, &from_date)?;
+        to += transactions::balance_at(conn, account_id, &to_date)?;
+    }
+
+    // Movements strictly after the start point, up to and including the end point
+    let period_start = (from_end + chrono::Duration::days(1)).format("%Y-%m-%d").to_string();
+    let transfer_ids: HashSet<i64> = transactions::find_transfer_pairs(conn, &period_start, &to_date)?
+        .into_iter()
+        .flat_map(|(out_id, in_id)| [out_id, in_id])
+        .collect();
+
+    let mut stmt = conn.prepare(
+        r#"SELECT t.id, t.amount,
+                  EXISTS(SELECT 1 FROM subscription_transactions st WHERE st.transaction_id = t.id)
+           FROM transactions t
+           WHERE t.date >= ?1 AND t.date <= ?2"#,
+    )?;
+    let rows = stmt.query_map(params![period_start, to_date], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, bool>(2)?))
+    })?;
+
+    let (mut income, mut expenses, mut subscriptions_total, mut transfers_net) = (0, 0, 0, 0);
+    for row in rows {
+        let (id, amount, is_subscription) = row?;
+        if transfer_ids.contains(&id) {
+            transfers_net += amount;
+        } else if amount > 0 {
+            income += amount;
+        } else if is_subscription {
+            subscriptions_total += -amount;
+        } else {
+            expenses += -amount;
+        }
+    }
+
+    Ok(NetWorthChangeAttribution {
+        from,
+        to,
+        change: to - from,
+        income,
+        expenses,
+        subscriptions_total,
+        transfers_net,
+    })
+}
+
+/// Average utilization below this (in percent) means a budget is over-allocated
+const UNDERUSED_UTILIZATION_PCT: f64 = 50.0;
+/// Average utilization above this (in percent) means a budget is under-allocated
+const OVERUSED_UTILIZATION_PCT: f64 = 90.0;
+/// Utilization that suggested allocation changes aim for, the middle of the well-calibrated band
+const TARGET_UTILIZATION_PCT: f64 = 70.0;
+
+/// How well each budget's allocation matched its spending over the last `months` completed months.
+/// Utilization is spent / allocated in percent; months without an allocation are ignored, and
+/// budgets that had no allocation in any of them are left out.
+pub fn get_budget_efficiency(conn: &Connection, months: u32) -> Result<Vec<BudgetEfficiency>, rusqlite::Error> {
+    // The current month is still in progress, so it would understate utilization
+    let mut month_keys = last_n_months(conn, months + 1)?;
+    month_keys.pop();
+
+    let mut budgets: Vec<Budget> = Vec::new();
+    let mut utilizations: HashMap<i64, Vec<f64>> = HashMap::new();
+    for month in &month_keys {
+        for entry in budgets::get_budgets_with_spending(conn, month, None)? {
+            let Some(budget_id) = entry.budget.id else {
+                continue;
+            };
+            if entry.allocated_amount <= 0 {
+                continue;
+            }
+            if !utilizations.contains_key(&budget_id) {
+                budgets.push(entry.budget.clone());
+            }
+            utilizations.entry(budget_id).or_default().push(entry.percent_spent);
+        }
+    }
+
+    let mut result: Vec<BudgetEfficiency> = budgets
+        .into_iter()
+        .map(|budget| {
+            let values = &utilizations[&budget.id.unwrap_or_default()];
+            let n = values.len() as f64;
+            let avg_utilization = values.iter().sum::<f64>() / n;
+            let variance = values.iter().map(|v| (v - avg_utilization).powi(2)).sum::<f64>() / n;
+            let change_pct = ((avg_utilization / TARGET_UTILIZATION_PCT - 1.0) * 100.0).round();
+            let suggestion = if avg_utilization < UNDERUSED_UTILIZATION_PCT {
+                format!("Reduce allocation by {}%", -change_pct)
+            } else if avg_utilization > OVERUSED_UTILIZATION_PCT {
+                format!("Increase allocation by {}%", change_pct)
+            } else {
+                "Allocation matches spending".to_string()
+            };
+            BudgetEfficiency {
+                budget,
+                avg_utilization,
+                variance,
+                suggestion,
+            }
+        })
+        .collect();
+    result.sort_by(|a, b| a.budget.name.cmp(&b.budget.name));
+    Ok(result)
+}
+
+/// Completed months averaged for current savings and category spending in a savings plan
+const SAVINGS_PLAN_HISTORY_MONTHS: i32 = 3;
+/// Number of discretionary categories a savings plan suggests cutting
+const SAVINGS_PLAN_CUT_CATEGORIES: usize = 3;
+
+/// Monthly savings needed to reach a goal by its target date, compared with what was actually saved
+/// per month over the last 3 completed months. Any shortfall is split over the 3 discretionary
+/// categories with the most spending, in proportion to their spending.
+pub fn get_savings_plan(conn: &Connection, goal_id: i64) -> Result<SavingsPlan, rusqlite::Error> {
+    let goal = savings_goals::get_by_id(conn, goal_id)?
+        .ok_or_else(|| rusqlite::Error::InvalidParameterName("Savings goal not found".to_string()))?;
+
+    let today = current_date(conn)?;
+    let target_month = month_index(goal.target_date.get(..7).unwrap_or_default()).unwrap_or_default();
+    let current_month = today.year() * 12 + today.month0() as i32;
+    // The current month still counts as one to save in, even when the target date is in it or has passed
+    let months_left = (target_month - current_month + 1).max(1) as i64;
+    let remaining = (goal.target_amount - goal.current_amount).max(0);
+    let required_monthly_savings = (remaining + months_left - 1) / months_left;
+
+    let history_start = first_of_month_offset(today, -SAVINGS_PLAN_HISTORY_MONTHS).format("%Y-%m-%d").to_string();
+    let history_end = first_of_month_offset(today, 0).format("%Y-%m-%d").to_string();
+    let net: i64 = conn.query_row(
+        r#"SELECT COALESCE(SUM(amount), 0) FROM transactions
+           WHERE date >= ?1 AND date < ?2 AND (?3 IS NULL OR account_id = ?3)"#,
+        params![history_start, history_end, goal.account_id],
+        |row| row.get(0),
+    )?;
+    let current_monthly_savings = net / SAVINGS_PLAN_HISTORY_MONTHS as i64;
+    let monthly_gap = (required_monthly_savings - current_monthly_savings).max(0);
+
+    let mut suggested_cuts = Vec::new();
+    if monthly_gap > 0 {
+        let mut stmt = conn.prepare(
+            r#"SELECT c.name, SUM(-t.amount) / ?4 as monthly
+               FROM transactions t
+               JOIN categories c ON t.category_id = c.id
+               WHERE c.spending_type = 'discretionary' AND t.amount < 0
+                 AND t.date >= ?1 AND t.date < ?2 AND (?3 IS NULL OR t.account_id = ?3)
+               GROUP BY c.id
+               ORDER BY monthly DESC, c.name
+               LIMIT ?5"#,
+        )?;
+        let categories: Vec<(String, i64)> = stmt
+            .query_map(
+                params![
+                    history_start,
+                    history_end,
+                    goal.account_id,
+                    SAVINGS_PLAN_HISTORY_MONTHS,
+                    SAVINGS_PLAN_CUT_CATEGORIES as i64
+                ],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let total: i64 = categories.iter().map(|(_, monthly)| monthly).sum();
+        for (category_name, current_monthly) in categories {
+            if current_monthly <= 0 {
+                continue;
+            }
+            // A category can't give up more than it spends
+            let estimated_savings =
+                ((monthly_gap as f64 * current_monthly as f64 / total as f64).round() as i64).min(current_monthly);
+            suggested_cuts.push(CategoryCutSuggestion {
+                category_name,
+                current_monthly,
+                suggested_reduction_pct: estimated_savings as f64 / current_monthly as f64 * 100.0,
+                estimated_savings,
+            });
+        }
+    }
+
+    Ok(SavingsPlan {
+        goal,
+        required_monthly_savings,
+        current_monthly_savings,
+        monthly_gap,
+        suggested_cuts,
+    })
+}
+
+/// Default discount assumed for annual billing when `annual_discount_pct` is not set
+const DEFAULT_ANNUAL_DISCOUNT_PCT: f64 = 15.0;
+
+/// For each monthly subscription, estimate what it would cost on an annual plan.
+/// The discount is read from the `annual_discount_pct` app setting (default 15%).
+pub fn get_billing_cycle_comparison(
+    conn: &Connection,
+    account_id: i64,
+) -> Result<Vec<BillingComparison>, rusqlite::Error> {
+    let discount_pct = settings::get_f64(conn, "annual_discount_pct", DEFAULT_ANNUAL_DISCOUNT_PCT)?;
+    let discount = (discount_pct / 100.0).clamp(0.0, 1.0);
+
+    let mut comparisons: Vec<BillingComparison> = subscriptions::get_by_account(conn, account_id)?
+        .into_iter()
+        .filter(|sub| sub.frequency == "monthly")
+        .map(|sub| {
+            let monthly_equivalent = sub.amount.abs();
+            let yearly = monthly_equivalent * 12;
+            let annual_estimate = (yearly as f64 * (1.0 - discount)).round() as i64;
+            BillingComparison {
+                subscription: sub,
+                monthly_equivalent,
+                annual_estimate,
+                potential_savings: yearly - annual_estimate,
+            }
+        })
+        .collect();
+
+    comparisons.sort_by_key(|c| std::cmp::Reverse(c.potential_savings));
+    Ok(comparisons)
+}
+
+/// Expenses in a date range per top-level category, each with its subcategories' share.
+/// Deeper categories count towards the subcategory they sit under. Parents and subcategories are
+/// both ordered by spending, largest first.
+pub fn get_hierarchical_spending(
+    conn: &Connection,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<HierarchicalSpending>, rusqlite::Error> {
+    // Every category with its top-level ancestor and the subcategory directly below that
+    const LINEAGE: &str = r#"WITH RECURSIVE lineage(id, top_id, sub_id) AS (
+               SELECT id, id, NULL FROM categories WHERE parent_id IS NULL
+               UNION
+               SELECT c.id, l.top_id, COALESCE(l.sub_id, c.id) FROM categories c JOIN lineage l ON c.parent_id = l.id
+           )"#;
+
+    let mut stmt = conn.prepare(&format!(
+        r#"{}
+           SELECT l.top_id, COALESCE(top.name, 'Uncategorized'), SUM(t.amount) AS total
+           FROM transactions t
+           LEFT JOIN lineage l ON t.category_id = l.id
+           LEFT JOIN categories top ON top.id = l.top_id
+           WHERE t.account_id = ?1 AND t.date >= ?2 AND t.date <= ?3 AND t.amount < 0
+           GROUP BY l.top_id
+           ORDER BY total ASC"#,
+        LINEAGE
+    ))?;
+    let mut parents: Vec<(Option<i64>, HierarchicalSpending)> = stmt
+        .query_map(params![account_id, start_date, end_date], |row| {
+            Ok((
+                row.get(0)?,
+                HierarchicalSpending {
+                    parent_category: row.get(1)?,
+                    parent_total: row.get(2)?,
+                    subcategories: Vec::new(),
+                },
+            ))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut stmt = conn.prepare(&format!(
+        r#"{}
+           SELECT l.top_id, sub.name, SUM(t.amount) AS total
+           FROM transactions t
+           JOIN lineage l ON t.category_id = l.id
+           JOIN categories sub ON sub.id = l.sub_id
+           WHERE t.account_id = ?1 AND t.date >= ?2 AND t.date <= ?3 AND t.amount < 0
+           GROUP BY l.sub_id
+           ORDER BY total ASC"#,
+        LINEAGE
+    ))?;
+    let subcategories = stmt
+        .query_map(params![account_id, start_date, end_date], |row| {
+            Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (top_id, name, total) in subcategories {
+        if let Some((_, entry)) = parents.iter_mut().find(|(id, _)| *id == Some(top_id)) {
+            entry.subcategories.push((name, total));
+        }
+    }
+    Ok(parents.into_iter().map(|(_, entry)| entry).collect())
+}
+
+/// How many days ahead subscription charges count as upcoming in the attention items
+const ATTENTION_UPCOMING_DAYS: i64 = 7;
+/// Days an expected deposit may be late before its income stream is reported as missed
+const ATTENTION_INCOME_GRACE_DAYS: i64 = 3;
+/// Share of a budget spent (percent) before it is reported
+const ATTENTION_BUDGET_PCT: f64 = 90.0;
+/// Gap length (days) between transactions reported as a possibly missing import
+const ATTENTION_GAP_DAYS: u32 = 15;
+
+/// Everything across all accounts that needs the user's action, gathered in one call
+pub fn get_attention_items(conn: &Connection) -> Result<AttentionItems, rusqlite::Error> {
+    let today = current_date(conn)?;
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let upcoming_end = (today + chrono::Duration::days(ATTENTION_UPCOMING_DAYS)).format("%Y-%m-%d").to_string();
+
+    let (uncategorized_count, unreconciled_count): (i64, i64) = conn.query_row(
+        r#"SELECT COALESCE(SUM(CASE WHEN category_id IS NULL THEN 1 ELSE 0 END), 0),
+                  COALESCE(SUM(CASE WHEN is_reconciled = 0 THEN 1 ELSE 0 END), 0)
+           FROM transactions"#,
+        [],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    let mut overdue_subscriptions = Vec::new();
+    let mut upcoming_subscriptions_7d = Vec::new();
+    let mut transaction_gaps = Vec::new();
+    for account in accounts::get_all(conn)? {
+        let account_id = account.id.unwrap_or_default();
+        for sub in subscriptions::get_by_account(conn, account_id)? {
+            match sub.next_charge_date.as_deref() {
+                Some(date) if date < today_str.as_str() => overdue_subscriptions.push(sub),
+                Some(date) if date <= upcoming_end.as_str() => upcoming_subscriptions_7d.push(sub),
+                _ => {}
+            }
+        }
+        transaction_gaps.extend(transactions::detect_gaps_at(conn, account_id, ATTENTION_GAP_DAYS, today)?);
+    }
+
+    // A stream is missed when the deposit projected from its last one is overdue; never-received streams can't be projected
+    let grace_cutoff = (today - chrono::Duration::days(ATTENTION_INCOME_GRACE_DAYS)).format("%Y-%m-%d").to_string();
+    let missed_income_streams = income_streams::get_all_with_last_received(conn)?
+        .into_iter()
+        .filter(|entry| entry.stream.is_active && entry.stream.direction == "inflow")
+        .filter(|entry| {
+            entry
+                .last_received
+                .as_ref()
+                .and_then(|last| subscription_engine::predict_next_date(&last.date, &entry.stream.frequency))
+                .is_some_and(|expected| expected < grace_cutoff)
+        })
+        .map(|entry| entry.stream)
+        .collect();
+
+    let month = today.format("%Y-%m").to_string();
+    let budget_over_90pct = budgets::get_budgets_with_spending_at(conn, &month, None, today)?
+        .into_iter()
+        .filter(|b| b.allocated_amount > 0 && b.percent_spent >= ATTENTION_BUDGET_PCT)
+        .collect();
+
+    Ok(AttentionItems {
+        uncategorized_count,
+        unreconciled_count,
+        overdue_subscriptions,
+        upcoming_subscriptions_7d,
+        missed_income_streams,
+        budget_over_90pct,
+        transaction_gaps,
+    })
+}
+
+// === Helpers ===
+
+/// First day of the month `offset` months away from the month containing `date`
+fn first_of_month_offset(date: NaiveDate, offset: i32) -> NaiveDate {
+    let total = date.year() * 12 + date.month0() as i32 + offset;
+    NaiveDate::from_ymd_opt(total.div_euclid(12), total.rem_euclid(12) as u32 + 1, 1).unwrap_or(date)
+}
+
+/// Dates from `first` onwards, stepping by `frequency`, that fall within [start, end]
+fn recurring_dates(first: Option<String>, frequency: &str, start: &str, end: &str) -> Vec<String> {
+    let mut dates = Vec::new();
+    let mut next = first;
+    while let Some(date) = next {
+        if date.as_str() > end {
+            break;
+        }
+        if date.as_str() >= start {
+            dates.push(date.clone());
+        }
+        next = subscription_engine::predict_next_date(&date, frequency);
+    }
+    dates
+}
+
+/// Weekdays after `from` up to and including `to`
+fn business_days_between(from: NaiveDate, to: NaiveDate) -> i64 {
+    from.iter_days()
+        .skip(1)
+        .take_while(|d| *d <= to)
+        .filter(|d| d.weekday().number_from_monday() <= 5)
+        .count() as i64
+}
+
+/// Months since year 0 for a YYYY-MM string, so consecutive months differ by one
+fn month_index(month: &str) -> Option<i32> {
+    let (year, month) = month.split_once('-')?;
+    let year: i32 = year.parse().ok()?;
+    let month: i32 = month.parse().ok()?;
+    Some(year * 12 + month - 1)
+}
+
+/// Today's date according to SQLite's clock
+pub fn current_date(conn: &Connection) -> Result<NaiveDate, rusqlite::Error> {
+    let today: String = conn.query_row("SELECT date('now')", [], |row| row.get(0))?;
+    NaiveDate::parse_from_str(&today, "%Y-%m-%d")
+        .map_err(|e| rusqlite::Error::InvalidParameterName(e.to_string()))
+}
+
+/// First day, last day and number of days of a YYYY-MM month
+fn month_bounds(month: &str) -> Result<(NaiveDate, NaiveDate, u32), rusqlite::Error> {
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| rusqlite::Error::InvalidParameterName(format!("Invalid month: {}", month)))?;
+    let next = if start.month() == 12 {
+        NaiveDate::from_ymd_opt(start.year() + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(start.year(), start.month() + 1, 1)
+    }
+    .ok_or_else(|| rusqlite::Error::InvalidParameterName(format!("Invalid month: {}", month)))?;
+    let end = next.pred_opt().unwrap_or(start);
+    Ok((start, end, end.day()))
+}
+
+/// The last `n` months (YYYY-MM) ending with the current month, oldest first
+fn last_n_months(conn: &Connection, n: u32) -> Result<Vec<String>, rusqlite::Error> {
+    let today = current_date(conn)?;
+    Ok((0..n as i32)
+        .rev()
+        .map(|offset| first_of_month_offset(today, -offset).format("%Y-%m").to_string())
+        .collect())
+}
+
+/// Øre as Danish kroner with thousands separators, e.g. -123456 -> "-1.234,56 kr"
+fn format_kr(ore: i64) -> String {
+    let kroner = (ore.abs() / 100).to_string();
+    let groups: Vec<&str> = kroner
+        .as_bytes()
+        .rchunks(3)
+        .rev()
+        .map(|digits| std::str::from_utf8(digits).unwrap_or_default())
+        .collect();
+    format!("{}{},{:02} kr", if ore < 0 { "-" } else { "" }, groups.join("."), ore.abs() % 100)
+}
+
+fn escape_html(s: &str) -> String {
+    s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Percentage change from `prior` to `current`; anything from zero counts as +100%
+fn percent_change(prior: i64, current: i64) -> f64 {
+    if prior == 0 {
+        if current == 0 { 0.0 } else { 100.0 }
+    } else {
+        (current - prior) as f64 / prior as f64 * 100.0
+    }
+}
+
+/// Slope of a least-squares line through the values (x = index)
+fn linear_slope(values: &[i64]) -> f64 {
+    let n = values.len() as f64;
+    if values.len() < 2 {
+        return 0.0;
+    }
+    let mean_x = (n - 1.0) / 2.0;
+    let mean_y = values.iter().sum::<i64>() as f64 / n;
+    let mut num = 0.0;
+    let mut den = 0.0;
+    for (i, &y) in values.iter().enumerate() {
+        let dx = i as f64 - mean_x;
+        num += dx * (y as f64 - mean_y);
+        den += dx * dx;
+    }
+    num / den
+}
+
+/// Classify a series as 'increasing', 'decreasing' or 'stable' by the sign of its slope
+fn trend_direction(values: &[i64]) -> String {
+    let slope = linear_slope(values);
+    if slope > 0.0 {
+        "increasing".to_string()
+    } else if slope < 0.0 {
+        "decreasing".to_string()
+    } else {
+        "stable".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        conn
+    }
+
+    fn category(conn: &Connection, name: &str, parent_id: Option<i64>) -> i64 {
+        conn.execute("INSERT INTO categories (name, parent_id) VALUES (?1, ?2)", params![name, parent_id]).unwrap();
+        conn.last_insert_rowid()
+    }
+
+    /// A transaction on the 5th, `months_ago` months before the current one
+    fn insert(conn: &Connection, months_ago: u32, amount: i64, category_id: i64) {
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount, category_id)
+             VALUES (1, date('now', 'start of month', ?1, '+4 days'), 'Butik', ?2, ?3)",
+            params![format!("-{} months", months_ago), amount, category_id],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn consistency_keeps_same_named_categories_apart() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let transport = category(&conn, "Transport", None);
+        let food_other = category(&conn, "Andet", Some(food));
+        let transport_other = category(&conn, "Andet", Some(transport));
+        for months_ago in 1..=3 {
+            insert(&conn, months_ago, -50000, food_other);
+        }
+        insert(&conn, 1, -90000, transport_other);
+
+        let result = get_category_consistency(&conn, 1, 3).unwrap();
+        assert_eq!(result.len(), 2);
+        assert_eq!((result[0].category_id, result[0].mean_monthly, result[0].cv), (food_other, 50000, 0.0));
+        assert_eq!((result[1].category_id, result[1].mean_monthly), (transport_other, 30000));
+        assert!(result.iter().all(|c| c.category_name == "Andet"));
+    }
+
+    fn add_stream(conn: &Connection, name: &str, expected_amount: i64, direction: &str) -> i64 {
+        conn.execute(
+            "INSERT INTO income_streams (name, expected_amount, frequency, direction) VALUES (?1, ?2, 'monthly', ?3)",
+            params![name, expected_amount, direction],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    #[test]
+    fn stress_test_removes_streams_by_direction() {
+        let conn = setup();
+        let rent = category(&conn, "Husleje", None);
+        for months_ago in 1..=6 {
+            insert(&conn, months_ago, -1_500_000, rent);
+        }
+        let salary = add_stream(&conn, "Løn", 1_200_000, "inflow");
+        let loan = add_stream(&conn, "Billån", -250_000, "outflow");
+
+        // 15.000 kr of monthly expenses against 12.000 kr of income
+        assert_eq!(cashflow_stress_test(&conn, None, 0.0).unwrap().monthly_deficit, 300_000);
+        // A paid-off loan lowers expenses; it must not be taken as lost income
+        assert_eq!(cashflow_stress_test(&conn, Some(loan), 0.0).unwrap().monthly_deficit, 50_000);
+        assert_eq!(cashflow_stress_test(&conn, Some(salary), 0.0).unwrap().monthly_deficit, 1_500_000);
+    }
+
+    fn range(preset: &str, today: &str) -> (String, String) {
+        date_range_from(preset, NaiveDate::parse_from_str(today, "%Y-%m-%d").unwrap()).unwrap()
+    }
+
+    fn pair(start: &str, end: &str) -> (String, String) {
+        (start.to_string(), end.to_string())
+    }
+
+    #[test]
+    fn date_range_presets_cross_month_boundaries() {
+        assert_eq!(range("this_month", "2024-02-10"), pair("2024-02-01", "2024-02-29"));
+        assert_eq!(range("last_month", "2024-03-31"), pair("2024-02-01", "2024-02-29"));
+        assert_eq!(range("last_3_months", "2024-05-31"), pair("2024-03-01", "2024-05-31"));
+    }
+
+    #[test]
+    fn date_range_presets_cross_year_boundaries() {
+        assert_eq!(range("last_month", "2024-01-15"), pair("2023-12-01", "2023-12-31"));
+        assert_eq!(range("last_3_months", "2024-01-15"), pair("2023-11-01", "2024-01-31"));
+        assert_eq!(range("last_12_months", "2024-01-15"), pair("2023-02-01", "2024-01-31"));
+        assert_eq!(range("ytd", "2024-01-15"), pair("2024-01-01", "2024-01-15"));
+        assert_eq!(range("this_year", "2024-01-15"), pair("2024-01-01", "2024-12-31"));
+        assert!(date_range_from("next_decade", NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()).is_err());
+    }
+
+    #[test]
+    fn monthly_summary_lists_an_outflow_stream_once() {
+        let conn = setup();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Budgetkonto')", []).unwrap();
+        add_stream(&conn, "Billån", -250_000, "outflow");
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, date('now', '-20 days'), 'Afdrag', -250000)",
+            [],
+        )
+        .unwrap();
+        let month: String = conn.query_row("SELECT strftime('%Y-%m', 'now')", [], |row| row.get(0)).unwrap();
+
+        let summary = format_monthly_summary(&conn, None, month).unwrap();
+        assert_eq!(summary.body_plain_text.matches("Billån").count(), 1, "{}", summary.body_plain_text);
+    }
+
+    #[test]
+    fn hierarchical_spending_groups_by_category_id() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let restaurants = category(&conn, "Restaurant", Some(food));
+        let sushi = category(&conn, "Sushi", Some(restaurants));
+        let other_food = category(&conn, "Mad", None);
+        let named_uncategorized = category(&conn, "Uncategorized", None);
+        for (amount, category_id) in [(-10000, food), (-20000, restaurants), (-40000, sushi), (-5000, other_food), (-3000, named_uncategorized)] {
+            insert(&conn, 1, amount, category_id);
+        }
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, date('now', 'start of month', '-1 months'), 'Kiosk', -1000)",
+            [],
+        )
+        .unwrap();
+
+        let spending = get_hierarchical_spending(&conn, 1, "0000-01-01".to_string(), "9999-12-31".to_string()).unwrap();
+        let totals: Vec<(&str, i64)> = spending.iter().map(|s| (s.parent_category.as_str(), s.parent_total)).collect();
+        assert_eq!(totals, [("Mad", -70000), ("Mad", -5000), ("Uncategorized", -3000), ("Uncategorized", -1000)]);
+        assert_eq!(spending[0].subcategories, [("Restaurant".to_string(), -60000)]);
+        assert!(spending[1..].iter().all(|s| s.subcategories.is_empty()));
+    }
+
+    #[test]
+    fn month_over_month_keeps_same_named_categories_apart() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let transport = category(&conn, "Transport", None);
+        let food_other = category(&conn, "Andet", Some(food));
+        let transport_other = category(&conn, "Andet", Some(transport));
+        for (date, amount, category_id) in [
+            ("2024-02-10", -10000, food_other),
+            ("2024-03-10", -30000, food_other),
+            ("2024-03-12", -5000, transport_other),
+        ] {
+            conn.execute(
+                "INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, ?1, 'Butik', ?2, ?3)",
+                params![date, amount, category_id],
+            )
+            .unwrap();
+        }
+
+        let changes = get_category_mom_changes(&conn, None, "2024-3".to_string()).unwrap();
+        let summary: Vec<(Option<i64>, i64, i64)> = changes.iter().map(|c| (c.category_id, c.current, c.prior)).collect();
+        assert_eq!(summary, [(Some(food_other), 30000, 10000), (Some(transport_other), 5000, 0)]);
+        assert!(get_category_mom_changes(&conn, None, "2024-13".to_string()).is_err());
+    }
+
+    #[test]
+    fn essential_vs_discretionary_normalizes_the_month() {
+        let conn = setup();
+        conn.execute("INSERT INTO categories (name, spending_type) VALUES ('Husleje', 'essential')", []).unwrap();
+        for (date, amount, category_id) in [("2024-03-01", -800000, Some(1)), ("2024-03-05", -4500, None), ("2024-04-01", -800000, Some(1))] {
+            conn.execute(
+                "INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, ?1, 'Butik', ?2, ?3)",
+                params![date, amount, category_id],
+            )
+            .unwrap();
+        }
+
+        let march = get_essential_vs_discretionary(&conn, None, "2024-3".to_string()).unwrap();
+        assert_eq!((march.essential, march.uncategorized), (800000, 4500));
+        assert!(get_essential_vs_discretionary(&conn, None, "2024".to_string()).is_err());
+    }
+
+    #[test]
+    fn seasonal_spending_includes_nested_subcategories() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let groceries = category(&conn, "Dagligvarer", Some(food));
+        let organic = category(&conn, "Økologi", Some(groceries));
+        let transport = category(&conn, "Transport", None);
+        for (date, amount, category_id) in [
+            ("2024-02-10", -10000, food),
+            ("2024-02-11", -20000, groceries),
+            ("2024-02-12", -40000, organic),
+            ("2024-02-13", -80000, transport),
+        ] {
+            conn.execute(
+                "INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, ?1, 'Butik', ?2, ?3)",
+                params![date, amount, category_id],
+            )
+            .unwrap();
+        }
+
+        let seasons = get_seasonal_spending(&conn, None, Some(food)).unwrap();
+        assert_eq!(seasons[0].avg_quarterly_spend, 70000);
+        assert_eq!(get_seasonal_spending(&conn, None, None).unwrap()[0].avg_quarterly_spend, 150000);
+    }
+
+    #[test]
+    fn spending_streak_includes_nested_subcategories() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let groceries = category(&conn, "Dagligvarer", Some(food));
+        let organic = category(&conn, "Økologi", Some(groceries));
+        insert(&conn, 0, -5000, organic);
+        insert(&conn, 1, -5000, groceries);
+        insert(&conn, 2, -5000, organic);
+
+        let streak = get_category_spending_streak(&conn, food, None).unwrap();
+        assert_eq!((streak.current_streak_months, streak.longest_streak_months), (3, 3));
+    }
+
+    #[test]
+    fn forecast_includes_nested_subcategories() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let groceries = category(&conn, "Dagligvarer", Some(food));
+        let organic = category(&conn, "Økologi", Some(groceries));
+        insert(&conn, 1, -10000, food);
+        insert(&conn, 1, -20000, organic);
+        insert(&conn, 2, -30000, groceries);
+
+        let forecast = forecast_category_spending(&conn, food, 1, 2).unwrap();
+        let totals: Vec<(i64, i64)> = forecast.historical.iter().map(|m| (m.total, m.count)).collect();
+        assert_eq!(totals, vec![(30000, 1), (30000, 2)]);
+    }
+}
@@ -0,0 +1,64 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use super::models::SavingsGoal;
+use chrono::NaiveDate;
+
+pub fn create(conn: &Connection, goal: &SavingsGoal) -> Result<i64, rusqlite::Error> {
+    validate(goal)?;
+    conn.execute(
+        "INSERT INTO savings_goals (name, target_amount, target_date, current_amount, account_id) VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![goal.name, goal.target_amount, goal.target_date, goal.current_amount, goal.account_id],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_all(conn: &Connection) -> Result<Vec<SavingsGoal>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        "SELECT id, name, target_amount, target_date, current_amount, account_id FROM savings_goals ORDER BY target_date, name",
+    )?;
+    let rows = stmt.query_map([], map_goal)?;
+    rows.collect()
+}
+
+pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<SavingsGoal>, rusqlite::Error> {
+    conn.query_row(
+        "SELECT id, name, target_amount, target_date, current_amount, account_id FROM savings_goals WHERE id = ?1",
+        params![id],
+        map_goal,
+    )
+    .optional()
+}
+
+pub fn update(conn: &Connection, goal: &SavingsGoal) -> Result<usize, rusqlite::Error> {
+    let id = goal.id.ok_or(rusqlite::Error::InvalidParameterName(
+        "Savings goal ID is required for update".to_string(),
+    ))?;
+    validate(goal)?;
+    conn.execute(
+        "UPDATE savings_goals SET name = ?1, target_amount = ?2, target_date = ?3, current_amount = ?4, account_id = ?5 WHERE id = ?6",
+        params![goal.name, goal.target_amount, goal.target_date, goal.current_amount, goal.account_id, id],
+    )
+}
+
+pub fn delete(conn: &Connection, id: i64) -> Result<usize, rusqlite::Error> {
+    conn.execute("DELETE FROM savings_goals WHERE id = ?1", params![id])
+}
+
+fn validate(goal: &SavingsGoal) -> Result<(), rusqlite::Error> {
+    if goal.target_amount <= 0 {
+        return Err(rusqlite::Error::InvalidParameterName("Target amount must be positive".to_string()));
+    }
+    NaiveDate::parse_from_str(&goal.target_date, "%Y-%m-%d")
+        .map_err(|_| rusqlite::Error::InvalidParameterName(format!("Invalid target date: {}", goal.target_date)))?;
+    Ok(())
+}
+
+fn map_goal(row: &rusqlite::Row) -> Result<SavingsGoal, rusqlite::Error> {
+    Ok(SavingsGoal {
+        id: Some(row.get(0)?),
+        name: row.get(1)?,
+        target_amount: row.get(2)?,
+        target_date: row.get(3)?,
+        current_amount: row.get(4)?,
+        account_id: row.get(5)?,
+    })
+}
@@ -2,6 +2,84 @@ use rusqlite::Connection;
 
 pub fn initialize(conn: &Connection) -> Result<(), rusqlite::Error> {
     conn.execute_batch(SCHEMA)?;
+    migrate(conn)?;
+    super::settings::seed_defaults(conn)?;
+    Ok(())
+}
+
+/// Bring databases created by older versions up to date with SCHEMA.
+/// Columns added after a table was first created must also be listed here.
+fn migrate(conn: &Connection) -> Result<(), rusqlite::Error> {
+    add_column_if_missing(
+        conn,
+        "transactions",
+        "subscription_id",
+        "INTEGER REFERENCES subscriptions(id) ON DELETE SET NULL",
+    )?;
+    // Fill the denormalized column from the link table for links made before it existed
+    conn.execute(
+        r#"UPDATE transactions SET subscription_id = (
+               SELECT st.subscription_id FROM subscription_transactions st WHERE st.transaction_id = transactions.id
+           )
+           WHERE subscription_id IS NULL AND id IN (SELECT transaction_id FROM subscription_transactions)"#,
+        [],
+    )?;
+    add_column_if_missing(
+        conn,
+        "categories",
+        "spending_type",
+        "TEXT DEFAULT 'discretionary' CHECK(spending_type IN ('essential', 'discretionary', 'savings'))",
+    )?;
+    add_column_if_missing(conn, "transactions", "raw_payee", "TEXT")?;
+    add_column_if_missing(conn, "transactions", "memo", "TEXT")?;
+    add_column_if_missing(conn, "transactions", "needs_review", "INTEGER DEFAULT 0")?;
+    add_column_if_missing(conn, "transactions", "hash_collision", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(conn, "accounts", "account_type", "TEXT NOT NULL DEFAULT 'checking'")?;
+    add_column_if_missing(conn, "accounts", "credit_limit", "INTEGER")?;
+    add_column_if_missing(conn, "accounts", "color", "TEXT NOT NULL DEFAULT '#6366f1'")?;
+    add_column_if_missing(conn, "accounts", "icon", "TEXT")?;
+    add_column_if_missing(conn, "categories", "is_hidden", "INTEGER NOT NULL DEFAULT 0")?;
+    add_column_if_missing(
+        conn,
+        "income_streams",
+        "direction",
+        "TEXT NOT NULL DEFAULT 'inflow' CHECK(direction IN ('inflow', 'outflow'))",
+    )?;
+    add_column_if_missing(conn, "income_streams", "anchor_date", "TEXT")?;
+    add_column_if_missing(conn, "import_log", "account_id", "INTEGER")?;
+    add_column_if_missing(conn, "import_log", "first_date", "TEXT")?;
+    add_column_if_missing(conn, "import_log", "last_date", "TEXT")?;
+    // Settings were first stored in a table named app_settings
+    let has_app_settings: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'app_settings')",
+        [],
+        |row| row.get(0),
+    )?;
+    if has_app_settings {
+        conn.execute_batch(
+            "INSERT OR REPLACE INTO settings (key, value) SELECT key, value FROM app_settings;
+             DROP TABLE app_settings;",
+        )?;
+    }
+    Ok(())
+}
+
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    definition: &str,
+) -> Result<(), rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute_batch(&format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition))?;
+    }
     Ok(())
 }
 
@@ -14,7 +92,11 @@ CREATE TABLE IF NOT EXISTS accounts (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     name TEXT NOT NULL,
     account_number TEXT,
-    currency TEXT DEFAULT 'DKK'
+    currency TEXT DEFAULT 'DKK',
+    account_type TEXT NOT NULL DEFAULT 'checking',  -- 'checking', 'savings', 'credit', ...
+    credit_limit INTEGER,                           -- Credit accounts only
+    color TEXT NOT NULL DEFAULT '#6366f1',
+    icon TEXT
 );
 
 -- 2. Categories (Self-referencing for hierarchy)
@@ -22,6 +104,8 @@ CREATE TABLE IF NOT EXISTS categories (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     name TEXT NOT NULL,
     parent_id INTEGER,
+    spending_type TEXT DEFAULT 'discretionary' CHECK(spending_type IN ('essential', 'discretionary', 'savings')),
+    is_hidden INTEGER NOT NULL DEFAULT 0,
     FOREIGN KEY(parent_id) REFERENCES categories(id) ON DELETE CASCADE
 );
 
@@ -32,13 +116,19 @@ CREATE TABLE IF NOT EXISTS transactions (
     category_id INTEGER,
     date TEXT NOT NULL,
     payee TEXT NOT NULL,
+    raw_payee TEXT,                       -- Payee as it appeared in the import, before payee rules
+    memo TEXT,
     amount INTEGER NOT NULL,
     balance_snapshot INTEGER,
     status TEXT,
     is_reconciled INTEGER DEFAULT 0,
     import_hash TEXT UNIQUE,
+    subscription_id INTEGER,              -- Denormalized from subscription_transactions
+    needs_review INTEGER DEFAULT 0,       -- Category was guessed by a rule and should be confirmed
+    hash_collision INTEGER NOT NULL DEFAULT 0, -- Imported, but lost its import_hash to a duplicate in recompute_hashes
     FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE,
-    FOREIGN KEY(category_id) REFERENCES categories(id) ON DELETE SET NULL
+    FOREIGN KEY(category_id) REFERENCES categories(id) ON DELETE SET NULL,
+    FOREIGN KEY(subscription_id) REFERENCES subscriptions(id) ON DELETE SET NULL
 );
 
 -- 4. Import History
@@ -46,7 +136,10 @@ CREATE TABLE IF NOT EXISTS import_log (
     id INTEGER PRIMARY KEY AUTOINCREMENT,
     filename TEXT NOT NULL,
     import_date TEXT DEFAULT (datetime('now')),
-    records_added INTEGER
+    records_added INTEGER,
+    account_id INTEGER,                   -- NULL for imports logged before accounts were recorded
+    first_date TEXT,                      -- Date range of the file's rows, duplicates included
+    last_date TEXT
 );
 
 -- Indexes for common queries
@@ -99,6 +192,14 @@ CREATE TABLE IF NOT EXISTS budget_categories (
     FOREIGN KEY(budget_id) REFERENCES budgets(id) ON DELETE CASCADE,
     FOREIGN KEY(category_id) REFERENCES categories(id) ON DELETE CASCADE
 );
+-- Accounts a budget is limited to (no rows = all accounts)
+CREATE TABLE IF NOT EXISTS budget_accounts (
+    budget_id INTEGER NOT NULL,
+    account_id INTEGER NOT NULL,
+    PRIMARY KEY (budget_id, account_id),
+    FOREIGN KEY(budget_id) REFERENCES budgets(id) ON DELETE CASCADE,
+    FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
 
 -- 9. Monthly budget allocations
 CREATE TABLE IF NOT EXISTS budget_allocations (
@@ -118,9 +219,179 @@ CREATE TABLE IF NOT EXISTS income_streams (
     frequency TEXT NOT NULL,
     category_id INTEGER,
     is_active INTEGER DEFAULT 1,
+    direction TEXT NOT NULL DEFAULT 'inflow' CHECK(direction IN ('inflow', 'outflow')),
+    anchor_date TEXT,
     FOREIGN KEY(category_id) REFERENCES categories(id) ON DELETE SET NULL
 );
 
+-- 11. Payee cleanup rules (regex replacements applied at import, in order)
+CREATE TABLE IF NOT EXISTS payee_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    replacement TEXT NOT NULL DEFAULT '',
+    rule_order INTEGER DEFAULT 0
+);
+
+-- 12. Auto-categorization rules (payee pattern -> category)
+CREATE TABLE IF NOT EXISTS categorization_rules (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pattern TEXT NOT NULL,
+    category_id INTEGER NOT NULL,
+    is_regex INTEGER DEFAULT 0,
+    priority INTEGER DEFAULT 0,           -- Higher priority rules are evaluated first
+    metadata TEXT,
+    FOREIGN KEY(category_id) REFERENCES categories(id) ON DELETE CASCADE
+);
+
+-- 13. Settings (key-value, values stored as JSON)
+CREATE TABLE IF NOT EXISTS settings (
+    key TEXT PRIMARY KEY,
+    value TEXT NOT NULL
+);
+
+-- 14. Transfers (the two legs of a move between own accounts)
+CREATE TABLE IF NOT EXISTS transfers (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    debit_transaction_id INTEGER NOT NULL UNIQUE,
+    credit_transaction_id INTEGER NOT NULL UNIQUE,
+    FOREIGN KEY(debit_transaction_id) REFERENCES transactions(id) ON DELETE CASCADE,
+    FOREIGN KEY(credit_transaction_id) REFERENCES transactions(id) ON DELETE CASCADE
+);
+
+-- 15. Savings goals
+CREATE TABLE IF NOT EXISTS savings_goals (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL,
+    target_amount INTEGER NOT NULL,
+    target_date TEXT NOT NULL,         -- YYYY-MM-DD
+    current_amount INTEGER NOT NULL DEFAULT 0,
+    account_id INTEGER,                -- Savings counted from this account only; NULL means all accounts
+    FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE SET NULL
+);
+
+-- 16. Reconciliation sessions (each time an account was balanced against a statement)
+CREATE TABLE IF NOT EXISTS reconciliation_sessions (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    account_id INTEGER NOT NULL,
+    reconciled_date TEXT NOT NULL,     -- Latest transaction date covered
+    ending_balance INTEGER NOT NULL,
+    transaction_count INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY(account_id) REFERENCES accounts(id) ON DELETE CASCADE
+);
+
+-- 17. Earlier expected amounts of income streams, recorded when the amount is changed
+CREATE TABLE IF NOT EXISTS income_stream_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    stream_id INTEGER NOT NULL,
+    expected_amount INTEGER NOT NULL,  -- The amount before the change
+    changed_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY(stream_id) REFERENCES income_streams(id) ON DELETE CASCADE
+);
+
+-- 18. Edits to transactions' categories and payees
+CREATE TABLE IF NOT EXISTS transaction_audit_log (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    transaction_id INTEGER NOT NULL,
+    field_changed TEXT NOT NULL,       -- 'category_id' or 'payee'
+    old_value TEXT,
+    new_value TEXT,
+    changed_at TEXT DEFAULT (datetime('now')),
+    FOREIGN KEY(transaction_id) REFERENCES transactions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_transaction_audit_log_transaction ON transaction_audit_log(transaction_id);
 CREATE INDEX IF NOT EXISTS idx_budget_allocations_month ON budget_allocations(month);
 CREATE INDEX IF NOT EXISTS idx_budget_categories_budget ON budget_categories(budget_id);
 "#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn migrate_backfills_transaction_subscription_id() {
+        let conn = Connection::open_in_memory().unwrap();
+        initialize(&conn).unwrap();
+        conn.execute_batch(
+            r#"INSERT INTO accounts (name) VALUES ('Lønkonto');
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-01-05', 'Spotify', -9900);
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-01-06', 'Netto', -4500);
+               INSERT INTO subscriptions (account_id, payee_pattern, amount, frequency) VALUES (1, 'spotify', -9900, 'monthly');
+               INSERT INTO subscription_transactions (subscription_id, transaction_id) VALUES (1, 1);"#,
+        )
+        .unwrap();
+
+        // A database upgraded before the column was kept in sync: the link exists, the column is empty
+        initialize(&conn).unwrap();
+        let column = |id: i64| -> Option<i64> {
+            conn.query_row("SELECT subscription_id FROM transactions WHERE id = ?1", [id], |row| row.get(0)).unwrap()
+        };
+        assert_eq!(column(1), Some(1));
+        assert_eq!(column(2), None);
+    }
+
+    #[test]
+    fn migrated_income_stream_direction_is_checked() {
+        let conn = Connection::open_in_memory().unwrap();
+        // income_streams as created before streams had a direction
+        conn.execute_batch(
+            r#"CREATE TABLE income_streams (
+                   id INTEGER PRIMARY KEY AUTOINCREMENT,
+                   name TEXT NOT NULL,
+                   expected_amount INTEGER NOT NULL,
+                   frequency TEXT NOT NULL,
+                   category_id INTEGER,
+                   is_active INTEGER DEFAULT 1
+               );
+               INSERT INTO income_streams (name, expected_amount, frequency) VALUES ('Løn', 3000000, 'monthly');"#,
+        )
+        .unwrap();
+        initialize(&conn).unwrap();
+
+        let direction: String = conn
+            .query_row("SELECT direction FROM income_streams WHERE id = 1", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(direction, "inflow");
+        assert!(conn
+            .execute("UPDATE income_streams SET direction = 'sideways' WHERE id = 1", [])
+            .is_err());
+        conn.execute("UPDATE income_streams SET direction = 'outflow' WHERE id = 1", []).unwrap();
+    }
+
+    #[test]
+    fn migrate_moves_app_settings_to_settings() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"CREATE TABLE app_settings (key TEXT PRIMARY KEY, value TEXT NOT NULL);
+               INSERT INTO app_settings (key, value) VALUES ('base_currency', '"EUR"');"#,
+        )
+        .unwrap();
+        initialize(&conn).unwrap();
+
+        let value: String = conn
+            .query_row("SELECT value FROM settings WHERE key = 'base_currency'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(value, "\"EUR\"");
+        let leftover: i64 = conn
+            .query_row("SELECT COUNT(*) FROM sqlite_master WHERE name = 'app_settings'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(leftover, 0);
+    }
+
+    #[test]
+    fn migrated_accounts_get_the_default_color() {
+        let conn = Connection::open_in_memory().unwrap();
+        conn.execute_batch(
+            r#"CREATE TABLE accounts (id INTEGER PRIMARY KEY AUTOINCREMENT, name TEXT NOT NULL, account_number TEXT, currency TEXT DEFAULT 'DKK');
+               INSERT INTO accounts (name) VALUES ('Lønkonto');"#,
+        )
+        .unwrap();
+        initialize(&conn).unwrap();
+
+        let (color, icon): (String, Option<String>) = conn
+            .query_row("SELECT color, icon FROM accounts WHERE id = 1", [], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap();
+        assert_eq!((color.as_str(), icon), ("#6366f1", None));
+    }
+}
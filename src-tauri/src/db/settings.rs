@@ -0,0 +1,103 @@
+use rusqlite::{params, Connection, OptionalExtension};
+use serde_json::{json, Value};
+
+/// Settings written on first run. Keys missing from the database fall back to these.
+fn defaults() -> Vec<(&'static str, Value)> {
+    vec![
+        ("base_currency", json!("DKK")),
+        ("default_account_id", Value::Null),
+        ("date_format", json!("dd-mm-yyyy")),
+        ("subscription_confidence_threshold", json!(0.6)),
+        ("subscription_amount_tolerance_pct", json!(0)),
+        ("annual_discount_pct", json!(15)),
+        ("import_amount_ceiling", json!(1_000_000_000)),
+    ]
+}
+
+/// Insert any default settings that are not stored yet; existing values are left untouched
+pub fn seed_defaults(conn: &Connection) -> Result<(), rusqlite::Error> {
+    let mut stmt = conn.prepare("INSERT OR IGNORE INTO settings (key, value) VALUES (?1, ?2)")?;
+    for (key, value) in defaults() {
+        stmt.execute(params![key, value.to_string()])?;
+    }
+    Ok(())
+}
+
+/// Stored value for `key`, or its default if it has never been set.
+/// Values that are not valid JSON (written by hand) are returned as strings.
+pub fn get_setting(conn: &Connection, key: &str) -> Result<Option<Value>, rusqlite::Error> {
+    let stored: Option<String> = conn
+        .query_row("SELECT value FROM settings WHERE key = ?1", params![key], |row| row.get(0))
+        .optional()?;
+
+    Ok(match stored {
+        Some(raw) => Some(serde_json::from_str(&raw).unwrap_or(Value::String(raw))),
+        None => defaults().into_iter().find(|(k, _)| *k == key).map(|(_, v)| v),
+    })
+}
+
+pub fn set_setting(conn: &Connection, key: &str, value: &Value) -> Result<(), rusqlite::Error> {
+    if key.trim().is_empty() {
+        return Err(rusqlite::Error::InvalidParameterName("Setting key cannot be empty".to_string()));
+    }
+    conn.execute(
+        "INSERT INTO settings (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value.to_string()],
+    )?;
+    Ok(())
+}
+
+/// Numeric setting, falling back to `default` when missing or not a number
+pub fn get_f64(conn: &Connection, key: &str, default: f64) -> Result<f64, rusqlite::Error> {
+    let value = get_setting(conn, key)?;
+    Ok(value
+        .and_then(|v| match v {
+            Value::Number(n) => n.as_f64(),
+            Value::String(s) => s.trim().parse().ok(),
+            _ => None,
+        })
+        .unwrap_or(default))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn
+    }
+
+    #[test]
+    fn set_values_round_trip() {
+        let conn = setup();
+        for (key, value) in [
+            ("base_currency", json!("EUR")),
+            ("default_account_id", json!(3)),
+            ("subscription_confidence_threshold", json!(0.75)),
+            ("custom", json!({ "columns": ["date", "amount"] })),
+        ] {
+            set_setting(&conn, key, &value).unwrap();
+            assert_eq!(get_setting(&conn, key).unwrap(), Some(value));
+        }
+        assert!(set_setting(&conn, " ", &json!(1)).is_err());
+    }
+
+    #[test]
+    fn missing_keys_fall_back_to_defaults() {
+        let conn = setup();
+        assert_eq!(get_setting(&conn, "date_format").unwrap(), Some(json!("dd-mm-yyyy")));
+
+        conn.execute("DELETE FROM settings", []).unwrap();
+        assert_eq!(get_setting(&conn, "base_currency").unwrap(), Some(json!("DKK")));
+        assert_eq!(get_f64(&conn, "subscription_confidence_threshold", 0.0).unwrap(), 0.6);
+        assert_eq!(get_setting(&conn, "unknown").unwrap(), None);
+        assert_eq!(get_f64(&conn, "unknown", 2.5).unwrap(), 2.5);
+
+        conn.execute("INSERT INTO settings (key, value) VALUES ('annual_discount_pct', '20 ')", []).unwrap();
+        assert_eq!(get_f64(&conn, "annual_discount_pct", 15.0).unwrap(), 20.0);
+    }
+}
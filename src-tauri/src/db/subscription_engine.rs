@@ -1,13 +1,69 @@
 use rusqlite::{params, Connection};
-use super::models::Subscription;
+use super::models::{DetectedSubscription, Subscription, SubscriptionOccurrence, TransactionWithCategory};
+use super::{settings, subscriptions};
+use serde_json::Value;
 use std::collections::{HashMap, HashSet};
 use chrono::{NaiveDate, Duration};
 
+/// Minimum confidence for a detected pattern when `subscription_confidence_threshold` is not set
+const DEFAULT_CONFIDENCE_THRESHOLD: f64 = 0.6;
+
 /// Analyze transactions for an account and detect recurring payment patterns
-/// Excludes patterns that are already saved as subscriptions
-pub fn detect_subscriptions(conn: &Connection, account_id: i64) -> Result<Vec<Subscription>, rusqlite::Error> {
+/// Excludes patterns that are already saved as subscriptions.
+/// Unless `force_full` is set, only patterns with a transaction newer than the last reviewed
+/// detection (see `mark_detection_reviewed`) are reported. Detecting doesn't move that point,
+/// so candidates keep coming back until the user has saved or ignored them.
+pub fn detect_subscriptions(
+    conn: &Connection,
+    account_id: i64,
+    force_full: bool,
+) -> Result<Vec<Subscription>, rusqlite::Error> {
+    let since = if force_full {
+        None
+    } else {
+        settings::get_setting(conn, &detection_key(account_id))?.and_then(|v| v.as_str().map(str::to_string))
+    };
+
+    let detected = detect(conn, account_id, since.as_deref())?;
+    Ok(detected.into_iter().map(|d| d.subscription).collect())
+}
+
+/// Record that the account's detected candidates have been saved or ignored, so the next
+/// incremental `detect_subscriptions` skips the history analyzed so far
+pub fn mark_detection_reviewed(conn: &Connection, account_id: i64) -> Result<(), rusqlite::Error> {
+    let latest: Option<String> = conn.query_row(
+        "SELECT MAX(date) FROM transactions WHERE account_id = ?1 AND amount < 0",
+        params![account_id],
+        |row| row.get(0),
+    )?;
+    if let Some(latest) = latest {
+        settings::set_setting(conn, &detection_key(account_id), &Value::String(latest))?;
+    }
+    Ok(())
+}
+
+fn detection_key(account_id: i64) -> String {
+    format!("subscription_detection_date:{}", account_id)
+}
+
+/// Same as a full `detect_subscriptions`, with the matching transactions (oldest first) included as evidence
+pub fn detect_subscriptions_detailed(
+    conn: &Connection,
+    account_id: i64,
+) -> Result<Vec<DetectedSubscription>, rusqlite::Error> {
+    detect(conn, account_id, None)
+}
+
+/// Detect recurring patterns, skipping those whose latest transaction is on or before `since`
+fn detect(
+    conn: &Connection,
+    account_id: i64,
+    since: Option<&str>,
+) -> Result<Vec<DetectedSubscription>, rusqlite::Error> {
     // 0. Get existing subscription patterns to exclude
     let existing_patterns = get_existing_patterns(conn, account_id)?;
+    let threshold = settings::get_f64(conn, "subscription_confidence_threshold", DEFAULT_CONFIDENCE_THRESHOLD)?;
+    let tolerance_pct = settings::get_f64(conn, "subscription_amount_tolerance_pct", 0.0)?.max(0.0);
     
     // 1. Get all transactions for the account (expenses only, negative amounts)
     let mut stmt = conn.prepare(
@@ -28,20 +84,33 @@ pub fn detect_subscriptions(conn: &Connection, account_id: i64) -> Result<Vec<Su
     }
     
     // 2. Group transactions by normalized payee and amount
-    let mut groups: HashMap<(String, i64), Vec<(i64, String)>> = HashMap::new();
+    let mut exact_groups: HashMap<(String, i64), Occurrences> = HashMap::new();
+    let mut payees: HashMap<i64, String> = HashMap::new();
+    let mut amounts: HashMap<i64, i64> = HashMap::new();
     
     for (id, payee, amount, date) in transactions {
         let normalized = normalize_payee(&payee);
         let key = (normalized, amount);
-        groups.entry(key).or_default().push((id, date));
+        exact_groups.entry(key).or_default().push((id, date));
+        payees.insert(id, payee);
+        amounts.insert(id, amount);
     }
+
+    let groups = if tolerance_pct > 0.0 {
+        group_with_amount_tolerance(exact_groups, &amounts, tolerance_pct)
+    } else {
+        exact_groups.into_iter().collect()
+    };
     
     // 3. Analyze each group for recurring patterns
-    let mut detected: Vec<Subscription> = vec![];
+    let mut detected: Vec<DetectedSubscription> = vec![];
     
     for ((payee_pattern, amount), mut occurrences) in groups {
         // Skip if already saved as a subscription
-        if existing_patterns.contains(&(payee_pattern.clone(), amount)) {
+        if existing_patterns
+            .iter()
+            .any(|(pattern, saved)| *pattern == payee_pattern && amount_within(amount, *saved, tolerance_pct))
+        {
             continue;
         }
         
@@ -52,39 +121,140 @@ pub fn detect_subscriptions(conn: &Connection, account_id: i64) -> Result<Vec<Su
         
         // Sort by date (ascending)
         occurrences.sort_by(|a, b| a.1.cmp(&b.1));
+
+        // Nothing new since the last run, so this pattern was already analyzed
+        if since.is_some_and(|since| occurrences.last().is_some_and(|(_, date)| date.as_str() <= since)) {
+            continue;
+        }
         
         // Calculate intervals between occurrences
         let intervals = calculate_intervals(&occurrences);
         
         if let Some((frequency, confidence)) = detect_frequency(&intervals) {
-            if confidence >= 0.6 {
+            if confidence >= threshold {
                 let last_date = &occurrences.last().unwrap().1;
                 let next_date = predict_next_date(last_date, &frequency);
                 let tx_ids: Vec<i64> = occurrences.iter().map(|(id, _)| *id).collect();
+                let evidence: Vec<SubscriptionOccurrence> = occurrences
+                    .iter()
+                    .map(|(id, date)| SubscriptionOccurrence {
+                        transaction_id: *id,
+                        date: date.clone(),
+                        amount: amounts.get(id).copied().unwrap_or(amount),
+                        payee: payees.get(id).cloned().unwrap_or_default(),
+                    })
+                    .collect();
                 
-                detected.push(Subscription {
-                    id: None,
-                    account_id,
-                    payee_pattern,
-                    amount,
-                    frequency,
-                    last_charge_date: Some(last_date.clone()),
-                    next_charge_date: next_date,
-                    is_active: true,
-                    category_id: None,
-                    confidence,
-                    transaction_ids: tx_ids,
+                detected.push(DetectedSubscription {
+                    subscription: Subscription {
+                        id: None,
+                        account_id,
+                        payee_pattern,
+                        amount,
+                        frequency,
+                        last_charge_date: Some(last_date.clone()),
+                        next_charge_date: next_date,
+                        is_active: true,
+                        category_id: None,
+                        confidence,
+                        transaction_ids: tx_ids,
+                    },
+                    occurrences: evidence,
                 });
             }
         }
     }
     
     // Sort by confidence (highest first)
-    detected.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
+    detected.sort_by(|a, b| b.subscription.confidence.partial_cmp(&a.subscription.confidence).unwrap());
     
     Ok(detected)
 }
 
+/// (transaction id, date) pairs of one candidate subscription
+type Occurrences = Vec<(i64, String)>;
+
+/// Interval ranges (in days) that `detect_frequency` recognises: weekly, biweekly, monthly, yearly
+const CADENCE_BANDS: [(i64, i64); 4] = [(6, 8), (12, 16), (25, 35), (355, 375)];
+
+/// Merge a payee's exact-amount groups whose amounts are within `tolerance_pct` of each other,
+/// then split each merged group by cadence so e.g. a monthly fee and a yearly fee of similar size
+/// stay separate. Groups are keyed by the amount of their most recent transaction.
+fn group_with_amount_tolerance(
+    exact_groups: HashMap<(String, i64), Occurrences>,
+    amounts: &HashMap<i64, i64>,
+    tolerance_pct: f64,
+) -> Vec<((String, i64), Occurrences)> {
+    let mut by_payee: HashMap<String, Vec<(i64, Occurrences)>> = HashMap::new();
+    for ((payee_pattern, amount), occurrences) in exact_groups {
+        by_payee.entry(payee_pattern).or_default().push((amount, occurrences));
+    }
+
+    let mut groups = Vec::new();
+    for (payee_pattern, mut amount_groups) in by_payee {
+        // Cluster neighbouring amounts, each cluster anchored on its smallest amount
+        amount_groups.sort_by_key(|(amount, _)| amount.abs());
+        let mut clusters: Vec<(i64, Occurrences)> = Vec::new();
+        for (amount, occurrences) in amount_groups {
+            match clusters.last_mut() {
+                Some((anchor, cluster)) if amount_within(amount, *anchor, tolerance_pct) => cluster.extend(occurrences),
+                _ => clusters.push((amount, occurrences)),
+            }
+        }
+
+        for (_, mut occurrences) in clusters {
+            occurrences.sort_by(|a, b| a.1.cmp(&b.1));
+            for chain in split_by_cadence(occurrences) {
+                let amount = chain.last().and_then(|(id, _)| amounts.get(id)).copied().unwrap_or_default();
+                groups.push(((payee_pattern.clone(), amount), chain));
+            }
+        }
+    }
+    groups
+}
+
+/// Split date-sorted occurrences into chains that each repeat at one cadence. Starting from the
+/// earliest remaining occurrence, the cadence giving the longest chain wins; chains of a single
+/// occurrence can't be a subscription and are dropped.
+fn split_by_cadence(mut remaining: Occurrences) -> Vec<Occurrences> {
+    let parse = |date: &str| NaiveDate::parse_from_str(date, "%Y-%m-%d").ok();
+    let mut chains = Vec::new();
+
+    while !remaining.is_empty() {
+        let mut best: Vec<usize> = vec![0];
+        for (min_days, max_days) in CADENCE_BANDS {
+            let mut chain = vec![0];
+            for i in 1..remaining.len() {
+                let last = &remaining[*chain.last().unwrap_or(&0)].1;
+                let (Some(prev), Some(curr)) = (parse(last), parse(&remaining[i].1)) else {
+                    continue;
+                };
+                if (min_days..=max_days).contains(&(curr - prev).num_days()) {
+                    chain.push(i);
+                }
+            }
+            if chain.len() > best.len() {
+                best = chain;
+            }
+        }
+
+        let mut taken = Vec::new();
+        for &i in best.iter().rev() {
+            taken.push(remaining.remove(i));
+        }
+        taken.reverse();
+        if taken.len() >= 2 {
+            chains.push(taken);
+        }
+    }
+    chains
+}
+
+/// Whether `amount` is within `tolerance_pct` of `reference`; with no tolerance they must be equal
+fn amount_within(amount: i64, reference: i64, tolerance_pct: f64) -> bool {
+    (amount - reference).abs() as f64 <= reference.abs() as f64 * tolerance_pct / 100.0
+}
+
 /// Get existing subscription patterns for deduplication
 fn get_existing_patterns(conn: &Connection, account_id: i64) -> Result<HashSet<(String, i64)>, rusqlite::Error> {
     let mut stmt = conn.prepare(
@@ -101,8 +271,34 @@ fn get_existing_patterns(conn: &Connection, account_id: i64) -> Result<HashSet<(
     Ok(patterns)
 }
 
+/// Tag transactions that belong to one of their account's saved, active subscriptions: linked to
+/// it directly, or with the same normalized payee and an amount within the detection tolerance.
+pub fn tag_subscriptions(conn: &Connection, transactions: &mut [TransactionWithCategory]) -> Result<(), rusqlite::Error> {
+    let tolerance_pct = settings::get_f64(conn, "subscription_amount_tolerance_pct", 0.0)?.max(0.0);
+    let account_ids: HashSet<i64> = transactions.iter().map(|row| row.transaction.account_id).collect();
+    let mut by_account: HashMap<i64, Vec<Subscription>> = HashMap::new();
+    for account_id in account_ids {
+        by_account.insert(account_id, subscriptions::get_by_account(conn, account_id)?);
+    }
+
+    for row in transactions.iter_mut() {
+        let tx = &row.transaction;
+        let Some(subs) = by_account.get(&tx.account_id) else { continue };
+        let payee = normalize_payee(&tx.payee);
+        let matched = subs.iter().find(|sub| {
+            (tx.subscription_id.is_some() && sub.id == tx.subscription_id)
+                || (sub.payee_pattern == payee && amount_within(tx.amount, sub.amount, tolerance_pct))
+        });
+        if let Some(sub) = matched {
+            row.matched_subscription_id = sub.id;
+            row.matched_subscription_pattern = Some(sub.payee_pattern.clone());
+        }
+    }
+    Ok(())
+}
+
 /// Normalize payee name for grouping
-fn normalize_payee(payee: &str) -> String {
+pub fn normalize_payee(payee: &str) -> String {
     let lower = payee.to_lowercase();
     // Remove common noise patterns (dates, transaction IDs, etc.)
     let cleaned: String = lower
@@ -113,7 +309,7 @@ fn normalize_payee(payee: &str) -> String {
 }
 
 /// Calculate day intervals between consecutive transactions
-fn calculate_intervals(occurrences: &[(i64, String)]) -> Vec<i64> {
+pub fn calculate_intervals(occurrences: &[(i64, String)]) -> Vec<i64> {
     let mut intervals = vec![];
     
     for i in 1..occurrences.len() {
@@ -132,7 +328,7 @@ fn calculate_intervals(occurrences: &[(i64, String)]) -> Vec<i64> {
 }
 
 /// Detect frequency from intervals
-fn detect_frequency(intervals: &[i64]) -> Option<(String, f64)> {
+pub fn detect_frequency(intervals: &[i64]) -> Option<(String, f64)> {
     if intervals.is_empty() {
         return None;
     }
@@ -167,7 +363,7 @@ fn detect_frequency(intervals: &[i64]) -> Option<(String, f64)> {
 }
 
 /// Predict the next charge date based on frequency
-fn predict_next_date(last_date: &str, frequency: &str) -> Option<String> {
+pub fn predict_next_date(last_date: &str, frequency: &str) -> Option<String> {
     let date = NaiveDate::parse_from_str(last_date, "%Y-%m-%d").ok()?;
     
     let next = match frequency {
@@ -180,3 +376,109 @@ fn predict_next_date(last_date: &str, frequency: &str) -> Option<String> {
     
     Some(next.format("%Y-%m-%d").to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, date: &str, payee: &str, amount: i64) {
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, ?1, ?2, ?3)",
+            params![date, payee, amount],
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn candidates_come_back_until_the_detection_is_reviewed() {
+        let conn = setup();
+        for date in ["2024-01-05", "2024-02-05", "2024-03-05"] {
+            insert(&conn, date, "Netflix", -9900);
+        }
+
+        assert_eq!(detect_subscriptions(&conn, 1, false).unwrap().len(), 1);
+        // Detecting again without saving or ignoring still offers the candidate
+        assert_eq!(detect_subscriptions(&conn, 1, false).unwrap().len(), 1);
+
+        mark_detection_reviewed(&conn, 1).unwrap();
+        assert!(detect_subscriptions(&conn, 1, false).unwrap().is_empty());
+        assert_eq!(detect_subscriptions(&conn, 1, true).unwrap().len(), 1);
+
+        insert(&conn, "2024-04-05", "Netflix", -9900);
+        assert_eq!(detect_subscriptions(&conn, 1, false).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn detailed_detection_includes_the_occurrences() {
+        let conn = setup();
+        for date in ["2024-03-05", "2024-01-05", "2024-02-05"] {
+            insert(&conn, date, "NETFLIX.COM", -9900);
+        }
+        insert(&conn, "2024-02-10", "Netto", -4500);
+
+        let detected = detect_subscriptions_detailed(&conn, 1).unwrap();
+        assert_eq!(detected.len(), 1);
+        let occurrences: Vec<(&str, i64, &str)> = detected[0]
+            .occurrences
+            .iter()
+            .map(|o| (o.date.as_str(), o.amount, o.payee.as_str()))
+            .collect();
+        assert_eq!(
+            occurrences,
+            [("2024-01-05", -9900, "NETFLIX.COM"), ("2024-02-05", -9900, "NETFLIX.COM"), ("2024-03-05", -9900, "NETFLIX.COM")]
+        );
+        let mut ids: Vec<i64> = detected[0].occurrences.iter().map(|o| o.transaction_id).collect();
+        ids.sort();
+        assert_eq!(ids, [1, 2, 3]);
+    }
+
+    #[test]
+    fn similar_monthly_and_yearly_charges_stay_separate() {
+        let conn = setup();
+        settings::set_setting(&conn, "subscription_amount_tolerance_pct", &serde_json::json!(10)).unwrap();
+        for month in 1..=6 {
+            insert(&conn, &format!("2024-{:02}-01", month), "Fjernvarme", -50000);
+        }
+        insert(&conn, "2023-03-15", "Fjernvarme", -52000);
+        insert(&conn, "2024-03-15", "Fjernvarme", -52000);
+
+        let mut found: Vec<(String, i64, usize)> = detect_subscriptions(&conn, 1, true)
+            .unwrap()
+            .into_iter()
+            .map(|s| (s.frequency, s.amount, s.transaction_ids.len()))
+            .collect();
+        found.sort();
+        assert_eq!(found, [("monthly".to_string(), -50000, 6), ("yearly".to_string(), -52000, 2)]);
+    }
+
+    #[test]
+    fn saved_subscriptions_tag_their_transactions() {
+        let conn = setup();
+        for date in ["2024-01-05", "2024-02-05", "2024-03-05"] {
+            insert(&conn, date, "NETFLIX.COM 1234", -9900);
+        }
+        insert(&conn, "2024-03-06", "NETFLIX.COM 1234", -14900);
+        insert(&conn, "2024-03-07", "Netto", -4500);
+        let detected = detect_subscriptions(&conn, 1, true).unwrap();
+        let subscription_id = subscriptions::create(&conn, &detected[0]).unwrap();
+        conn.execute("UPDATE transactions SET subscription_id = ?1 WHERE id = 5", params![subscription_id]).unwrap();
+
+        let mut rows = crate::db::transactions::get_by_account(&conn, 1, None).unwrap();
+        tag_subscriptions(&conn, &mut rows).unwrap();
+        let mut tagged: Vec<(i64, Option<i64>, Option<String>)> = rows
+            .into_iter()
+            .map(|r| (r.transaction.id.unwrap(), r.matched_subscription_id, r.matched_subscription_pattern))
+            .collect();
+        tagged.sort();
+        let netflix = |id: i64| (id, Some(subscription_id), Some(detected[0].payee_pattern.clone()));
+        assert_eq!(tagged, [netflix(1), netflix(2), netflix(3), (4, None, None), netflix(5)]);
+    }
+}
@@ -1,4 +1,4 @@
-use rusqlite::{params, Connection};
+use rusqlite::{params, Connection, OptionalExtension};
 use super::models::Subscription;
 
 /// Create a new subscription and link its transactions
@@ -24,15 +24,26 @@ pub fn create(conn: &Connection, sub: &Subscription) -> Result<i64, rusqlite::Er
     
     // Link transactions to this subscription
     for tx_id in &sub.transaction_ids {
-        conn.execute(
-            "INSERT OR IGNORE INTO subscription_transactions (subscription_id, transaction_id) VALUES (?1, ?2)",
-            params![sub_id, tx_id],
-        )?;
+        link_transaction(conn, sub_id, *tx_id)?;
     }
     
     Ok(sub_id)
 }
 
+/// Link a transaction to a subscription.
+/// The link table is the source of truth; `transactions.subscription_id` is kept in sync for fast filtering.
+pub fn link_transaction(conn: &Connection, subscription_id: i64, transaction_id: i64) -> Result<(), rusqlite::Error> {
+    conn.execute(
+        "INSERT OR IGNORE INTO subscription_transactions (subscription_id, transaction_id) VALUES (?1, ?2)",
+        params![subscription_id, transaction_id],
+    )?;
+    conn.execute(
+        "UPDATE transactions SET subscription_id = ?1 WHERE id = ?2",
+        params![subscription_id, transaction_id],
+    )?;
+    Ok(())
+}
+
 /// Get all subscriptions for an account
 pub fn get_by_account(conn: &Connection, account_id: i64) -> Result<Vec<Subscription>, rusqlite::Error> {
     let mut stmt = conn.prepare(
@@ -74,6 +85,42 @@ pub fn get_by_account(conn: &Connection, account_id: i64) -> Result<Vec<Subscrip
     Ok(result)
 }
 
+/// Get a single subscription (active or dismissed) with its linked transaction IDs
+pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<Subscription>, rusqlite::Error> {
+    let sub = conn
+        .query_row(
+            r#"SELECT id, account_id, payee_pattern, amount, frequency,
+               last_charge_date, next_charge_date, is_active, category_id, confidence
+               FROM subscriptions WHERE id = ?1"#,
+            params![id],
+            |row| {
+                Ok(Subscription {
+                    id: Some(row.get(0)?),
+                    account_id: row.get(1)?,
+                    payee_pattern: row.get(2)?,
+                    amount: row.get(3)?,
+                    frequency: row.get(4)?,
+                    last_charge_date: row.get(5)?,
+                    next_charge_date: row.get(6)?,
+                    is_active: row.get::<_, i64>(7)? != 0,
+                    category_id: row.get(8)?,
+                    confidence: row.get(9)?,
+                    transaction_ids: vec![],
+                })
+            },
+        )
+        .optional()?;
+
+    let Some(mut sub) = sub else {
+        return Ok(None);
+    };
+    let mut tx_stmt = conn.prepare("SELECT transaction_id FROM subscription_transactions WHERE subscription_id = ?1")?;
+    sub.transaction_ids = tx_stmt
+        .query_map(params![id], |row| row.get(0))?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(sub))
+}
+
 /// Dismiss (deactivate) a subscription
 pub fn dismiss(conn: &Connection, id: i64) -> Result<usize, rusqlite::Error> {
     conn.execute(
@@ -94,3 +141,46 @@ pub fn clear_for_account(conn: &Connection, account_id: i64) -> Result<usize, ru
         params![account_id],
     )
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{schema, transactions};
+
+    #[test]
+    fn create_fills_transaction_subscription_id() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-01-05', 'Spotify', -9900)",
+            [],
+        )
+        .unwrap();
+        let tx_id = conn.last_insert_rowid();
+
+        let sub_id = create(
+            &conn,
+            &Subscription {
+                id: None,
+                account_id: 1,
+                payee_pattern: "spotify".to_string(),
+                amount: -9900,
+                frequency: "monthly".to_string(),
+                last_charge_date: Some("2024-01-05".to_string()),
+                next_charge_date: Some("2024-02-05".to_string()),
+                is_active: true,
+                category_id: None,
+                confidence: 1.0,
+                transaction_ids: vec![tx_id],
+            },
+        )
+        .unwrap();
+
+        let rows = transactions::get_by_account(&conn, 1, None).unwrap();
+        assert_eq!(rows[0].transaction.subscription_id, Some(sub_id));
+
+        delete(&conn, sub_id).unwrap();
+        assert_eq!(transactions::get_by_id(&conn, tx_id).unwrap().unwrap().subscription_id, None);
+    }
+}
@@ -0,0 +1,109 @@
+use rusqlite::Connection;
+use super::{accounts, budgets, reports, transactions};
+use super::models::HomeSummary;
+use chrono::{Duration, NaiveDate};
+
+/// How far ahead upcoming subscription charges are counted
+const UPCOMING_DAYS: i64 = 30;
+
+/// Everything the home screen shows for `month` (YYYY-MM), as of today
+pub fn home_summary(conn: &Connection, month: &str) -> Result<HomeSummary, rusqlite::Error> {
+    home_summary_at(conn, month, reports::current_date(conn)?)
+}
+
+/// Same as `home_summary`, with net worth and upcoming charges computed relative to `today`
+pub fn home_summary_at(conn: &Connection, month: &str, today: NaiveDate) -> Result<HomeSummary, rusqlite::Error> {
+    let today_str = today.format("%Y-%m-%d").to_string();
+    let upcoming_end = (today + Duration::days(UPCOMING_DAYS)).format("%Y-%m-%d").to_string();
+
+    let mut net_worth = 0;
+    let mut upcoming_charges_total = 0;
+    for account in accounts::get_all(conn)? {
+        let account_id = account.id.unwrap_or_default();
+        net_worth += transactions::balance_at(conn, account_id, &today_str)?;
+        upcoming_charges_total += reports::get_subscription_calendar(conn, account_id, &today_str, &upcoming_end)?
+            .iter()
+            .map(|event| event.amount.abs())
+            .sum::<i64>();
+    }
+    // Outflow streams aren't tied to an account, so they're counted once
+    upcoming_charges_total += reports::get_income_stream_calendar(conn, &today_str, &upcoming_end)?
+        .iter()
+        .filter(|event| event.event_type == "charge")
+        .map(|event| event.amount.abs())
+        .sum::<i64>();
+
+    // ISO dates compare as strings, so day 31 covers every month length
+    let totals = transactions::savings_rate_all_accounts(conn, &format!("{}-01", month), &format!("{}-31", month))?;
+
+    let overspent_budget_count = budgets::get_budgets_with_spending_at(conn, month, None, today)?
+        .iter()
+        .filter(|b| b.spent_amount > b.allocated_amount && b.allocated_amount > 0)
+        .count();
+
+    Ok(HomeSummary {
+        month: month.to_string(),
+        net_worth,
+        income: totals.income,
+        expense: totals.expense,
+        net: totals.income - totals.expense,
+        overspent_budget_count,
+        upcoming_charges_total,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema;
+    use serde_json::json;
+
+    #[test]
+    fn summary_serializes_with_the_fields_the_frontend_reads() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute_batch(
+            r#"INSERT INTO accounts (name) VALUES ('Lønkonto');
+               INSERT INTO categories (name) VALUES ('Dagligvarer');
+               INSERT INTO budgets (name) VALUES ('Mad');
+               INSERT INTO budget_categories (budget_id, category_id) VALUES (1, 1);
+               INSERT INTO budget_allocations (budget_id, month, allocated_amount) VALUES (1, '2024-04', 100000);
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-04-01', 'Løn', 2500000);
+               INSERT INTO transactions (account_id, date, payee, amount, category_id) VALUES (1, '2024-04-10', 'Netto', -150000, 1);
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-04-28', 'Husleje', -800000);
+               INSERT INTO subscriptions (account_id, payee_pattern, amount, frequency, next_charge_date)
+                   VALUES (1, 'netflix', -9900, 'monthly', '2024-04-25');"#,
+        )
+        .unwrap();
+
+        let summary = home_summary_at(&conn, "2024-04", NaiveDate::from_ymd_opt(2024, 4, 20).unwrap()).unwrap();
+        assert_eq!(
+            serde_json::to_value(&summary).unwrap(),
+            json!({
+                "month": "2024-04",
+                "net_worth": 2350000,
+                "income": 2500000,
+                "expense": 950000,
+                "net": 1550000,
+                "overspent_budget_count": 1,
+                "upcoming_charges_total": 9900,
+            })
+        );
+    }
+
+    #[test]
+    fn outflow_streams_count_once_across_accounts() {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute_batch(
+            r#"INSERT INTO accounts (name) VALUES ('Lønkonto');
+               INSERT INTO accounts (name) VALUES ('Budgetkonto');
+               INSERT INTO income_streams (name, expected_amount, frequency, direction) VALUES ('Billån', -250000, 'monthly', 'outflow');
+               INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, '2024-04-05', 'Afdrag', -250000);"#,
+        )
+        .unwrap();
+
+        let summary = home_summary_at(&conn, "2024-04", NaiveDate::from_ymd_opt(2024, 4, 20).unwrap()).unwrap();
+        assert_eq!(summary.upcoming_charges_total, 250000);
+    }
+}
@@ -1,46 +1,53 @@
 use rusqlite::{params, Connection, OptionalExtension};
-use super::models::{Transaction, TransactionWithCategory};
+use super::models::{AnnualSpendingProjection, BalanceDiscrepancy, CategoryAnnualProjection, CategoryAverage, HashCollision, PayeeCategoryInconsistency, TransactionGap, MonthSpendProjection, PayeeFirstSeen, QuarterlySummary, RecomputeHashesResult, ReconciliationSummary, SavingsRate, Transaction, TransactionWithAccount, TransactionWithCategory};
+use super::{audit, budgets, reports};
+use chrono::{Datelike, Months, NaiveDate};
+use std::collections::HashMap;
 
 pub fn create(conn: &Connection, tx: &Transaction) -> Result<i64, rusqlite::Error> {
     conn.execute(
         r#"INSERT INTO transactions 
-           (account_id, category_id, date, payee, amount, balance_snapshot, status, is_reconciled, import_hash)
-           VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"#,
+           (account_id, category_id, date, payee, raw_payee, memo, amount, balance_snapshot, status, is_reconciled, import_hash, needs_review)
+           VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12)"#,
         params![
             tx.account_id,
             tx.category_id,
             tx.date,
             tx.payee,
+            tx.raw_payee,
+            tx.memo,
             tx.amount,
             tx.balance_snapshot,
             tx.status,
             tx.is_reconciled as i64,
             tx.import_hash,
+            tx.needs_review as i64,
         ],
     )?;
     Ok(conn.last_insert_rowid())
 }
 
+/// Column list shared by every query that returns `TransactionWithCategory`
+const SELECT_WITH_CATEGORY: &str = r#"SELECT
+            t.id, t.account_id, t.category_id, t.date, t.payee, t.amount,
+            t.balance_snapshot, t.status, t.is_reconciled, t.import_hash, t.subscription_id,
+            t.raw_payee, t.memo, t.needs_review,
+            c.name as category_name,
+            p.name as parent_category_name
+           FROM transactions t
+           LEFT JOIN categories c ON t.category_id = c.id
+           LEFT JOIN categories p ON c.parent_id = p.id"#;
+
 pub fn get_by_id(conn: &Connection, id: i64) -> Result<Option<Transaction>, rusqlite::Error> {
+    get_with_category_by_id(conn, id).map(|tx| tx.map(|t| t.transaction))
+}
+
+/// Get a single transaction with its category names
+pub fn get_with_category_by_id(conn: &Connection, id: i64) -> Result<Option<TransactionWithCategory>, rusqlite::Error> {
     conn.query_row(
-        r#"SELECT id, account_id, category_id, date, payee, amount, 
-           balance_snapshot, status, is_reconciled, import_hash
-           FROM transactions WHERE id = ?1"#,
+        &format!("{} WHERE t.id = ?1", SELECT_WITH_CATEGORY),
         params![id],
-        |row| {
-            Ok(Transaction {
-                id: Some(row.get(0)?),
-                account_id: row.get(1)?,
-                category_id: row.get(2)?,
-                date: row.get(3)?,
-                payee: row.get(4)?,
-                amount: row.get(5)?,
-                balance_snapshot: row.get(6)?,
-                status: row.get(7)?,
-                is_reconciled: row.get::<_, i64>(8)? != 0,
-                import_hash: row.get(9)?,
-            })
-        },
+        map_transaction_with_category,
     )
     .optional()
 }
@@ -52,17 +59,11 @@ pub fn get_by_account(
     limit: Option<i64>,
 ) -> Result<Vec<TransactionWithCategory>, rusqlite::Error> {
     let sql = format!(
-        r#"SELECT 
-            t.id, t.account_id, t.category_id, t.date, t.payee, t.amount, 
-            t.balance_snapshot, t.status, t.is_reconciled, t.import_hash,
-            c.name as category_name,
-            p.name as parent_category_name
-           FROM transactions t
-           LEFT JOIN categories c ON t.category_id = c.id
-           LEFT JOIN categories p ON c.parent_id = p.id
+        r#"{}
            WHERE t.account_id = ?1
            ORDER BY t.date DESC, t.id DESC
            {}"#,
+        SELECT_WITH_CATEGORY,
         limit.map(|l| format!("LIMIT {}", l)).unwrap_or_default()
     );
 
@@ -78,18 +79,12 @@ pub fn get_by_date_range(
     start_date: &str,
     end_date: &str,
 ) -> Result<Vec<TransactionWithCategory>, rusqlite::Error> {
-    let mut stmt = conn.prepare(
-        r#"SELECT 
-            t.id, t.account_id, t.category_id, t.date, t.payee, t.amount, 
-            t.balance_snapshot, t.status, t.is_reconciled, t.import_hash,
-            c.name as category_name,
-            p.name as parent_category_name
-           FROM transactions t
-           LEFT JOIN categories c ON t.category_id = c.id
-           LEFT JOIN categories p ON c.parent_id = p.id
+    let mut stmt = conn.prepare(&format!(
+        r#"{}
            WHERE t.account_id = ?1 AND t.date >= ?2 AND t.date <= ?3
            ORDER BY t.date DESC, t.id DESC"#,
-    )?;
+        SELECT_WITH_CATEGORY
+    ))?;
     let rows = stmt.query_map(
         params![account_id, start_date, end_date],
         map_transaction_with_category,
@@ -97,6 +92,162 @@ pub fn get_by_date_range(
     rows.collect()
 }
 
+/// Get transactions from every account within a date range, with the account name.
+/// Accounts cannot be archived, so no account is left out.
+pub fn get_all_by_date_range(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<TransactionWithAccount>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        r#"SELECT q.*, a.name FROM ({}) q
+           JOIN accounts a ON q.account_id = a.id
+           WHERE q.date >= ?1 AND q.date <= ?2
+           ORDER BY q.date DESC, q.id DESC"#,
+        SELECT_WITH_CATEGORY
+    ))?;
+    let rows = stmt.query_map(params![start_date, end_date], |row| {
+        Ok(TransactionWithAccount {
+            transaction: map_transaction_with_category(row)?,
+            account_name: row.get(16)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Search an account's transactions by payee, optionally also matching the memo
+pub fn search(
+    conn: &Connection,
+    account_id: i64,
+    query: &str,
+    include_memo: bool,
+) -> Result<Vec<TransactionWithCategory>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        r#"{}
+           WHERE t.account_id = ?1
+             AND (t.payee LIKE '%' || ?2 || '%' OR (?3 AND t.memo LIKE '%' || ?2 || '%'))
+           ORDER BY t.date DESC, t.id DESC"#,
+        SELECT_WITH_CATEGORY
+    ))?;
+    let rows = stmt.query_map(
+        params![account_id, query, include_memo],
+        map_transaction_with_category,
+    )?;
+    rows.collect()
+}
+
+/// First transaction date and visit count per payee, most recently discovered first.
+/// With `since_date`, only payees first seen on or after that date are returned.
+pub fn get_payee_first_seen(
+    conn: &Connection,
+    account_id: Option<i64>,
+    since_date: Option<String>,
+) -> Result<Vec<PayeeFirstSeen>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT payee, MIN(date) as first_seen, COUNT(*) as total_visits
+           FROM transactions
+           WHERE (?1 IS NULL OR account_id = ?1)
+           GROUP BY payee
+           HAVING (?2 IS NULL OR first_seen >= ?2)
+           ORDER BY first_seen DESC, payee"#,
+    )?;
+    let rows = stmt.query_map(params![account_id, since_date], |row| {
+        Ok(PayeeFirstSeen {
+            payee: row.get(0)?,
+            first_seen: row.get(1)?,
+            total_visits: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Payees whose transactions are spread over more than one category, most categories first.
+/// Each payee lists its categories with the number of transactions in each, largest first.
+pub fn get_payee_category_inconsistencies(
+    conn: &Connection,
+    account_id: Option<i64>,
+) -> Result<Vec<PayeeCategoryInconsistency>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT payee, category_id, COUNT(*) as tx_count
+           FROM transactions
+           WHERE category_id IS NOT NULL AND (?1 IS NULL OR account_id = ?1)
+             AND payee IN (
+                 SELECT payee FROM transactions
+                 WHERE category_id IS NOT NULL AND (?1 IS NULL OR account_id = ?1)
+                 GROUP BY payee
+                 HAVING COUNT(DISTINCT category_id) > 1
+             )
+           GROUP BY payee, category_id
+           ORDER BY payee, tx_count DESC, category_id"#,
+    )?;
+    let rows: Vec<(String, i64, i64)> = stmt
+        .query_map(params![account_id], |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)))?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut result: Vec<PayeeCategoryInconsistency> = Vec::new();
+    for (payee, category_id, count) in rows {
+        let Some(category) = super::categories::get_by_id(conn, category_id)? else {
+            continue;
+        };
+        match result.last_mut() {
+            Some(last) if last.payee == payee => last.categories.push((category, count)),
+            _ => result.push(PayeeCategoryInconsistency {
+                payee,
+                categories: vec![(category, count)],
+            }),
+        }
+    }
+    result.sort_by(|a, b| b.categories.len().cmp(&a.categories.len()).then_with(|| a.payee.cmp(&b.payee)));
+    Ok(result)
+}
+
+/// Transactions whose category was guessed by a rule and hasn't been confirmed yet
+pub fn get_needing_review(
+    conn: &Connection,
+    account_id: i64,
+) -> Result<Vec<TransactionWithCategory>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        r#"{}
+           WHERE t.account_id = ?1 AND t.needs_review = 1
+           ORDER BY t.date DESC, t.id DESC"#,
+        SELECT_WITH_CATEGORY
+    ))?;
+    let rows = stmt.query_map(params![account_id], map_transaction_with_category)?;
+    rows.collect()
+}
+
+/// Hand-entered transactions on an account. Imported rows keep the payee as it came from the bank
+/// (`raw_payee`) and either an import hash or the `hash_collision` marker left by `recompute_hashes`.
+pub fn get_manual(
+    conn: &Connection,
+    account_id: i64,
+) -> Result<Vec<TransactionWithCategory>, rusqlite::Error> {
+    let mut stmt = conn.prepare(&format!(
+        r#"{}
+           WHERE t.account_id = ?1 AND t.import_hash IS NULL AND t.raw_payee IS NULL AND t.hash_collision = 0
+           ORDER BY t.date DESC, t.id DESC"#,
+        SELECT_WITH_CATEGORY
+    ))?;
+    let rows = stmt.query_map(params![account_id], map_transaction_with_category)?;
+    rows.collect()
+}
+
+/// Accept a transaction's current category
+pub fn confirm_categorization(conn: &Connection, transaction_id: i64) -> Result<usize, rusqlite::Error> {
+    conn.execute(
+        "UPDATE transactions SET needs_review = 0 WHERE id = ?1",
+        params![transaction_id],
+    )
+}
+
+/// Mark a transaction's category as a guess that the user should confirm
+pub fn flag_for_review(conn: &Connection, transaction_id: i64) -> Result<usize, rusqlite::Error> {
+    conn.execute(
+        "UPDATE transactions SET needs_review = 1 WHERE id = ?1",
+        params![transaction_id],
+    )
+}
+
 /// Get spending by category for a date range (for reports)
 pub fn get_spending_by_category(
     conn: &Connection,
@@ -121,6 +272,482 @@ pub fn get_spending_by_category(
     rows.collect()
 }
 
+/// `get_spending_by_category` for several months (YYYY-MM) in one query, keyed by month.
+/// Every requested month gets an entry, empty when nothing was spent.
+pub fn spending_by_category_multi(
+    conn: &Connection,
+    account_id: i64,
+    months: &[String],
+) -> Result<HashMap<String, Vec<(String, i64)>>, rusqlite::Error> {
+    let months = months
+        .iter()
+        .map(|m| budgets::validate_month(m))
+        .collect::<Result<Vec<_>, _>>()?;
+    let mut result: HashMap<String, Vec<(String, i64)>> =
+        months.iter().map(|m| (m.clone(), Vec::new())).collect();
+    if months.is_empty() {
+        return Ok(result);
+    }
+
+    let placeholders = vec!["?"; months.len()].join(",");
+    let mut stmt = conn.prepare(&format!(
+        r#"SELECT
+            strftime('%Y-%m', t.date) as month,
+            COALESCE(p.name, c.name, 'Uncategorized') as category,
+            SUM(t.amount) as total
+           FROM transactions t
+           LEFT JOIN categories c ON t.category_id = c.id
+           LEFT JOIN categories p ON c.parent_id = p.id
+           WHERE t.account_id = ? AND t.amount < 0 AND strftime('%Y-%m', t.date) IN ({})
+           GROUP BY month, category
+           ORDER BY month, total ASC"#,
+        placeholders
+    ))?;
+    let args: Vec<&dyn rusqlite::ToSql> = std::iter::once(&account_id as &dyn rusqlite::ToSql)
+        .chain(months.iter().map(|m| m as &dyn rusqlite::ToSql))
+        .collect();
+    let rows = stmt.query_map(args.as_slice(), |row| {
+        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?, row.get::<_, i64>(2)?))
+    })?;
+    for row in rows {
+        let (month, category, total) = row?;
+        result.entry(month).or_default().push((category, total));
+    }
+    Ok(result)
+}
+
+/// Average size of an expense in each category between two dates (inclusive), largest first.
+/// A category's figures include the transactions of all its subcategories.
+pub fn average_by_category(
+    conn: &Connection,
+    account_id: i64,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<CategoryAverage>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"WITH RECURSIVE subtree(root_id, id) AS (
+               SELECT id, id FROM categories
+               UNION
+               SELECT s.root_id, c.id FROM categories c JOIN subtree s ON c.parent_id = s.id
+           )
+           SELECT r.id, r.name, AVG(-t.amount), COUNT(*)
+           FROM subtree s
+           JOIN categories r ON r.id = s.root_id
+           JOIN transactions t ON t.category_id = s.id
+           WHERE t.account_id = ?1 AND t.date >= ?2 AND t.date <= ?3 AND t.amount < 0
+           GROUP BY r.id
+           ORDER BY AVG(-t.amount) DESC, r.name"#,
+    )?;
+    let rows = stmt.query_map(params![account_id, start_date, end_date], |row| {
+        Ok(CategoryAverage {
+            category_id: row.get(0)?,
+            category_name: row.get(1)?,
+            average_amount: row.get::<_, f64>(2)?.round() as i64,
+            transaction_count: row.get(3)?,
+        })
+    })?;
+    rows.collect()
+}
+
+/// Income, expense and savings rate for an account over a date range
+pub fn savings_rate(
+    conn: &Connection,
+    account_id: i64,
+    start_date: &str,
+    end_date: &str,
+) -> Result<SavingsRate, rusqlite::Error> {
+    query_savings_rate(conn, Some(account_id), start_date, end_date)
+}
+
+/// Income, expense and savings rate across all accounts over a date range
+pub fn savings_rate_all_accounts(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<SavingsRate, rusqlite::Error> {
+    query_savings_rate(conn, None, start_date, end_date)
+}
+
+/// Spending so far in `month` (YYYY-MM) and a linear projection to month-end, as of today
+pub fn project_month_spend(
+    conn: &Connection,
+    account_id: i64,
+    month: &str,
+) -> Result<MonthSpendProjection, rusqlite::Error> {
+    project_month_spend_at(conn, account_id, month, reports::current_date(conn)?)
+}
+
+/// Same as `project_month_spend`, relative to `today`. Today counts as elapsed, so on the first
+/// of the month the projection is a full month at today's pace. Past months are not extrapolated,
+/// and future months project to zero.
+pub fn project_month_spend_at(
+    conn: &Connection,
+    account_id: i64,
+    month: &str,
+    today: NaiveDate,
+) -> Result<MonthSpendProjection, rusqlite::Error> {
+    let start = NaiveDate::parse_from_str(&format!("{}-01", month), "%Y-%m-%d")
+        .map_err(|_| rusqlite::Error::InvalidParameterName(format!("Invalid month: {}", month)))?;
+    let end = start
+        .checked_add_months(Months::new(1))
+        .and_then(|next| next.pred_opt())
+        .unwrap_or(start);
+    let days_in_month = end.day();
+    let elapsed_days = if today > end {
+        days_in_month
+    } else if today < start {
+        0
+    } else {
+        today.day()
+    };
+
+    let actual_to_date: i64 = conn.query_row(
+        r#"SELECT COALESCE(SUM(-amount), 0) FROM transactions
+           WHERE account_id = ?1 AND amount < 0 AND date >= ?2 AND date <= ?3"#,
+        params![
+            account_id,
+            start.format("%Y-%m-%d").to_string(),
+            today.min(end).format("%Y-%m-%d").to_string()
+        ],
+        |row| row.get(0),
+    )?;
+
+    let projected_total = if elapsed_days == 0 {
+        actual_to_date
+    } else {
+        (actual_to_date as f64 * days_in_month as f64 / elapsed_days as f64).round() as i64
+    };
+
+    Ok(MonthSpendProjection {
+        month: month.to_string(),
+        actual_to_date,
+        projected_total,
+        percent_month_elapsed: elapsed_days as f64 / days_in_month as f64 * 100.0,
+    })
+}
+
+/// Spending so far in `year`, overall and per top-level category, extrapolated to the whole year
+pub fn annualize_spending(
+    conn: &Connection,
+    account_id: i64,
+    year: i32,
+) -> Result<AnnualSpendingProjection, rusqlite::Error> {
+    annualize_spending_at(conn, account_id, year, reports::current_date(conn)?)
+}
+
+/// Same as `annualize_spending`, relative to `today`. Elapsed time is counted in days, so the
+/// current month counts only as far as it has gone, with today as elapsed. Past years are not
+/// extrapolated, and future years project to zero.
+pub fn annualize_spending_at(
+    conn: &Connection,
+    account_id: i64,
+    year: i32,
+    today: NaiveDate,
+) -> Result<AnnualSpendingProjection, rusqlite::Error> {
+    let invalid = || rusqlite::Error::InvalidParameterName(format!("Invalid year: {}", year));
+    let start = NaiveDate::from_ymd_opt(year, 1, 1).ok_or_else(invalid)?;
+    let end = NaiveDate::from_ymd_opt(year, 12, 31).ok_or_else(invalid)?;
+    let days_in_year = end.ordinal();
+    let elapsed_days = if today > end {
+        days_in_year
+    } else if today < start {
+        0
+    } else {
+        today.ordinal()
+    };
+    let project = |actual: i64| {
+        if elapsed_days == 0 {
+            actual
+        } else {
+            (actual as f64 * days_in_year as f64 / elapsed_days as f64).round() as i64
+        }
+    };
+
+    let mut stmt = conn.prepare(
+        r#"SELECT
+            COALESCE(p.name, c.name, 'Uncategorized') as category,
+            SUM(-t.amount) as total
+           FROM transactions t
+           LEFT JOIN categories c ON t.category_id = c.id
+           LEFT JOIN categories p ON c.parent_id = p.id
+           WHERE t.account_id = ?1 AND t.date >= ?2 AND t.date <= ?3 AND t.amount < 0
+           GROUP BY category
+           ORDER BY total DESC"#,
+    )?;
+    let categories: Vec<CategoryAnnualProjection> = stmt
+        .query_map(
+            params![
+                account_id,
+                start.format("%Y-%m-%d").to_string(),
+                today.min(end).format("%Y-%m-%d").to_string()
+            ],
+            |row| {
+                let actual_to_date: i64 = row.get(1)?;
+                Ok(CategoryAnnualProjection {
+                    category_name: row.get(0)?,
+                    actual_to_date,
+                    projected_total: project(actual_to_date),
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let actual_to_date = categories.iter().map(|c| c.actual_to_date).sum();
+    Ok(AnnualSpendingProjection {
+        year,
+        actual_to_date,
+        projected_total: project(actual_to_date),
+        percent_year_elapsed: elapsed_days as f64 / days_in_year as f64 * 100.0,
+        categories,
+    })
+}
+
+/// Income, expense and net per quarter (Q1-Q4) of a year. Quarters without data are reported as zeros.
+pub fn quarterly_summary(
+    conn: &Connection,
+    account_id: i64,
+    year: i32,
+) -> Result<Vec<QuarterlySummary>, rusqlite::Error> {
+    let mut quarters: Vec<QuarterlySummary> = (1..=4)
+        .map(|quarter| QuarterlySummary { quarter, income: 0, expense: 0, net: 0 })
+        .collect();
+
+    let mut stmt = conn.prepare(
+        r#"SELECT
+            (CAST(strftime('%m', date) AS INTEGER) - 1) / 3 + 1 as quarter,
+            COALESCE(SUM(CASE WHEN amount > 0 THEN amount ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN amount < 0 THEN -amount ELSE 0 END), 0)
+           FROM transactions
+           WHERE account_id = ?1 AND strftime('%Y', date) = ?2
+           GROUP BY quarter"#,
+    )?;
+    let rows = stmt.query_map(params![account_id, format!("{:04}", year)], |row| {
+        Ok((row.get::<_, i64>(0)?, row.get::<_, i64>(1)?, row.get::<_, i64>(2)?))
+    })?;
+    for row in rows {
+        let (quarter, income, expense) = row?;
+        if let Some(q) = quarters.get_mut((quarter - 1) as usize) {
+            q.income = income;
+            q.expense = expense;
+            q.net = income - expense;
+        }
+    }
+    Ok(quarters)
+}
+
+fn query_savings_rate(
+    conn: &Connection,
+    account_id: Option<i64>,
+    start_date: &str,
+    end_date: &str,
+) -> Result<SavingsRate, rusqlite::Error> {
+    let (income, expense): (i64, i64) = conn.query_row(
+        r#"SELECT
+            COALESCE(SUM(CASE WHEN amount > 0 THEN amount ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN amount < 0 THEN -amount ELSE 0 END), 0)
+           FROM transactions
+           WHERE date >= ?1 AND date <= ?2 AND (?3 IS NULL OR account_id = ?3)"#,
+        params![start_date, end_date, account_id],
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+    Ok(SavingsRate {
+        income,
+        expense,
+        savings_rate: savings_rate_percent(income, expense),
+    })
+}
+
+/// (income - expense) / income as a percentage.
+/// Returns 0.0 when there is no income rather than NaN/infinity.
+pub fn savings_rate_percent(income: i64, expense: i64) -> f64 {
+    if income <= 0 {
+        return 0.0;
+    }
+    (income - expense) as f64 / income as f64 * 100.0
+}
+
+/// Count and total transactions in a date range by whether they are reconciled
+pub fn reconciliation_summary(
+    conn: &Connection,
+    account_id: i64,
+    start_date: &str,
+    end_date: &str,
+) -> Result<ReconciliationSummary, rusqlite::Error> {
+    let last_reconciled_date = super::reconciliation::last_reconciled_date(conn, account_id)?;
+    conn.query_row(
+        r#"SELECT
+            COALESCE(SUM(CASE WHEN is_reconciled != 0 THEN 1 ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN is_reconciled != 0 THEN amount ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN is_reconciled = 0 THEN 1 ELSE 0 END), 0),
+            COALESCE(SUM(CASE WHEN is_reconciled = 0 THEN amount ELSE 0 END), 0)
+           FROM transactions
+           WHERE account_id = ?1 AND date >= ?2 AND date <= ?3"#,
+        params![account_id, start_date, end_date],
+        |row| {
+            Ok(ReconciliationSummary {
+                reconciled_count: row.get(0)?,
+                reconciled_total: row.get(1)?,
+                unreconciled_count: row.get(2)?,
+                unreconciled_total: row.get(3)?,
+                last_reconciled_date,
+            })
+        },
+    )
+}
+
+/// Reconstruct an account's balance at the end of a given date.
+/// Uses the latest balance snapshot on or before the date plus any later movements;
+/// without snapshots the balance is the running sum of all amounts.
+pub fn balance_at(conn: &Connection, account_id: i64, date: &str) -> Result<i64, rusqlite::Error> {
+    let snapshot: Option<(String, i64, i64)> = conn
+        .query_row(
+            r#"SELECT date, id, balance_snapshot FROM transactions
+               WHERE account_id = ?1 AND date <= ?2 AND balance_snapshot IS NOT NULL
+               ORDER BY date DESC, id DESC LIMIT 1"#,
+            params![account_id, date],
+            |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+        )
+        .optional()?;
+
+    match snapshot {
+        Some((snap_date, snap_id, balance)) => {
+            let after: i64 = conn.query_row(
+                r#"SELECT COALESCE(SUM(amount), 0) FROM transactions
+                   WHERE account_id = ?1 AND date <= ?2
+                     AND (date > ?3 OR (date = ?3 AND id > ?4))"#,
+                params![account_id, date, snap_date, snap_id],
+                |row| row.get(0),
+            )?;
+            Ok(balance + after)
+        }
+        None => conn.query_row(
+            "SELECT COALESCE(SUM(amount), 0) FROM transactions WHERE account_id = ?1 AND date <= ?2",
+            params![account_id, date],
+            |row| row.get(0),
+        ),
+    }
+}
+
+/// Walk an account's transactions in date order and report every row whose balance snapshot
+/// isn't the previous balance plus its amount. Rows without a snapshot carry the running balance forward.
+pub fn verify_balance_continuity(
+    conn: &Connection,
+    account_id: i64,
+) -> Result<Vec<BalanceDiscrepancy>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT id, date, payee, amount, balance_snapshot FROM transactions
+           WHERE account_id = ?1
+           ORDER BY date ASC, id ASC"#,
+    )?;
+    let rows: Vec<(i64, String, String, i64, Option<i64>)> = stmt
+        .query_map(params![account_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut discrepancies = Vec::new();
+    let mut running: Option<i64> = None;
+    for (id, date, payee, amount, snapshot) in rows {
+        let expected = running.map(|balance| balance + amount);
+        if let (Some(expected_balance), Some(actual_balance)) = (expected, snapshot) {
+            if expected_balance != actual_balance {
+                discrepancies.push(BalanceDiscrepancy {
+                    transaction_id: id,
+                    date,
+                    payee,
+                    amount,
+                    expected_balance,
+                    actual_balance,
+                    difference: actual_balance - expected_balance,
+                });
+            }
+        }
+        // Re-anchor on each snapshot so one gap is reported once, not on every later row
+        running = snapshot.or(expected);
+    }
+    Ok(discrepancies)
+}
+
+/// Stretches longer than `max_gap_days` between consecutive transaction dates on an account,
+/// usually a statement that was never imported
+pub fn detect_gaps(
+    conn: &Connection,
+    account_id: i64,
+    max_gap_days: u32,
+) -> Result<Vec<TransactionGap>, rusqlite::Error> {
+    detect_gaps_at(conn, account_id, max_gap_days, reports::current_date(conn)?)
+}
+
+/// Same as `detect_gaps`, ignoring anything dated after `today`. The stretch from the last
+/// transaction up to today is never reported since those transactions may simply not exist yet.
+pub fn detect_gaps_at(
+    conn: &Connection,
+    account_id: i64,
+    max_gap_days: u32,
+    today: NaiveDate,
+) -> Result<Vec<TransactionGap>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT DISTINCT date FROM transactions
+           WHERE account_id = ?1 AND date <= ?2
+           ORDER BY date ASC"#,
+    )?;
+    let dates: Vec<NaiveDate> = stmt
+        .query_map(params![account_id, today.format("%Y-%m-%d").to_string()], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?
+        .iter()
+        .filter_map(|d| NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+        .collect();
+
+    Ok(dates
+        .windows(2)
+        .filter_map(|pair| {
+            let gap_days = (pair[1] - pair[0]).num_days() as u64;
+            (gap_days > max_gap_days as u64).then(|| TransactionGap {
+                start_date: pair[0].format("%Y-%m-%d").to_string(),
+                end_date: pair[1].format("%Y-%m-%d").to_string(),
+                gap_days,
+            })
+        })
+        .collect())
+}
+
+/// Pair up likely internal transfers in a date range: an outflow and an inflow of the same
+/// size on different accounts within a few days of each other. Returns (outflow_id, inflow_id).
+pub fn find_transfer_pairs(
+    conn: &Connection,
+    start_date: &str,
+    end_date: &str,
+) -> Result<Vec<(i64, i64)>, rusqlite::Error> {
+    const MAX_DAYS_APART: f64 = 3.0;
+
+    let mut stmt = conn.prepare(
+        r#"SELECT o.id, i.id
+           FROM transactions o
+           JOIN transactions i ON i.amount = -o.amount AND i.account_id != o.account_id
+           WHERE o.amount < 0 AND o.date >= ?1 AND o.date <= ?2
+             AND ABS(julianday(i.date) - julianday(o.date)) <= ?3
+           ORDER BY o.date, o.id, ABS(julianday(i.date) - julianday(o.date)), i.id"#,
+    )?;
+    let candidates: Vec<(i64, i64)> = stmt
+        .query_map(params![start_date, end_date, MAX_DAYS_APART], |row| {
+            Ok((row.get(0)?, row.get(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Greedily take the closest match so each transaction is used at most once
+    let mut used = std::collections::HashSet::new();
+    let mut pairs = Vec::new();
+    for (out_id, in_id) in candidates {
+        if used.contains(&out_id) || used.contains(&in_id) {
+            continue;
+        }
+        used.insert(out_id);
+        used.insert(in_id);
+        pairs.push((out_id, in_id));
+    }
+    Ok(pairs)
+}
+
 /// Check if a transaction with this import hash already exists
 pub fn exists_by_hash(conn: &Connection, hash: &str) -> Result<bool, rusqlite::Error> {
     let count: i64 = conn.query_row(
@@ -131,13 +758,77 @@ pub fn exists_by_hash(conn: &Connection, hash: &str) -> Result<bool, rusqlite::E
     Ok(count > 0)
 }
 
+/// (id, date, payee, raw_payee, amount, balance_snapshot, import_hash)
+type HashRow = (i64, String, String, Option<String>, i64, Option<i64>, Option<String>);
+
+/// Regenerate the import hash of every imported transaction in an account from its current date,
+/// raw payee, amount and balance, so re-importing a file still deduplicates after parsing changes.
+/// Hand-entered transactions (no hash) are left alone. A transaction whose new hash is already taken
+/// (by an older transaction, possibly in another account) is left without a hash, marked with
+/// `hash_collision` and for review, and reported as a collision; a later run retries it.
+/// All-or-nothing: runs inside a database transaction.
+pub fn recompute_hashes(conn: &Connection, account_id: i64) -> Result<RecomputeHashesResult, rusqlite::Error> {
+    let db_tx = conn.unchecked_transaction()?;
+
+    let mut stmt = db_tx.prepare(
+        r#"SELECT id, date, payee, raw_payee, amount, balance_snapshot, import_hash
+           FROM transactions
+           WHERE account_id = ?1 AND (import_hash IS NOT NULL OR hash_collision = 1)
+           ORDER BY id ASC"#,
+    )?;
+    let rows: Vec<HashRow> = stmt
+        .query_map(params![account_id], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?, row.get(5)?, row.get(6)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    drop(stmt);
+
+    // Clear first so hashes can move between this account's transactions without UNIQUE conflicts
+    db_tx.execute(
+        "UPDATE transactions SET import_hash = NULL WHERE account_id = ?1 AND import_hash IS NOT NULL",
+        params![account_id],
+    )?;
+
+    let mut result = RecomputeHashesResult {
+        updated: 0,
+        unchanged: 0,
+        collisions: Vec::new(),
+    };
+    for (id, date, payee, raw_payee, amount, balance, old_hash) in rows {
+        let hash = super::import::generate_import_hash(&date, raw_payee.as_deref().unwrap_or(&payee), amount, balance);
+
+        let holder: Option<i64> = db_tx
+            .query_row("SELECT id FROM transactions WHERE import_hash = ?1", params![hash], |row| row.get(0))
+            .optional()?;
+        if let Some(duplicate_of) = holder {
+            db_tx.execute("UPDATE transactions SET needs_review = 1, hash_collision = 1 WHERE id = ?1", params![id])?;
+            result.collisions.push(HashCollision { transaction_id: id, duplicate_of });
+            continue;
+        }
+
+        db_tx.execute(
+            "UPDATE transactions SET import_hash = ?1, hash_collision = 0 WHERE id = ?2",
+            params![hash, id],
+        )?;
+        if old_hash.as_deref() == Some(hash.as_str()) {
+            result.unchanged += 1;
+        } else {
+            result.updated += 1;
+        }
+    }
+
+    db_tx.commit()?;
+    Ok(result)
+}
+
 pub fn update_category(
     conn: &Connection,
     transaction_id: i64,
     category_id: Option<i64>,
 ) -> Result<usize, rusqlite::Error> {
+    audit::log_category_change(conn, transaction_id, category_id)?;
     conn.execute(
-        "UPDATE transactions SET category_id = ?1 WHERE id = ?2",
+        "UPDATE transactions SET category_id = ?1, needs_review = 0 WHERE id = ?2",
         params![category_id, transaction_id],
     )
 }
@@ -147,14 +838,85 @@ pub fn update_batch_categories(
     transaction_ids: Vec<i64>,
     category_id: Option<i64>,
 ) -> Result<usize, rusqlite::Error> {
-    let mut stmt = conn.prepare("UPDATE transactions SET category_id = ?1 WHERE id = ?2")?;
+    let mut stmt = conn.prepare("UPDATE transactions SET category_id = ?1, needs_review = 0 WHERE id = ?2")?;
     let mut count = 0;
     for id in transaction_ids {
+        audit::log_category_change(conn, id, category_id)?;
         count += stmt.execute(params![category_id, id])?;
     }
     Ok(count)
 }
 
+/// Clear the category and review flag of the given transactions in one database
+/// transaction. Returns the number updated.
+pub fn uncategorize(conn: &Connection, transaction_ids: &[i64]) -> Result<usize, rusqlite::Error> {
+    if transaction_ids.is_empty() {
+        return Ok(0);
+    }
+    let db_tx = conn.unchecked_transaction()?;
+    for &id in transaction_ids {
+        audit::log_category_change(&db_tx, id, None)?;
+    }
+    let placeholders = vec!["?"; transaction_ids.len()].join(",");
+    let updated = db_tx.execute(
+        &format!(
+            "UPDATE transactions SET category_id = NULL, needs_review = 0 WHERE id IN ({})",
+            placeholders
+        ),
+        rusqlite::params_from_iter(transaction_ids),
+    )?;
+    db_tx.commit()?;
+    Ok(updated)
+}
+
+/// Set the category on every transaction whose payee contains `payee_query` (case-insensitive).
+/// Limited to one account when `account_id` is given. Returns the number updated.
+pub fn recategorize_by_payee(
+    conn: &Connection,
+    payee_query: &str,
+    category_id: Option<i64>,
+    account_id: Option<i64>,
+) -> Result<usize, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT id FROM transactions
+           WHERE LOWER(payee) LIKE '%' || LOWER(?1) || '%'
+             AND (?2 IS NULL OR account_id = ?2)"#,
+    )?;
+    let ids: Vec<i64> = stmt
+        .query_map(params![payee_query.trim(), account_id], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    update_batch_categories(conn, ids, category_id)
+}
+
+/// Rename every transaction with exactly the payee `old_payee`, optionally on one account only.
+/// The raw payee from the bank is kept. Returns the number renamed.
+pub fn rename_payee_across_transactions(
+    conn: &Connection,
+    old_payee: &str,
+    new_payee: &str,
+    account_id: Option<i64>,
+) -> Result<usize, rusqlite::Error> {
+    let new_payee = new_payee.trim();
+    if new_payee.is_empty() {
+        return Err(rusqlite::Error::InvalidParameterName("Payee cannot be empty".to_string()));
+    }
+    if new_payee == old_payee {
+        return Ok(0);
+    }
+
+    let db_tx = conn.unchecked_transaction()?;
+    let ids: Vec<i64> = db_tx
+        .prepare("SELECT id FROM transactions WHERE payee = ?1 AND (?2 IS NULL OR account_id = ?2)")?
+        .query_map(params![old_payee, account_id], |row| row.get(0))?
+        .collect::<Result<_, _>>()?;
+    for &id in &ids {
+        audit::log_change(&db_tx, id, "payee", Some(old_payee), Some(new_payee))?;
+        db_tx.execute("UPDATE transactions SET payee = ?1 WHERE id = ?2", params![new_payee, id])?;
+    }
+    db_tx.commit()?;
+    Ok(ids.len())
+}
+
 pub fn delete(conn: &Connection, id: i64) -> Result<usize, rusqlite::Error> {
     conn.execute("DELETE FROM transactions WHERE id = ?1", params![id])
 }
@@ -163,6 +925,7 @@ pub fn delete_by_account(conn: &Connection, account_id: i64) -> Result<usize, ru
     conn.execute("DELETE FROM transactions WHERE account_id = ?1", params![account_id])
 }
 
+/// Maps a row selected with `SELECT_WITH_CATEGORY`
 fn map_transaction_with_category(
     row: &rusqlite::Row,
 ) -> Result<TransactionWithCategory, rusqlite::Error> {
@@ -178,8 +941,423 @@ fn map_transaction_with_category(
             status: row.get(7)?,
             is_reconciled: row.get::<_, i64>(8)? != 0,
             import_hash: row.get(9)?,
+            subscription_id: row.get(10)?,
+            raw_payee: row.get(11)?,
+            memo: row.get(12)?,
+            needs_review: row.get::<_, i64>(13)? != 0,
         },
-        category_name: row.get(10)?,
-        parent_category_name: row.get(11)?,
+        category_name: row.get(14)?,
+        parent_category_name: row.get(15)?,
+        matched_subscription_id: None,
+        matched_subscription_pattern: None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{categories, import, schema};
+    use crate::db::models::Category;
+
+    fn setup() -> Connection {
+        let conn = Connection::open_in_memory().unwrap();
+        schema::initialize(&conn).unwrap();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Lønkonto')", []).unwrap();
+        conn
+    }
+
+    fn insert(conn: &Connection, date: &str, payee: &str, amount: i64) -> i64 {
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount) VALUES (1, ?1, ?2, ?3)",
+            params![date, payee, amount],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn category(conn: &Connection, name: &str, parent_id: Option<i64>) -> i64 {
+        categories::create(
+            conn,
+            &Category {
+                id: None,
+                name: name.to_string(),
+                parent_id,
+                spending_type: "discretionary".to_string(),
+                is_hidden: false,
+            },
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn average_by_category_includes_subcategories() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let restaurants = category(&conn, "Restauranter", Some(food));
+        for (payee, amount, category_id) in [("Cafe", -20000, restaurants), ("Sushi", -48000, restaurants), ("Netto", -10000, food)] {
+            let id = insert(&conn, "2024-03-10", payee, amount);
+            update_category(&conn, id, Some(category_id)).unwrap();
+        }
+        let salary = insert(&conn, "2024-03-25", "Løn", 3000000);
+        update_category(&conn, salary, Some(food)).unwrap();
+
+        let averages = average_by_category(&conn, 1, "2024-03-01", "2024-03-31").unwrap();
+        assert_eq!(averages.len(), 2);
+        assert_eq!((averages[0].category_id, averages[0].average_amount, averages[0].transaction_count), (restaurants, 34000, 2));
+        assert_eq!((averages[1].category_id, averages[1].average_amount, averages[1].transaction_count), (food, 26000, 3));
+    }
+
+    #[test]
+    fn average_by_category_survives_parent_cycle() {
+        let conn = setup();
+        let a = category(&conn, "A", None);
+        let b = category(&conn, "B", Some(a));
+        conn.execute("UPDATE categories SET parent_id = ?1 WHERE id = ?2", params![b, a]).unwrap();
+        let id = insert(&conn, "2024-03-10", "Netto", -5000);
+        update_category(&conn, id, Some(b)).unwrap();
+
+        let averages = average_by_category(&conn, 1, "2024-03-01", "2024-03-31").unwrap();
+        assert_eq!(averages.len(), 2);
+        assert!(averages.iter().all(|a| a.average_amount == 5000 && a.transaction_count == 1));
+    }
+
+    fn insert_imported(conn: &Connection, date: &str, raw_payee: &str, amount: i64, import_hash: &str) -> i64 {
+        conn.execute(
+            r#"INSERT INTO transactions (account_id, date, payee, raw_payee, amount, import_hash)
+               VALUES (1, ?1, ?2, ?2, ?3, ?4)"#,
+            params![date, raw_payee, amount, import_hash],
+        )
+        .unwrap();
+        conn.last_insert_rowid()
+    }
+
+    fn stored_hash(conn: &Connection, id: i64) -> Option<String> {
+        conn.query_row("SELECT import_hash FROM transactions WHERE id = ?1", [id], |row| row.get(0)).unwrap()
+    }
+
+    #[test]
+    fn recompute_hashes_regenerates_and_reports_collisions() {
+        let conn = setup();
+        // Hashes from an older parser that also hashed the memo, so identical rows had distinct hashes
+        let first = insert_imported(&conn, "2024-03-01", "Netto", -4500, "old-1");
+        let second = insert_imported(&conn, "2024-03-01", "Netto", -4500, "old-2");
+        let other = insert_imported(&conn, "2024-03-02", "Irma", -1200, "old-3");
+        let manual = insert(&conn, "2024-03-03", "Kontant", -500);
+
+        let result = recompute_hashes(&conn, 1).unwrap();
+        assert_eq!((result.updated, result.unchanged), (2, 0));
+        assert_eq!(result.collisions.len(), 1);
+        assert_eq!((result.collisions[0].transaction_id, result.collisions[0].duplicate_of), (second, first));
+
+        let expected = import::generate_import_hash("2024-03-01", "Netto", -4500, None);
+        assert_eq!(stored_hash(&conn, first), Some(expected));
+        assert_eq!(stored_hash(&conn, second), None);
+        assert_eq!(stored_hash(&conn, manual), None);
+        let (collision, review): (bool, bool) = conn
+            .query_row("SELECT hash_collision, needs_review FROM transactions WHERE id = ?1", [second], |row| {
+                Ok((row.get(0)?, row.get(1)?))
+            })
+            .unwrap();
+        assert!(collision && review);
+
+        // Running again changes nothing and reports the same collision
+        let again = recompute_hashes(&conn, 1).unwrap();
+        assert_eq!((again.updated, again.unchanged, again.collisions.len()), (0, 2, 1));
+        assert!(stored_hash(&conn, other).is_some());
+    }
+
+    #[test]
+    fn get_manual_skips_imported_and_collision_rows() {
+        let conn = setup();
+        insert_imported(&conn, "2024-03-01", "Netto", -4500, "old-1");
+        insert_imported(&conn, "2024-03-01", "Netto", -4500, "old-2"); // Collides on recompute
+        let manual = insert(&conn, "2024-03-02", "Kontant", -500);
+        recompute_hashes(&conn, 1).unwrap();
+        // An imported row whose hash was dropped by hand still has its raw payee
+        let unhashed = insert_imported(&conn, "2024-03-04", "Irma", -900, "old-4");
+        conn.execute("UPDATE transactions SET import_hash = NULL WHERE id = ?1", [unhashed]).unwrap();
+
+        let ids: Vec<i64> = get_manual(&conn, 1).unwrap().iter().filter_map(|t| t.transaction.id).collect();
+        assert_eq!(ids, vec![manual]);
+    }
+
+    #[test]
+    fn reconciliation_summary_splits_by_state() {
+        let conn = setup();
+        let rent = insert(&conn, "2024-03-01", "Husleje", -850000);
+        let salary = insert(&conn, "2024-03-25", "Løn", 3000000);
+        insert(&conn, "2024-03-10", "Netto", -12500);
+        insert(&conn, "2024-03-12", "Irma", -4500);
+        insert(&conn, "2024-04-01", "Husleje", -850000);
+        conn.execute("UPDATE transactions SET is_reconciled = 1 WHERE id IN (?1, ?2)", params![rent, salary]).unwrap();
+
+        let summary = reconciliation_summary(&conn, 1, "2024-03-01", "2024-03-31").unwrap();
+        assert_eq!((summary.reconciled_count, summary.reconciled_total), (2, 2150000));
+        assert_eq!((summary.unreconciled_count, summary.unreconciled_total), (2, -17000));
+        assert_eq!(summary.last_reconciled_date, None);
+    }
+
+    #[test]
+    fn recategorize_by_payee_matches_the_same_merchant() {
+        let conn = setup();
+        let groceries = category(&conn, "Dagligvarer", None);
+        let first = insert(&conn, "2024-03-01", "NETTO 1234 KBH", -4500);
+        let second = insert(&conn, "2024-03-08", "Netto Aarhus", -12500);
+        let other = insert(&conn, "2024-03-09", "Irma", -3000);
+
+        assert_eq!(recategorize_by_payee(&conn, " netto ", Some(groceries), None).unwrap(), 2);
+        let category_of = |id: i64| get_by_id(&conn, id).unwrap().unwrap().category_id;
+        assert_eq!((category_of(first), category_of(second), category_of(other)), (Some(groceries), Some(groceries), None));
+        assert_eq!(recategorize_by_payee(&conn, "netto", Some(groceries), Some(2)).unwrap(), 0);
+    }
+
+    #[test]
+    fn broken_balance_chain_is_reported_once() {
+        let conn = setup();
+        let rows = [
+            ("2024-03-01", "Løn", 3000000, Some(3000000)),
+            ("2024-03-02", "Netto", -12500, Some(2987500)),
+            // A -50.000 øre row between these two was never imported
+            ("2024-03-05", "Irma", -4500, Some(2933000)),
+            ("2024-03-06", "Kiosk", -2000, None),
+            ("2024-03-07", "Bager", -3000, Some(2928000)),
+        ];
+        let mut ids = Vec::new();
+        for (date, payee, amount, balance) in rows {
+            let id = insert(&conn, date, payee, amount);
+            conn.execute("UPDATE transactions SET balance_snapshot = ?1 WHERE id = ?2", params![balance, id]).unwrap();
+            ids.push(id);
+        }
+
+        let discrepancies = verify_balance_continuity(&conn, 1).unwrap();
+        assert_eq!(discrepancies.len(), 1);
+        let gap = &discrepancies[0];
+        assert_eq!(gap.transaction_id, ids[2]);
+        assert_eq!((gap.expected_balance, gap.actual_balance, gap.difference), (2983000, 2933000, -50000));
+    }
+
+    #[test]
+    fn quarterly_summary_covers_the_whole_year() {
+        let conn = setup();
+        for (date, amount) in [
+            ("2023-12-31", -99900),
+            ("2024-01-01", 3000000),
+            ("2024-03-31", -50000),
+            ("2024-04-15", -25000),
+            ("2024-06-30", 1000000),
+            ("2024-10-01", -10000),
+            ("2024-12-31", 2000000),
+            ("2025-01-01", -77700),
+        ] {
+            insert(&conn, date, "Test", amount);
+        }
+
+        let quarters: Vec<(u32, i64, i64, i64)> = quarterly_summary(&conn, 1, 2024)
+            .unwrap()
+            .into_iter()
+            .map(|q| (q.quarter, q.income, q.expense, q.net))
+            .collect();
+        assert_eq!(
+            quarters,
+            vec![
+                (1, 3000000, 50000, 2950000),
+                (2, 1000000, 25000, 975000),
+                (3, 0, 0, 0),
+                (4, 2000000, 10000, 1990000),
+            ]
+        );
+    }
+
+    #[test]
+    fn get_with_category_by_id_expands_the_category() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let groceries = category(&conn, "Dagligvarer", Some(food));
+        let id = insert(&conn, "2024-03-01", "Netto", -4500);
+        update_category(&conn, id, Some(groceries)).unwrap();
+
+        let found = get_with_category_by_id(&conn, id).unwrap().unwrap();
+        assert_eq!(found.transaction.payee, "Netto");
+        assert_eq!(found.category_name.as_deref(), Some("Dagligvarer"));
+        assert_eq!(found.parent_category_name.as_deref(), Some("Mad"));
+        assert!(get_with_category_by_id(&conn, id + 1).unwrap().is_none());
+    }
+
+    #[test]
+    fn uncategorize_clears_only_the_category() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let ids: Vec<i64> = [("Netto", -4500), ("Irma", -3000), ("Føtex", -9900)]
+            .into_iter()
+            .map(|(payee, amount)| insert(&conn, "2024-03-01", payee, amount))
+            .collect();
+        update_batch_categories(&conn, ids.clone(), Some(food)).unwrap();
+        flag_for_review(&conn, ids[0]).unwrap();
+        let before = get_by_id(&conn, ids[0]).unwrap().unwrap();
+
+        assert_eq!(uncategorize(&conn, &ids[..2]).unwrap(), 2);
+        assert!(get_needing_review(&conn, 1).unwrap().is_empty());
+        let after = get_by_id(&conn, ids[0]).unwrap().unwrap();
+        assert_eq!(after.category_id, None);
+        assert_eq!((after.date, after.payee, after.amount), (before.date, before.payee, before.amount));
+        assert_eq!(get_by_id(&conn, ids[1]).unwrap().unwrap().category_id, None);
+        assert_eq!(get_by_id(&conn, ids[2]).unwrap().unwrap().category_id, Some(food));
+        assert_eq!(uncategorize(&conn, &[]).unwrap(), 0);
+    }
+
+    #[test]
+    fn month_spend_is_projected_from_the_fifteenth() {
+        let conn = setup();
+        for (date, amount) in [
+            ("2024-03-31", -99900),
+            ("2024-04-01", -100000),
+            ("2024-04-15", -200000),
+            ("2024-04-10", 2500000),
+            ("2024-04-20", -50000),
+        ] {
+            insert(&conn, date, "Test", amount);
+        }
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+
+        let mid = project_month_spend_at(&conn, 1, "2024-04", day("2024-04-15")).unwrap();
+        assert_eq!((mid.actual_to_date, mid.projected_total), (300000, 600000));
+        assert_eq!(mid.percent_month_elapsed, 50.0);
+
+        let first = project_month_spend_at(&conn, 1, "2024-04", day("2024-04-01")).unwrap();
+        assert_eq!((first.actual_to_date, first.projected_total), (100000, 3000000));
+
+        let past = project_month_spend_at(&conn, 1, "2024-04", day("2024-05-02")).unwrap();
+        assert_eq!((past.actual_to_date, past.projected_total), (350000, 350000));
+
+        let future = project_month_spend_at(&conn, 1, "2024-05", day("2024-04-15")).unwrap();
+        assert_eq!((future.actual_to_date, future.projected_total), (0, 0));
+    }
+
+    #[test]
+    fn all_by_date_range_spans_accounts() {
+        let conn = setup();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Budgetkonto')", []).unwrap();
+        insert(&conn, "2024-03-01", "Løn", 2500000);
+        insert(&conn, "2024-04-01", "Husleje", -800000);
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount) VALUES (2, '2024-03-10', 'Netto', -4500)",
+            [],
+        )
+        .unwrap();
+
+        let rows: Vec<(String, String, String)> = get_all_by_date_range(&conn, "2024-03-01", "2024-03-31")
+            .unwrap()
+            .into_iter()
+            .map(|r| (r.transaction.transaction.date, r.transaction.transaction.payee, r.account_name))
+            .collect();
+        assert_eq!(
+            rows,
+            [
+                ("2024-03-10".to_string(), "Netto".to_string(), "Budgetkonto".to_string()),
+                ("2024-03-01".to_string(), "Løn".to_string(), "Lønkonto".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn spending_is_annualized_by_elapsed_days() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let groceries = category(&conn, "Dagligvarer", Some(food));
+        for (date, amount, category_id) in [
+            ("2023-03-10", -100000, None),
+            ("2024-02-29", -60000, Some(groceries)),
+            ("2024-05-01", -40000, Some(food)),
+            ("2024-06-15", -50000, None),
+            ("2024-06-25", 2500000, None),
+            ("2024-07-02", -99900, None),
+        ] {
+            let id = insert(&conn, date, "Test", amount);
+            update_category(&conn, id, category_id).unwrap();
+        }
+        let day = |d: &str| NaiveDate::parse_from_str(d, "%Y-%m-%d").unwrap();
+        let by_category = |p: &AnnualSpendingProjection| {
+            p.categories
+                .iter()
+                .map(|c| (c.category_name.clone(), c.actual_to_date, c.projected_total))
+                .collect::<Vec<_>>()
+        };
+
+        // July 1st is day 183 of 2024's 366
+        let leap = annualize_spending_at(&conn, 1, 2024, day("2024-07-01")).unwrap();
+        assert_eq!((leap.actual_to_date, leap.projected_total, leap.percent_year_elapsed), (150000, 300000, 50.0));
+        assert_eq!(
+            by_category(&leap),
+            [("Mad".to_string(), 100000, 200000), ("Uncategorized".to_string(), 50000, 100000)]
+        );
+
+        // July 2nd is day 183 of 2023's 365
+        let common = annualize_spending_at(&conn, 1, 2023, day("2023-07-02")).unwrap();
+        assert_eq!((common.actual_to_date, common.projected_total), (100000, 199454));
+
+        let past = annualize_spending_at(&conn, 1, 2023, day("2024-07-01")).unwrap();
+        assert_eq!((past.actual_to_date, past.projected_total, past.percent_year_elapsed), (100000, 100000, 100.0));
+
+        let future = annualize_spending_at(&conn, 1, 2025, day("2024-07-01")).unwrap();
+        assert_eq!((future.actual_to_date, future.projected_total, future.percent_year_elapsed), (0, 0, 0.0));
+    }
+
+    #[test]
+    fn spending_by_category_multi_groups_by_month() {
+        let conn = setup();
+        let food = category(&conn, "Mad", None);
+        let groceries = category(&conn, "Dagligvarer", Some(food));
+        let transport = category(&conn, "Transport", None);
+        for (date, amount, category_id) in [
+            ("2024-01-05", -30000, Some(groceries)),
+            ("2024-01-20", -10000, Some(food)),
+            ("2024-01-22", -5000, Some(transport)),
+            ("2024-01-25", 2500000, None),
+            ("2024-03-01", -80000, None),
+            ("2024-03-31", -2000, Some(transport)),
+            ("2024-04-01", -99900, Some(food)),
+        ] {
+            let id = insert(&conn, date, "Test", amount);
+            update_category(&conn, id, category_id).unwrap();
+        }
+
+        let months = ["2024-01".to_string(), "2024-2".to_string(), "2024-03".to_string()];
+        let spending = spending_by_category_multi(&conn, 1, &months).unwrap();
+        assert_eq!(spending.len(), 3);
+        assert_eq!(spending["2024-01"], [("Mad".to_string(), -40000), ("Transport".to_string(), -5000)]);
+        assert!(spending["2024-02"].is_empty());
+        assert_eq!(spending["2024-03"], [("Uncategorized".to_string(), -80000), ("Transport".to_string(), -2000)]);
+        assert!(spending_by_category_multi(&conn, 1, &["2024-13".to_string()]).is_err());
+    }
+
+    #[test]
+    fn savings_rate_of_a_month_without_income_is_zero() {
+        let conn = setup();
+        insert(&conn, "2024-02-10", "Netto", -45000);
+
+        let rate = savings_rate(&conn, 1, "2024-02-01", "2024-02-29").unwrap();
+        assert_eq!((rate.income, rate.expense, rate.savings_rate), (0, 45000, 0.0));
+        let empty = savings_rate(&conn, 1, "2024-05-01", "2024-05-31").unwrap();
+        assert_eq!((empty.income, empty.expense, empty.savings_rate), (0, 0, 0.0));
+        assert_eq!(savings_rate_percent(-100, 50), 0.0);
+    }
+
+    #[test]
+    fn savings_rate_per_account_and_across_accounts() {
+        let conn = setup();
+        conn.execute("INSERT INTO accounts (name) VALUES ('Budgetkonto')", []).unwrap();
+        insert(&conn, "2024-03-01", "Løn", 2000000);
+        insert(&conn, "2024-03-05", "Husleje", -1500000);
+        insert(&conn, "2024-04-01", "Løn", 2000000);
+        conn.execute(
+            "INSERT INTO transactions (account_id, date, payee, amount) VALUES (2, '2024-03-10', 'Netto', -300000)",
+            [],
+        )
+        .unwrap();
+
+        let rate = savings_rate(&conn, 1, "2024-03-01", "2024-03-31").unwrap();
+        assert_eq!((rate.income, rate.expense, rate.savings_rate), (2000000, 1500000, 25.0));
+        let all = savings_rate_all_accounts(&conn, "2024-03-01", "2024-03-31").unwrap();
+        assert_eq!((all.income, all.expense, all.savings_rate), (2000000, 1800000, 10.0));
+    }
+}
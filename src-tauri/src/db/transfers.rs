@@ -0,0 +1,45 @@
+use rusqlite::{params, Connection};
+use super::models::Transfer;
+use super::transactions;
+
+/// Record two transactions as the legs of a transfer. The debit must be an outflow and the credit an inflow;
+/// whether they actually match up is checked by the transfer reconciliation report.
+pub fn create(conn: &Connection, transfer: &Transfer) -> Result<i64, rusqlite::Error> {
+    let debit = transactions::get_by_id(conn, transfer.debit_transaction_id)?;
+    let credit = transactions::get_by_id(conn, transfer.credit_transaction_id)?;
+    let (Some(debit), Some(credit)) = (debit, credit) else {
+        return Err(rusqlite::Error::InvalidParameterName("Transaction not found".to_string()));
+    };
+    if debit.amount >= 0 || credit.amount <= 0 {
+        return Err(rusqlite::Error::InvalidParameterName(
+            "Debit must be an outflow and credit an inflow".to_string(),
+        ));
+    }
+
+    conn.execute(
+        "INSERT INTO transfers (debit_transaction_id, credit_transaction_id) VALUES (?1, ?2)",
+        params![transfer.debit_transaction_id, transfer.credit_transaction_id],
+    )?;
+    Ok(conn.last_insert_rowid())
+}
+
+pub fn get_all(conn: &Connection) -> Result<Vec<Transfer>, rusqlite::Error> {
+    let mut stmt = conn.prepare(
+        r#"SELECT tr.id, tr.debit_transaction_id, tr.credit_transaction_id
+           FROM transfers tr
+           JOIN transactions d ON tr.debit_transaction_id = d.id
+           ORDER BY d.date DESC, tr.id DESC"#,
+    )?;
+    let rows = stmt.query_map([], |row| {
+        Ok(Transfer {
+            id: Some(row.get(0)?),
+            debit_transaction_id: row.get(1)?,
+            credit_transaction_id: row.get(2)?,
+        })
+    })?;
+    rows.collect()
+}
+
+pub fn delete(conn: &Connection, id: i64) -> Result<usize, rusqlite::Error> {
+    conn.execute("DELETE FROM transfers WHERE id = ?1", params![id])
+}
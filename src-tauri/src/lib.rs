@@ -1,7 +1,8 @@
 mod db;
 
-use db::models::{Account, Category, ImportResult, TransactionWithCategory, Subscription, Budget, BudgetAllocation, BudgetWithSpending, IncomeStream};
-use db::{accounts, categories, import, transactions, subscriptions, subscription_engine, budgets, income_streams, Database};
+use db::models::{Account, AccountStatement, AccountTypeSpending, AmountBucket, AnnualSpendingProjection, ApplyRulesResult, AttentionItems, AuditLogEntry, BalanceDiscrepancy, BenchmarkReport, BillingComparison, CalendarEvent, CashRunway, Category, CategoryAverage, CategoryConsistency, CategoryMoMChange, CategoryStreak, CategoryWithDepth, CategorizationRule, CommittedSplit, CreditUtilization, CsvValidationResult, DetectedSubscription, DuplicateCategoryGroup, EIRatioPoint, HierarchicalSpending, HomeSummary, ImportGap, ImportPreview, IncomeGrowth, ImportProfile, ImportResult, MigrationResult, MonthCoverage, MonthlySummary, MonthSpendProjection, OverlapReport, PayeeCategoryInconsistency, PayeeRule, RecomputeHashesResult, ReconciliationSession, ReconciliationSummary, SavingsGoal, SavingsPlan, SavingsRate, TransactionGap, TransactionWithAccount, TransactionWithCategory, UpcomingIncome, Subscription, Budget, BudgetAllocation, BudgetEfficiency, BudgetFundingStatus, BudgetWithSpending, IncomeStream, IncomeStreamHistoryEntry, IncomeStreamWithLastReceived, MerchantBreakdown, MerchantTenure, NetWorthChangeAttribution, PaycheckPattern, PayeeFirstSeen, PayeeTrend, QuarterlySummary, SavingsRateProjection, SeasonalPattern, SpendingCadence, SpendingForecast, SpendingPercentile, SpendingTypeBreakdown, SpendingVelocity, StressTestResult, SubscriptionEngagement, Transfer, TransferReconciliation, WeekendAnomaly};
+use db::{accounts, categories, import, export, transactions, subscriptions, subscription_engine, income_stream_engine, budgets, income_streams, reports, payee_rules, categorization_rules, settings, transfers, summary, savings_goals, reconciliation, audit, Database};
+use std::collections::HashMap;
 use tauri::{Manager, State};
 
 // === Account Commands ===
@@ -36,6 +37,18 @@ fn delete_account(db: State<Database>, id: i64) -> Result<usize, String> {
     accounts::delete(&conn, id).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+fn get_credit_card_utilization(db: State<Database>, account_id: i64) -> Result<CreditUtilization, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    accounts::get_credit_utilization(&conn, account_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_all_credit_utilizations(db: State<Database>) -> Result<Vec<CreditUtilization>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    accounts::get_all_credit_utilizations(&conn).map_err(|e| e.to_string())
+}
+
 // === Category Commands ===
 
 #[tauri::command]
@@ -45,39 +58,157 @@ fn create_category(db: State<Database>, category: Category) -> Result<i64, Strin
 }
 
 #[tauri::command]
-fn get_categories(db: State<Database>) -> Result<Vec<Category>, String> {
+fn get_categories(db: State<Database>, include_hidden: Option<bool>) -> Result<Vec<Category>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categories::get_all(&conn, include_hidden.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_top_level_categories(db: State<Database>, include_hidden: Option<bool>) -> Result<Vec<Category>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categories::get_top_level(&conn, include_hidden.unwrap_or(false)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_subcategories(
+    db: State<Database>,
+    parent_id: i64,
+    include_hidden: Option<bool>,
+) -> Result<Vec<Category>, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    categories::get_all(&conn).map_err(|e| e.to_string())
+    categories::get_children(&conn, parent_id, include_hidden.unwrap_or(false)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_top_level_categories(db: State<Database>) -> Result<Vec<Category>, String> {
+fn set_category_hidden(db: State<Database>, id: i64, hidden: bool) -> Result<usize, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    categories::get_top_level(&conn).map_err(|e| e.to_string())
+    categories::set_hidden(&conn, id, hidden).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_subcategories(db: State<Database>, parent_id: i64) -> Result<Vec<Category>, String> {
+fn update_category(db: State<Database>, category: Category) -> Result<usize, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    categories::get_children(&conn, parent_id).map_err(|e| e.to_string())
+    categories::update(&conn, &category).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn delete_category(db: State<Database>, id: i64) -> Result<usize, String> {
+fn delete_category(db: State<Database>, id: i64) -> Result<String, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
     categories::delete(&conn, id).map_err(|e| e.to_string())
 }
 
+/// Undo a category deletion using the snapshot returned by `delete_category`
+#[tauri::command]
+fn restore_category(db: State<Database>, snapshot: String) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categories::restore(&conn, &snapshot).map_err(|e| e.to_string())
+}
+
+/// Categories with the same name under the same parent, as candidates for merging
+#[tauri::command]
+fn get_duplicate_categories(db: State<Database>) -> Result<Vec<DuplicateCategoryGroup>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categories::find_duplicates(&conn).map_err(|e| e.to_string())
+}
+
+/// All categories in tree order with their depth, for rendering the hierarchy
+#[tauri::command]
+fn get_category_depth_report(db: State<Database>) -> Result<Vec<CategoryWithDepth>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categories::get_hierarchy_report(&conn).map_err(|e| e.to_string())
+}
+
+/// Export the category hierarchy as CSV for backup or sharing
+#[tauri::command]
+fn export_categories_csv(db: State<Database>) -> Result<String, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    export::export_categories_csv(&conn)
+}
+
+/// Export an account's transactions as QIF; both dates are optional and inclusive
+#[tauri::command]
+fn export_qif(
+    db: State<Database>,
+    account_id: i64,
+    start_date: Option<String>,
+    end_date: Option<String>,
+) -> Result<String, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    export::export_qif(&conn, account_id, start_date, end_date)
+}
+
+/// Rebuild categories from an exported CSV, matching each category on its parent path
+#[tauri::command]
+fn import_categories_csv(db: State<Database>, csv_content: String) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    export::import_categories_csv(&conn, &csv_content)
+}
+
+// === Categorization Rule Commands ===
+
+#[tauri::command]
+fn create_categorization_rule(db: State<Database>, rule: CategorizationRule) -> Result<i64, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categorization_rules::create(&conn, &rule).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_categorization_rules(db: State<Database>) -> Result<Vec<CategorizationRule>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categorization_rules::get_all(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_categorization_rule(db: State<Database>, rule: CategorizationRule) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categorization_rules::update(&conn, &rule).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_categorization_rule(db: State<Database>, id: i64) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categorization_rules::delete(&conn, id).map_err(|e| e.to_string())
+}
+
+/// One-time conversion of imported payee → category assignments into rules
+#[tauri::command]
+fn migrate_category_patterns_to_rules(db: State<Database>) -> Result<MigrationResult, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categorization_rules::migrate_from_category_history(&conn).map_err(|e| e.to_string())
+}
+
+/// Apply the rules retroactively; `overwrite_existing` also re-categorizes already categorized transactions
+#[tauri::command]
+fn apply_all_categorization_rules(
+    db: State<Database>,
+    account_id: Option<i64>,
+    overwrite_existing: bool,
+) -> Result<ApplyRulesResult, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    categorization_rules::apply_to_account(&conn, account_id, overwrite_existing).map_err(|e| e.to_string())
+}
+
 // === Transaction Commands ===
 
+#[tauri::command]
+fn get_transaction(db: State<Database>, id: i64) -> Result<Option<TransactionWithCategory>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::get_with_category_by_id(&conn, id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn get_transactions(
     db: State<Database>,
     account_id: i64,
     limit: Option<i64>,
+    tag_subscriptions: Option<bool>,
 ) -> Result<Vec<TransactionWithCategory>, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    transactions::get_by_account(&conn, account_id, limit).map_err(|e| e.to_string())
+    let mut rows = transactions::get_by_account(&conn, account_id, limit).map_err(|e| e.to_string())?;
+    if tag_subscriptions.unwrap_or(false) {
+        subscription_engine::tag_subscriptions(&conn, &mut rows).map_err(|e| e.to_string())?;
+    }
+    Ok(rows)
 }
 
 #[tauri::command]
@@ -92,6 +223,38 @@ fn get_transactions_by_date_range(
         .map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+fn get_all_transactions_by_date_range(
+    db: State<Database>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<TransactionWithAccount>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::get_all_by_date_range(&conn, &start_date, &end_date).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn search_transactions(
+    db: State<Database>,
+    account_id: i64,
+    query: String,
+    include_memo: Option<bool>,
+) -> Result<Vec<TransactionWithCategory>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::search(&conn, account_id, &query, include_memo.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// Transactions that were entered by hand rather than imported
+#[tauri::command]
+fn get_manual_transactions(
+    db: State<Database>,
+    account_id: i64,
+) -> Result<Vec<TransactionWithCategory>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::get_manual(&conn, account_id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn get_spending_by_category(
     db: State<Database>,
@@ -104,6 +267,139 @@ fn get_spending_by_category(
         .map_err(|e| e.to_string())
 }
 
+/// `get_spending_by_category` for several months (YYYY-MM) at once, keyed by month
+#[tauri::command]
+fn get_spending_by_category_multi(
+    db: State<Database>,
+    account_id: i64,
+    months: Vec<String>,
+) -> Result<HashMap<String, Vec<(String, i64)>>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::spending_by_category_multi(&conn, account_id, &months).map_err(|e| e.to_string())
+}
+
+/// Spending per top-level category with a breakdown of its subcategories
+#[tauri::command]
+fn get_spending_by_category_with_subcategory_drill(
+    db: State<Database>,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<HierarchicalSpending>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_hierarchical_spending(&conn, account_id, start_date, end_date).map_err(|e| e.to_string())
+}
+
+/// Average expense per category for a period, e.g. the typical restaurant bill
+#[tauri::command]
+fn get_average_by_category(
+    db: State<Database>,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<CategoryAverage>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::average_by_category(&conn, account_id, &start_date, &end_date).map_err(|e| e.to_string())
+}
+
+/// Savings rate for a period; omit `account_id` to cover all accounts
+#[tauri::command]
+fn get_savings_rate(
+    db: State<Database>,
+    account_id: Option<i64>,
+    start_date: String,
+    end_date: String,
+) -> Result<SavingsRate, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    match account_id {
+        Some(id) => transactions::savings_rate(&conn, id, &start_date, &end_date),
+        None => transactions::savings_rate_all_accounts(&conn, &start_date, &end_date),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Payees by the date they first appeared; `since_date` limits to recently discovered ones
+#[tauri::command]
+fn get_payee_first_seen(
+    db: State<Database>,
+    account_id: Option<i64>,
+    since_date: Option<String>,
+) -> Result<Vec<PayeeFirstSeen>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::get_payee_first_seen(&conn, account_id, since_date).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_quarterly_summary(db: State<Database>, account_id: i64, year: i32) -> Result<Vec<QuarterlySummary>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::quarterly_summary(&conn, account_id, year).map_err(|e| e.to_string())
+}
+
+/// Spending so far in a month plus the projected month-end total
+#[tauri::command]
+fn project_month_spend(db: State<Database>, account_id: i64, month: String) -> Result<MonthSpendProjection, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::project_month_spend(&conn, account_id, &month).map_err(|e| e.to_string())
+}
+
+/// Spending so far this year, overall and per category, projected to a full-year total
+#[tauri::command]
+fn annualize_spending(db: State<Database>, account_id: i64, year: i32) -> Result<AnnualSpendingProjection, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::annualize_spending(&conn, account_id, year).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_reconciliation_summary(
+    db: State<Database>,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<ReconciliationSummary, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::reconciliation_summary(&conn, account_id, &start_date, &end_date)
+        .map_err(|e| e.to_string())
+}
+
+/// Mark transactions as reconciled against a statement and record the session
+#[tauri::command]
+fn create_reconciliation_session(
+    db: State<Database>,
+    account_id: i64,
+    ending_balance: i64,
+    mark_transactions: Vec<i64>,
+) -> Result<ReconciliationSession, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reconciliation::create_session(&conn, account_id, ending_balance, &mark_transactions).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_account_reconciliation_history(
+    db: State<Database>,
+    account_id: i64,
+) -> Result<Vec<ReconciliationSession>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reconciliation::get_history(&conn, account_id).map_err(|e| e.to_string())
+}
+
+/// Report rows where the imported running balance doesn't reconcile (missing or duplicate imports)
+#[tauri::command]
+fn verify_balance_continuity(db: State<Database>, account_id: i64) -> Result<Vec<BalanceDiscrepancy>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::verify_balance_continuity(&conn, account_id).map_err(|e| e.to_string())
+}
+
+/// Periods without transactions that may be missing imports; gaps over `max_gap_days` (default 15)
+#[tauri::command]
+fn get_transaction_gap_detection(
+    db: State<Database>,
+    account_id: i64,
+    max_gap_days: Option<u32>,
+) -> Result<Vec<TransactionGap>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::detect_gaps(&conn, account_id, max_gap_days.unwrap_or(15)).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn update_transaction_category(
     db: State<Database>,
@@ -124,6 +420,62 @@ fn update_batch_categories(
     transactions::update_batch_categories(&conn, transaction_ids, category_id).map_err(|e| e.to_string())
 }
 
+/// Transactions auto-categorized by a low-confidence rule, for the user to confirm
+#[tauri::command]
+fn get_transactions_needing_review(
+    db: State<Database>,
+    account_id: i64,
+) -> Result<Vec<TransactionWithCategory>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::get_needing_review(&conn, account_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn confirm_categorization(db: State<Database>, transaction_id: i64) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::confirm_categorization(&conn, transaction_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn uncategorize_transactions(db: State<Database>, transaction_ids: Vec<i64>) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::uncategorize(&conn, &transaction_ids).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn recategorize_by_payee(
+    db: State<Database>,
+    payee_query: String,
+    category_id: Option<i64>,
+    account_id: Option<i64>,
+) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::recategorize_by_payee(&conn, &payee_query, category_id, account_id)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn rename_payee_across_transactions(
+    db: State<Database>,
+    old_payee: String,
+    new_payee: String,
+    account_id: Option<i64>,
+) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::rename_payee_across_transactions(&conn, &old_payee, &new_payee, account_id)
+        .map_err(|e| e.to_string())
+}
+
+/// Category and payee edits to one transaction, or the most recent edits overall
+#[tauri::command]
+fn get_transaction_correction_log(
+    db: State<Database>,
+    transaction_id: Option<i64>,
+) -> Result<Vec<AuditLogEntry>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    audit::get_log(&conn, transaction_id).map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 fn delete_transaction(db: State<Database>, id: i64) -> Result<usize, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
@@ -161,84 +513,327 @@ fn import_csv_bytes(
     import::import_csv_bytes(&conn, &bytes, account_id, &filename)
 }
 
-// === Subscription Commands ===
-
 #[tauri::command]
-fn detect_subscriptions(db: State<Database>, account_id: i64) -> Result<Vec<Subscription>, String> {
+fn import_apple_card_csv(
+    db: State<Database>,
+    csv_content: String,
+    account_id: i64,
+    filename: String,
+) -> Result<ImportResult, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    subscription_engine::detect_subscriptions(&conn, account_id).map_err(|e| e.to_string())
+    import::import_apple_card_csv(&conn, &csv_content, account_id, &filename)
 }
 
+/// Months with suspiciously few transactions, likely statements that were never imported
 #[tauri::command]
-fn get_subscriptions(db: State<Database>, account_id: i64) -> Result<Vec<Subscription>, String> {
+fn detect_import_gaps(db: State<Database>, account_id: i64) -> Result<Vec<ImportGap>, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    subscriptions::get_by_account(&conn, account_id).map_err(|e| e.to_string())
+    import::detect_gaps(&conn, account_id)
 }
 
+/// Import wizard step 1: detect delimiter and column mapping
 #[tauri::command]
-fn save_subscription(db: State<Database>, subscription: Subscription) -> Result<i64, String> {
+fn validate_csv_file(
+    db: State<Database>,
+    csv_content: String,
+    account_id: i64,
+    profile: Option<ImportProfile>,
+) -> Result<CsvValidationResult, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    subscriptions::create(&conn, &subscription).map_err(|e| e.to_string())
+    Ok(import::validate_csv(&conn, &csv_content, account_id, &profile.unwrap_or_default()))
 }
 
+/// Import wizard step 2: show what would be imported
 #[tauri::command]
-fn dismiss_subscription(db: State<Database>, id: i64) -> Result<usize, String> {
+fn preview_csv_import(
+    db: State<Database>,
+    csv_content: String,
+    account_id: i64,
+    profile: Option<ImportProfile>,
+) -> Result<ImportPreview, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    subscriptions::dismiss(&conn, id).map_err(|e| e.to_string())
+    import::preview_csv_import(&conn, &csv_content, account_id, &profile.unwrap_or_default())
 }
 
-// === Budget Commands ===
-
+/// Import wizard step 3: import using the confirmed column mapping and profile
 #[tauri::command]
-fn create_budget(db: State<Database>, budget: Budget) -> Result<i64, String> {
+fn commit_csv_import(
+    db: State<Database>,
+    csv_content: String,
+    account_id: i64,
+    filename: String,
+    profile: Option<ImportProfile>,
+) -> Result<ImportResult, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    budgets::create(&conn, &budget).map_err(|e| e.to_string())
+    import::commit_csv_import(&conn, &csv_content, account_id, &filename, &profile.unwrap_or_default())
 }
 
+/// Regenerate import hashes after payee or parsing changes so re-imports still deduplicate
 #[tauri::command]
-fn get_budgets(db: State<Database>) -> Result<Vec<Budget>, String> {
+fn recompute_import_hashes(db: State<Database>, account_id: i64) -> Result<RecomputeHashesResult, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    budgets::get_all(&conn).map_err(|e| e.to_string())
+    transactions::recompute_hashes(&conn, account_id).map_err(|e| e.to_string())
 }
 
-#[tauri::command]
-fn update_budget(db: State<Database>, budget: Budget) -> Result<usize, String> {
-    let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    budgets::update(&conn, &budget).map_err(|e| e.to_string())
-}
+// === Payee Rule Commands ===
 
 #[tauri::command]
-fn delete_budget(db: State<Database>, id: i64) -> Result<usize, String> {
+fn create_payee_rule(db: State<Database>, rule: PayeeRule) -> Result<i64, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    budgets::delete(&conn, id).map_err(|e| e.to_string())
+    payee_rules::create(&conn, &rule).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn set_budget_categories(db: State<Database>, budget_id: i64, category_ids: Vec<i64>) -> Result<(), String> {
+fn get_payee_rules(db: State<Database>) -> Result<Vec<PayeeRule>, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    budgets::remove_all_categories(&conn, budget_id).map_err(|e| e.to_string())?;
-    for cat_id in category_ids {
-        budgets::add_category(&conn, budget_id, cat_id).map_err(|e| e.to_string())?;
-    }
-    Ok(())
+    payee_rules::get_all(&conn).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn get_budget_categories(db: State<Database>, budget_id: i64) -> Result<Vec<i64>, String> {
+fn update_payee_rule(db: State<Database>, rule: PayeeRule) -> Result<usize, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    budgets::get_categories(&conn, budget_id).map_err(|e| e.to_string())
+    payee_rules::update(&conn, &rule).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
-fn set_budget_allocation(db: State<Database>, budget_id: i64, month: String, amount: i64) -> Result<(), String> {
+fn delete_payee_rule(db: State<Database>, id: i64) -> Result<usize, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    budgets::set_allocation(&conn, budget_id, &month, amount).map_err(|e| e.to_string())
+    payee_rules::delete(&conn, id).map_err(|e| e.to_string())
 }
 
+/// Re-apply payee rules to all existing transactions
 #[tauri::command]
-fn get_budgets_with_spending(db: State<Database>, month: String) -> Result<Vec<BudgetWithSpending>, String> {
+fn clean_existing_payees(db: State<Database>) -> Result<usize, String> {
     let conn = db.conn.lock().map_err(|e| e.to_string())?;
-    budgets::get_budgets_with_spending(&conn, &month).map_err(|e| e.to_string())
+    payee_rules::clean_existing_payees(&conn).map_err(|e| e.to_string())
+}
+
+// === Subscription Commands ===
+
+#[tauri::command]
+fn detect_subscriptions(
+    db: State<Database>,
+    account_id: i64,
+    force_full: Option<bool>,
+) -> Result<Vec<Subscription>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    subscription_engine::detect_subscriptions(&conn, account_id, force_full.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+/// Called once the detected candidates have been saved or ignored
+#[tauri::command]
+fn mark_subscription_detection_reviewed(db: State<Database>, account_id: i64) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    subscription_engine::mark_detection_reviewed(&conn, account_id).map_err(|e| e.to_string())
+}
+
+/// Like `detect_subscriptions`, but with each candidate's transactions included for review
+#[tauri::command]
+fn detect_subscriptions_detailed(db: State<Database>, account_id: i64) -> Result<Vec<DetectedSubscription>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    subscription_engine::detect_subscriptions_detailed(&conn, account_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_subscriptions(db: State<Database>, account_id: i64) -> Result<Vec<Subscription>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    subscriptions::get_by_account(&conn, account_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn save_subscription(db: State<Database>, subscription: Subscription) -> Result<i64, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    subscriptions::create(&conn, &subscription).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn dismiss_subscription(db: State<Database>, id: i64) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    subscriptions::dismiss(&conn, id).map_err(|e| e.to_string())
+}
+
+// === Transfer Commands ===
+
+#[tauri::command]
+fn create_transfer(db: State<Database>, transfer: Transfer) -> Result<i64, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transfers::create(&conn, &transfer).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_transfers(db: State<Database>) -> Result<Vec<Transfer>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transfers::get_all(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_transfer(db: State<Database>, id: i64) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transfers::delete(&conn, id).map_err(|e| e.to_string())
+}
+
+// === Savings Goal Commands ===
+
+#[tauri::command]
+fn create_savings_goal(db: State<Database>, goal: SavingsGoal) -> Result<i64, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    savings_goals::create(&conn, &goal).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_savings_goals(db: State<Database>) -> Result<Vec<SavingsGoal>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    savings_goals::get_all(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_savings_goal(db: State<Database>, goal: SavingsGoal) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    savings_goals::update(&conn, &goal).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_savings_goal(db: State<Database>, id: i64) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    savings_goals::delete(&conn, id).map_err(|e| e.to_string())
+}
+
+// === Budget Commands ===
+
+#[tauri::command]
+fn create_budget(db: State<Database>, budget: Budget) -> Result<i64, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::create(&conn, &budget).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_budgets(db: State<Database>) -> Result<Vec<Budget>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::get_all(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn update_budget(db: State<Database>, budget: Budget) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::update(&conn, &budget).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn delete_budget(db: State<Database>, id: i64) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::delete(&conn, id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_budget_categories(db: State<Database>, budget_id: i64, category_ids: Vec<i64>) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::remove_all_categories(&conn, budget_id).map_err(|e| e.to_string())?;
+    for cat_id in category_ids {
+        budgets::add_category(&conn, budget_id, cat_id).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_budget_categories(db: State<Database>, budget_id: i64) -> Result<Vec<i64>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::get_categories(&conn, budget_id).map_err(|e| e.to_string())
+}
+
+/// Limit a budget's spending to these accounts; an empty list means all accounts
+#[tauri::command]
+fn set_budget_accounts(db: State<Database>, budget_id: i64, account_ids: Vec<i64>) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::remove_all_accounts(&conn, budget_id).map_err(|e| e.to_string())?;
+    for account_id in account_ids {
+        budgets::add_account(&conn, budget_id, account_id).map_err(|e| e.to_string())?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_budget_accounts(db: State<Database>, budget_id: i64) -> Result<Vec<i64>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::get_accounts(&conn, budget_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_budget_category_overlap(db: State<Database>) -> Result<Vec<OverlapReport>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::get_category_overlap(&conn).map_err(|e| e.to_string())
+}
+
+/// Keep the category in one budget and unlink it from all others
+#[tauri::command]
+fn resolve_category_overlap(
+    db: State<Database>,
+    category_id: i64,
+    keep_budget_id: i64,
+) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::resolve_category_overlap(&conn, category_id, keep_budget_id).map_err(|e| e.to_string())
+}
+
+/// Recategorize transactions into a category that belongs to the budget
+#[tauri::command]
+fn assign_transactions_to_budget(
+    db: State<Database>,
+    budget_id: i64,
+    transaction_ids: Vec<i64>,
+    category_id: i64,
+) -> Result<usize, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::assign_transactions(&conn, budget_id, transaction_ids, category_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_budget_allocation(db: State<Database>, budget_id: i64, month: String, amount: i64) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::set_allocation(&conn, budget_id, &month, amount).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn adjust_budget_allocation(
+    db: State<Database>,
+    budget_id: i64,
+    month: String,
+    delta: i64,
+    allow_negative: Option<bool>,
+) -> Result<i64, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::adjust_allocation(&conn, budget_id, &month, delta, allow_negative.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn move_between_budgets(
+    db: State<Database>,
+    from_budget_id: i64,
+    to_budget_id: i64,
+    month: String,
+    amount: i64,
+    allow_negative: Option<bool>,
+) -> Result<i64, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::move_between_budgets(&conn, from_budget_id, to_budget_id, &month, amount, allow_negative.unwrap_or(false))
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_budgets_with_spending(
+    db: State<Database>,
+    month: String,
+    prorate_from_day: Option<u32>,
+) -> Result<Vec<BudgetWithSpending>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::get_budgets_with_spending(&conn, &month, prorate_from_day).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_budget_funding_status(db: State<Database>, month: String) -> Result<BudgetFundingStatus, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    budgets::funding_status(&conn, &month).map_err(|e| e.to_string())
 }
 
 // === Income Stream Commands ===
@@ -267,6 +862,412 @@ fn delete_income_stream(db: State<Database>, id: i64) -> Result<usize, String> {
     income_streams::delete(&conn, id).map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+fn get_income_stream_last_received(db: State<Database>) -> Result<Vec<IncomeStreamWithLastReceived>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    income_streams::get_all_with_last_received(&conn).map_err(|e| e.to_string())
+}
+
+/// Month-by-month check of whether an income stream was received during `year`
+#[tauri::command]
+fn get_income_stream_coverage_by_month(
+    db: State<Database>,
+    stream_id: i64,
+    year: i32,
+) -> Result<Vec<MonthCoverage>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    income_streams::get_monthly_coverage(&conn, stream_id, year).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_income_stream_amount_history(
+    db: State<Database>,
+    stream_id: i64,
+) -> Result<Vec<IncomeStreamHistoryEntry>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    income_streams::get_amount_history(&conn, stream_id).map_err(|e| e.to_string())
+}
+
+/// Percentage change in a stream's expected amount since it was first changed
+#[tauri::command]
+fn get_income_stream_growth(db: State<Database>, stream_id: i64) -> Result<Option<f64>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    income_streams::get_income_growth_from_streams(&conn, stream_id).map_err(|e| e.to_string())
+}
+
+/// Projected income deposits over the next `days_ahead` days
+#[tauri::command]
+fn get_upcoming_income(db: State<Database>, days_ahead: i64) -> Result<Vec<UpcomingIncome>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    income_streams::next_occurrences(&conn, days_ahead).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_paycheck_pattern(db: State<Database>, account_id: i64) -> Result<Option<PaycheckPattern>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    income_stream_engine::get_paycheck_pattern(&conn, account_id).map_err(|e| e.to_string())
+}
+
+// === Report Commands ===
+
+/// Net worth, this month's totals, overspent budgets and upcoming charges for the home screen
+#[tauri::command]
+fn get_home_summary(db: State<Database>, month: String) -> Result<HomeSummary, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    summary::home_summary(&conn, &month).map_err(|e| e.to_string())
+}
+
+/// Uncategorized and unreconciled counts, subscription, income and budget alerts and import gaps
+/// across all accounts; queried on startup for the notification badge
+#[tauri::command]
+fn get_accounts_needing_attention(db: State<Database>) -> Result<AttentionItems, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_attention_items(&conn).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_payee_spending_trend(
+    db: State<Database>,
+    payee: String,
+    months: u32,
+    account_id: Option<i64>,
+) -> Result<PayeeTrend, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_payee_trend(&conn, payee, months, account_id).map_err(|e| e.to_string())
+}
+
+/// Histogram of a payee's transaction amounts; `bucket_size` is in øre and defaults to 100 DKK
+#[tauri::command]
+fn get_payee_spending_distribution(
+    db: State<Database>,
+    payee: String,
+    bucket_size: Option<i64>,
+) -> Result<Vec<AmountBucket>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_payee_amount_distribution(&conn, payee, bucket_size.unwrap_or(10000)).map_err(|e| e.to_string())
+}
+
+/// How many months the current balance lasts if an income stream stops
+#[tauri::command]
+fn get_cashflow_stress_test(
+    db: State<Database>,
+    remove_stream_id: Option<i64>,
+    emergency_fund_months: Option<f64>,
+) -> Result<StressTestResult, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::cashflow_stress_test(&conn, remove_stream_id, emergency_fund_months.unwrap_or(0.0))
+        .map_err(|e| e.to_string())
+}
+
+/// Days the balance on non-credit accounts would last at the last 90 days' spending pace
+#[tauri::command]
+fn get_cash_runway(db: State<Database>) -> Result<CashRunway, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_cash_runway(&conn).map_err(|e| e.to_string())
+}
+
+/// Monthly spending / income ratio with a 3-month trend label
+#[tauri::command]
+fn get_expense_income_ratio_trend(
+    db: State<Database>,
+    account_id: Option<i64>,
+    months: u32,
+) -> Result<Vec<EIRatioPoint>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_expense_income_ratio_trend(&conn, account_id, months).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_savings_rate_projection(
+    db: State<Database>,
+    account_id: Option<i64>,
+) -> Result<SavingsRateProjection, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::project_savings_rate(&conn, account_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_transaction_velocity(
+    db: State<Database>,
+    account_id: Option<i64>,
+    month: String,
+) -> Result<SpendingVelocity, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_spending_velocity(&conn, account_id, month).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_discretionary_vs_essential_spending(
+    db: State<Database>,
+    account_id: Option<i64>,
+    month: String,
+) -> Result<SpendingTypeBreakdown, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_essential_vs_discretionary(&conn, account_id, month).map_err(|e| e.to_string())
+}
+
+/// Share of a month's spending that goes to subscriptions, by actual subscription links
+#[tauri::command]
+fn get_subscription_vs_one_off_spending_split(
+    db: State<Database>,
+    account_id: i64,
+    month: String,
+) -> Result<CommittedSplit, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_committed_vs_discretionary(&conn, account_id, month).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_monthly_summary_content(
+    db: State<Database>,
+    account_id: Option<i64>,
+    month: String,
+) -> Result<MonthlySummary, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::format_monthly_summary(&conn, account_id, month).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_spending_benchmark(
+    db: State<Database>,
+    account_id: Option<i64>,
+    month: String,
+    benchmark_profile: String,
+) -> Result<BenchmarkReport, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_spending_benchmark(&conn, account_id, month, benchmark_profile).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_merchant_spending_map(
+    db: State<Database>,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<MerchantBreakdown>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_merchant_breakdown(&conn, account_id, start_date, end_date).map_err(|e| e.to_string())
+}
+
+/// Payees ranked by how long the user has been spending with them
+#[tauri::command]
+fn get_merchant_loyalty_analysis(
+    db: State<Database>,
+    account_id: Option<i64>,
+    min_visits: u32,
+) -> Result<Vec<MerchantTenure>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_merchant_tenure(&conn, account_id, min_visits).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_net_worth_change_attribution(
+    db: State<Database>,
+    from_month: String,
+    to_month: String,
+) -> Result<NetWorthChangeAttribution, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_net_worth_change_attribution(&conn, from_month, to_month).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_category_month_over_month_change(
+    db: State<Database>,
+    account_id: Option<i64>,
+    current_month: String,
+) -> Result<Vec<CategoryMoMChange>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_category_mom_changes(&conn, account_id, current_month).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_subscription_annual_vs_monthly_savings(
+    db: State<Database>,
+    account_id: i64,
+) -> Result<Vec<BillingComparison>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_billing_cycle_comparison(&conn, account_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_subscription_roi(
+    db: State<Database>,
+    subscription_id: i64,
+    months: Option<u32>,
+) -> Result<SubscriptionEngagement, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_subscription_engagement(&conn, subscription_id, months.unwrap_or(6)).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_transaction_streak(
+    db: State<Database>,
+    category_id: i64,
+    account_id: Option<i64>,
+) -> Result<CategoryStreak, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_category_spending_streak(&conn, category_id, account_id).map_err(|e| e.to_string())
+}
+
+/// Month-to-month predictability of each category's spending on an account
+#[tauri::command]
+fn get_category_spending_consistency(
+    db: State<Database>,
+    account_id: i64,
+    months: u32,
+) -> Result<Vec<CategoryConsistency>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_category_consistency(&conn, account_id, months).map_err(|e| e.to_string())
+}
+
+/// Payees assigned to more than one category, most ambiguous first
+#[tauri::command]
+fn get_payee_category_consistency(
+    db: State<Database>,
+    account_id: Option<i64>,
+) -> Result<Vec<PayeeCategoryInconsistency>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    transactions::get_payee_category_inconsistencies(&conn, account_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_category_spending_percentile(
+    db: State<Database>,
+    category_id: i64,
+    month: String,
+    account_id: Option<i64>,
+) -> Result<SpendingPercentile, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_spending_percentile(&conn, category_id, month, account_id).map_err(|e| e.to_string())
+}
+
+/// Monthly savings needed for a goal and which discretionary categories to cut to get there
+#[tauri::command]
+fn get_savings_acceleration_plan(db: State<Database>, goal_id: i64) -> Result<SavingsPlan, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_savings_plan(&conn, goal_id).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_category_budget_efficiency(db: State<Database>, months: u32) -> Result<Vec<BudgetEfficiency>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_budget_efficiency(&conn, months).map_err(|e| e.to_string())
+}
+
+/// Quarterly spending averaged across years, to spot seasonal patterns
+#[tauri::command]
+fn get_spending_by_time_of_year(
+    db: State<Database>,
+    account_id: Option<i64>,
+    category_id: Option<i64>,
+) -> Result<Vec<SeasonalPattern>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_seasonal_spending(&conn, account_id, category_id).map_err(|e| e.to_string())
+}
+
+/// Yearly income with year-over-year growth
+#[tauri::command]
+fn get_income_growth_rate(db: State<Database>, account_id: Option<i64>) -> Result<Vec<IncomeGrowth>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_income_growth(&conn, account_id).map_err(|e| e.to_string())
+}
+
+/// Categories with unusually high Saturday/Sunday spending compared to weekdays
+#[tauri::command]
+fn get_unusual_weekend_spending(
+    db: State<Database>,
+    account_id: i64,
+    months: u32,
+) -> Result<Vec<WeekendAnomaly>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_weekend_spending_anomalies(&conn, account_id, months).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_spending_cadence(
+    db: State<Database>,
+    account_id: i64,
+    months: u32,
+) -> Result<SpendingCadence, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_spending_cadence(&conn, account_id, months).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_spending_forecast(
+    db: State<Database>,
+    category_id: i64,
+    forecast_months: u32,
+    history_months: u32,
+) -> Result<SpendingForecast, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::forecast_category_spending(&conn, category_id, forecast_months, history_months)
+        .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn get_spending_by_account_type(
+    db: State<Database>,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<AccountTypeSpending>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_spending_by_account_type(&conn, start_date, end_date).map_err(|e| e.to_string())
+}
+
+/// Expected subscription charges and income payments in a date range
+#[tauri::command]
+fn get_recurring_payment_calendar(
+    db: State<Database>,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<Vec<CalendarEvent>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_recurring_calendar(&conn, account_id, start_date, end_date).map_err(|e| e.to_string())
+}
+
+/// One-page account summary for sharing: balances, totals, category breakdown and transactions
+#[tauri::command]
+fn get_bank_statement_summary(
+    db: State<Database>,
+    account_id: i64,
+    start_date: String,
+    end_date: String,
+) -> Result<AccountStatement, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_account_statement(&conn, account_id, start_date, end_date).map_err(|e| e.to_string())
+}
+
+/// Both legs of every recorded transfer, flagging those that don't match up
+#[tauri::command]
+fn get_transfer_reconciliation_report(db: State<Database>) -> Result<Vec<TransferReconciliation>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::get_transfer_reconciliation(&conn).map_err(|e| e.to_string())
+}
+
+/// Resolve a date range preset (e.g. "this_month", "ytd") to (start_date, end_date)
+#[tauri::command]
+fn date_range(db: State<Database>, preset: String) -> Result<(String, String), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    reports::date_range(&conn, &preset)
+}
+
+// === Settings Commands ===
+
+/// Stored value for a setting, or its default if it has never been set
+#[tauri::command]
+fn get_setting(db: State<Database>, key: String) -> Result<Option<serde_json::Value>, String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    settings::get_setting(&conn, &key).map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+fn set_setting(db: State<Database>, key: String, value: serde_json::Value) -> Result<(), String> {
+    let conn = db.conn.lock().map_err(|e| e.to_string())?;
+    settings::set_setting(&conn, &key, &value).map_err(|e| e.to_string())
+}
+
 // === App Entry Point ===
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
@@ -288,28 +1289,92 @@ pub fn run() {
             get_account,
             update_account,
             delete_account,
+            get_credit_card_utilization,
+            get_all_credit_utilizations,
             // Categories
             create_category,
             get_categories,
             get_top_level_categories,
             get_subcategories,
+            set_category_hidden,
+            update_category,
             delete_category,
+            restore_category,
+            get_duplicate_categories,
+            get_category_depth_report,
+            export_categories_csv,
+            import_categories_csv,
+            export_qif,
+            // Categorization Rules
+            create_categorization_rule,
+            get_categorization_rules,
+            update_categorization_rule,
+            delete_categorization_rule,
+            apply_all_categorization_rules,
+            get_payee_category_consistency,
+            get_transactions_needing_review,
+            get_manual_transactions,
+            confirm_categorization,
+            migrate_category_patterns_to_rules,
             // Transactions
+            get_transaction,
             get_transactions,
             get_transactions_by_date_range,
+            get_all_transactions_by_date_range,
+            search_transactions,
             get_spending_by_category,
+            get_spending_by_category_multi,
+            get_spending_by_category_with_subcategory_drill,
+            get_average_by_category,
+            get_savings_rate,
+            get_payee_first_seen,
+            get_quarterly_summary,
+            get_reconciliation_summary,
+            create_reconciliation_session,
+            get_account_reconciliation_history,
+            project_month_spend,
+            annualize_spending,
+            verify_balance_continuity,
+            get_transaction_gap_detection,
             update_transaction_category,
             update_batch_categories,
+            uncategorize_transactions,
+            recategorize_by_payee,
+            rename_payee_across_transactions,
+            get_transaction_correction_log,
             delete_transaction,
             delete_transactions_by_account,
             // Import
             import_csv_file,
             import_csv_bytes,
+            import_apple_card_csv,
+            detect_import_gaps,
+            validate_csv_file,
+            preview_csv_import,
+            commit_csv_import,
+            recompute_import_hashes,
+            // Payee Rules
+            create_payee_rule,
+            get_payee_rules,
+            update_payee_rule,
+            delete_payee_rule,
+            clean_existing_payees,
             // Subscriptions
             detect_subscriptions,
+            mark_subscription_detection_reviewed,
+            detect_subscriptions_detailed,
             get_subscriptions,
             save_subscription,
             dismiss_subscription,
+            // Transfers
+            create_transfer,
+            get_transfers,
+            delete_transfer,
+            // Savings goals
+            create_savings_goal,
+            get_savings_goals,
+            update_savings_goal,
+            delete_savings_goal,
             // Budgets
             create_budget,
             get_budgets,
@@ -317,13 +1382,65 @@ pub fn run() {
             delete_budget,
             set_budget_categories,
             get_budget_categories,
+            set_budget_accounts,
+            get_budget_accounts,
+            get_budget_category_overlap,
+            resolve_category_overlap,
+            assign_transactions_to_budget,
             set_budget_allocation,
+            adjust_budget_allocation,
+            move_between_budgets,
             get_budgets_with_spending,
+            get_budget_funding_status,
             // Income Streams
             create_income_stream,
             get_income_streams,
             update_income_stream,
             delete_income_stream,
+            get_income_stream_last_received,
+            get_income_stream_coverage_by_month,
+            get_income_stream_amount_history,
+            get_income_stream_growth,
+            get_upcoming_income,
+            get_paycheck_pattern,
+            // Reports
+            get_home_summary,
+            get_accounts_needing_attention,
+            get_payee_spending_trend,
+            get_payee_spending_distribution,
+            get_savings_rate_projection,
+            get_expense_income_ratio_trend,
+            get_cashflow_stress_test,
+            get_cash_runway,
+            get_transaction_velocity,
+            get_discretionary_vs_essential_spending,
+            get_subscription_vs_one_off_spending_split,
+            get_spending_benchmark,
+            get_monthly_summary_content,
+            get_merchant_spending_map,
+            get_merchant_loyalty_analysis,
+            get_net_worth_change_attribution,
+            get_category_month_over_month_change,
+            get_subscription_annual_vs_monthly_savings,
+            get_subscription_roi,
+            get_transaction_streak,
+            get_category_spending_consistency,
+            get_category_spending_percentile,
+            get_spending_forecast,
+            get_spending_cadence,
+            get_unusual_weekend_spending,
+            get_spending_by_time_of_year,
+            get_income_growth_rate,
+            get_category_budget_efficiency,
+            get_savings_acceleration_plan,
+            get_spending_by_account_type,
+            get_recurring_payment_calendar,
+            get_bank_statement_summary,
+            get_transfer_reconciliation_report,
+            date_range,
+            // Settings
+            get_setting,
+            set_setting,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
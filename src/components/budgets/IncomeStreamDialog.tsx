@@ -37,6 +37,7 @@ const formSchema = z.object({
   frequency: z.string().min(1, "Frequency is required"),
   category_id: z.number().nullable(),
   is_active: z.boolean(),
+  anchor_date: z.string(),
 });
 
 interface IncomeStreamDialogProps {
@@ -62,6 +63,7 @@ export function IncomeStreamDialog({
       frequency: "monthly",
       category_id: null,
       is_active: true,
+      anchor_date: "",
     },
   });
 
@@ -83,6 +85,7 @@ export function IncomeStreamDialog({
           frequency: stream.frequency,
           category_id: stream.category_id,
           is_active: stream.is_active,
+          anchor_date: stream.anchor_date ?? "",
         });
       } else {
         form.reset({
@@ -91,6 +94,7 @@ export function IncomeStreamDialog({
           frequency: "monthly",
           category_id: null,
           is_active: true,
+          anchor_date: "",
         });
       }
     }
@@ -100,14 +104,17 @@ export function IncomeStreamDialog({
     try {
       if (stream?.id) {
         await updateIncomeStream({
+          ...stream,
           ...values,
           id: stream.id,
           expected_amount: values.expected_amount * 100,
+          anchor_date: values.anchor_date || null,
         });
       } else {
         await createIncomeStream({
           ...values,
           expected_amount: values.expected_amount * 100,
+          anchor_date: values.anchor_date || null,
         });
       }
       toast.success(stream ? "Income stream updated" : "Income stream created");
@@ -179,6 +186,7 @@ export function IncomeStreamDialog({
                         <SelectItem value="weekly">Weekly</SelectItem>
                         <SelectItem value="biweekly">Bi-weekly</SelectItem>
                         <SelectItem value="monthly">Monthly</SelectItem>
+                        <SelectItem value="quarterly">Quarterly</SelectItem>
                         <SelectItem value="yearly">Yearly</SelectItem>
                       </SelectContent>
                     </Select>
@@ -188,6 +196,20 @@ export function IncomeStreamDialog({
               />
             </div>
 
+            <FormField
+              control={form.control}
+              name="anchor_date"
+              render={({ field }) => (
+                <FormItem>
+                  <FormLabel>Next Payment Date (Optional)</FormLabel>
+                  <FormControl>
+                    <Input type="date" {...field} />
+                  </FormControl>
+                  <FormMessage />
+                </FormItem>
+              )}
+            />
+
             <FormField
               control={form.control}
               name="category_id"
@@ -7,12 +7,23 @@ export interface Account {
   name: string;
   account_number: string | null;
   currency: string;
+  /** "checking", "savings", "credit", ... */
+  account_type: string;
+  /** Credit accounts only */
+  credit_limit?: number | null;
+  /** Defaults to "#6366f1" */
+  color?: string;
+  icon?: string | null;
 }
 
+export type SpendingType = "essential" | "discretionary" | "savings";
+
 export interface Category {
   id: number | null;
   name: string;
   parent_id: number | null;
+  spending_type: SpendingType;
+  is_hidden?: boolean;
 }
 
 export interface Transaction {
@@ -21,26 +32,62 @@ export interface Transaction {
   category_id: number | null;
   date: string;
   payee: string;
+  raw_payee: string | null;
+  memo: string | null;
   amount: number;
   balance_snapshot: number | null;
   status: string | null;
   is_reconciled: boolean;
   import_hash: string | null;
+  subscription_id: number | null;
+  /** Auto-categorized by a low-confidence rule */
+  needs_review: boolean;
+}
+
+export interface TransactionWithAccount extends TransactionWithCategory {
+  account_name: string;
 }
 
 export interface TransactionWithCategory extends Transaction {
   category_name: string | null;
+  matched_subscription_id?: number | null;
+  /** Normalized payee pattern of the matched subscription */
+  matched_subscription_pattern?: string | null;
   parent_category_name: string | null;
 }
 
+export interface AmbiguousColumn {
+  field: string;
+  candidates: string[];
+  chosen: string;
+}
+
 export interface ImportResult {
   total_rows: number;
   imported: number;
   skipped_duplicates: number;
+  ambiguous_columns: AmbiguousColumn[];
+  skipped_preamble_lines: number;
+  /** ";" or "," */
+  delimiter_used: string;
+  /** "UTF-8" or "windows-1252" */
+  encoding_used: string;
+  number_format: NumberFormat;
+  /** Problems that didn't stop the import, e.g. a number format unusual for the account currency */
+  warnings: string[];
 }
 
+/** "danish" is 1.234,56 and "us" is 1,234.56 */
+export type NumberFormat = "danish" | "us";
+
 export type SpendingByCategory = [string, number][];
 
+export interface SavingsRate {
+  income: number;
+  expense: number;
+  savings_rate: number;
+}
+
 // ===== Account API =====
 
 export async function createAccount(account: Omit<Account, "id">): Promise<number> {
@@ -63,37 +110,196 @@ export async function deleteAccount(id: number): Promise<number> {
   return invoke<number>("delete_account", { id });
 }
 
+export interface CreditUtilization {
+  account: Account;
+  /** Sum of the account's transactions; negative while money is owed */
+  current_balance: number;
+  credit_limit: number;
+  utilization_pct: number;
+  available_credit: number;
+  /** Utilization above 30% */
+  is_high_utilization: boolean;
+}
+
+export async function getCreditCardUtilization(accountId: number): Promise<CreditUtilization> {
+  return invoke<CreditUtilization>("get_credit_card_utilization", { accountId });
+}
+
+/** Credit accounts without a credit limit are left out */
+export async function getAllCreditUtilizations(): Promise<CreditUtilization[]> {
+  return invoke<CreditUtilization[]>("get_all_credit_utilizations");
+}
+
 // ===== Category API =====
 
-export async function createCategory(category: Omit<Category, "id">): Promise<number> {
+export async function createCategory(
+  category: Omit<Category, "id" | "spending_type"> & { spending_type?: SpendingType }
+): Promise<number> {
   return invoke<number>("create_category", { category });
 }
 
-export async function getCategories(): Promise<Category[]> {
-  return invoke<Category[]>("get_categories");
+/** Hidden categories are left out unless `includeHidden` is set */
+export async function getCategories(includeHidden?: boolean): Promise<Category[]> {
+  return invoke<Category[]>("get_categories", { includeHidden: includeHidden ?? null });
+}
+
+export async function getTopLevelCategories(includeHidden?: boolean): Promise<Category[]> {
+  return invoke<Category[]>("get_top_level_categories", { includeHidden: includeHidden ?? null });
+}
+
+export async function getSubcategories(parentId: number, includeHidden?: boolean): Promise<Category[]> {
+  return invoke<Category[]>("get_subcategories", { parentId, includeHidden: includeHidden ?? null });
+}
+
+export async function setCategoryHidden(id: number, hidden: boolean): Promise<number> {
+  return invoke<number>("set_category_hidden", { id, hidden });
+}
+
+export async function updateCategory(category: Category): Promise<number> {
+  return invoke<number>("update_category", { category });
+}
+
+/** Returns an opaque snapshot that can be passed to `restoreCategory` to undo the deletion */
+export async function deleteCategory(id: number): Promise<string> {
+  return invoke<string>("delete_category", { id });
+}
+
+/** Returns the number of transactions moved back into the restored categories */
+export async function restoreCategory(snapshot: string): Promise<number> {
+  return invoke<number>("restore_category", { snapshot });
+}
+
+export interface DuplicateCategoryGroup {
+  name: string;
+  parent_id: number | null;
+  category_ids: number[];
+  /** Same order as category_ids */
+  transaction_counts: number[];
+}
+
+export async function getDuplicateCategories(): Promise<DuplicateCategoryGroup[]> {
+  return invoke<DuplicateCategoryGroup[]>("get_duplicate_categories");
+}
+
+export interface CategoryWithDepth {
+  category: Category;
+  /** 0 = top-level, 1 = child, 2 = grandchild, ... */
+  depth: number;
+}
+
+/** All categories in tree order (each directly after its parent) */
+export async function getCategoryDepthReport(): Promise<CategoryWithDepth[]> {
+  return invoke<CategoryWithDepth[]>("get_category_depth_report");
+}
+
+export async function exportCategoriesCsv(): Promise<string> {
+  return invoke<string>("export_categories_csv");
+}
+
+/** QIF `!Type:Bank` export; both dates are optional and inclusive */
+export async function exportQif(
+  accountId: number,
+  startDate?: string,
+  endDate?: string
+): Promise<string> {
+  return invoke<string>("export_qif", {
+    accountId,
+    startDate: startDate ?? null,
+    endDate: endDate ?? null,
+  });
+}
+
+/** Returns the number of rows imported */
+export async function importCategoriesCsv(csvContent: string): Promise<number> {
+  return invoke<number>("import_categories_csv", { csvContent });
+}
+
+// ===== Categorization Rule API =====
+
+export interface CategorizationRule {
+  id: number | null;
+  pattern: string;
+  category_id: number;
+  /** Plain patterns must equal the whole payee (case-insensitive); regex patterns may match part of it */
+  is_regex: boolean;
+  priority: number;
+  metadata: string | null;
+}
+
+export interface MigrationResult {
+  rules_created: number;
+  payees_analyzed: number;
+}
+
+export async function createCategorizationRule(rule: Omit<CategorizationRule, "id">): Promise<number> {
+  return invoke<number>("create_categorization_rule", { rule });
+}
+
+export async function getCategorizationRules(): Promise<CategorizationRule[]> {
+  return invoke<CategorizationRule[]>("get_categorization_rules");
+}
+
+export async function updateCategorizationRule(rule: CategorizationRule): Promise<number> {
+  return invoke<number>("update_categorization_rule", { rule });
+}
+
+export async function deleteCategorizationRule(id: number): Promise<number> {
+  return invoke<number>("delete_categorization_rule", { id });
+}
+
+export async function migrateCategoryPatternsToRules(): Promise<MigrationResult> {
+  return invoke<MigrationResult>("migrate_category_patterns_to_rules");
+}
+
+export interface ApplyRulesResult {
+  transactions_categorized: number;
+  transactions_unchanged: number;
+  /** Rule ID -> number of transactions it matched */
+  rules_applied: Record<number, number>;
+}
+
+/** Omit accountId to cover all accounts; overwriteExisting also re-categorizes categorized transactions */
+export async function applyAllCategorizationRules(
+  overwriteExisting: boolean,
+  accountId?: number
+): Promise<ApplyRulesResult> {
+  return invoke<ApplyRulesResult>("apply_all_categorization_rules", {
+    accountId: accountId ?? null,
+    overwriteExisting,
+  });
 }
 
-export async function getTopLevelCategories(): Promise<Category[]> {
-  return invoke<Category[]>("get_top_level_categories");
+export async function getTransactionsNeedingReview(
+  accountId: number
+): Promise<TransactionWithCategory[]> {
+  return invoke<TransactionWithCategory[]>("get_transactions_needing_review", { accountId });
 }
 
-export async function getSubcategories(parentId: number): Promise<Category[]> {
-  return invoke<Category[]>("get_subcategories", { parentId });
+/** Transactions without an import hash, i.e. entered by hand */
+export async function getManualTransactions(accountId: number): Promise<TransactionWithCategory[]> {
+  return invoke<TransactionWithCategory[]>("get_manual_transactions", { accountId });
 }
 
-export async function deleteCategory(id: number): Promise<number> {
-  return invoke<number>("delete_category", { id });
+export async function confirmCategorization(transactionId: number): Promise<number> {
+  return invoke<number>("confirm_categorization", { transactionId });
 }
 
 // ===== Transaction API =====
 
+export async function getTransaction(id: number): Promise<TransactionWithCategory | null> {
+  return invoke<TransactionWithCategory | null>("get_transaction", { id });
+}
+
+/** With `tagSubscriptions`, rows that look like charges of a saved subscription get `matched_subscription_*` set */
 export async function getTransactions(
   accountId: number,
-  limit?: number
+  limit?: number,
+  tagSubscriptions?: boolean
 ): Promise<TransactionWithCategory[]> {
   return invoke<TransactionWithCategory[]>("get_transactions", {
     accountId,
     limit: limit ?? null,
+    tagSubscriptions: tagSubscriptions ?? null,
   });
 }
 
@@ -109,6 +315,26 @@ export async function getTransactionsByDateRange(
   });
 }
 
+/** Transactions from every account in the date range */
+export async function getAllTransactionsByDateRange(
+  startDate: string,
+  endDate: string
+): Promise<TransactionWithAccount[]> {
+  return invoke<TransactionWithAccount[]>("get_all_transactions_by_date_range", { startDate, endDate });
+}
+
+export async function searchTransactions(
+  accountId: number,
+  query: string,
+  includeMemo = false
+): Promise<TransactionWithCategory[]> {
+  return invoke<TransactionWithCategory[]>("search_transactions", {
+    accountId,
+    query,
+    includeMemo,
+  });
+}
+
 export async function getSpendingByCategory(
   accountId: number,
   startDate: string,
@@ -121,6 +347,223 @@ export async function getSpendingByCategory(
   });
 }
 
+/** Spending by category for each month (YYYY-MM) in one call; every requested month is a key */
+export async function getSpendingByCategoryMulti(
+  accountId: number,
+  months: string[]
+): Promise<Record<string, SpendingByCategory>> {
+  return invoke<Record<string, SpendingByCategory>>("get_spending_by_category_multi", {
+    accountId,
+    months,
+  });
+}
+
+export interface HierarchicalSpending {
+  parent_category: string;
+  /** Including subcategories, negative */
+  parent_total: number;
+  /** [name, total] pairs; the parent's own spending is parent_total minus these */
+  subcategories: [string, number][];
+}
+
+/** Spending per top-level category with its subcategories broken out, for drill-down views */
+export async function getSpendingByCategoryWithSubcategoryDrill(
+  accountId: number,
+  startDate: string,
+  endDate: string
+): Promise<HierarchicalSpending[]> {
+  return invoke<HierarchicalSpending[]>("get_spending_by_category_with_subcategory_drill", {
+    accountId,
+    startDate,
+    endDate,
+  });
+}
+
+export interface CategoryAverage {
+  category_id: number;
+  category_name: string;
+  /** Positive øre, subcategories included */
+  average_amount: number;
+  transaction_count: number;
+}
+
+export async function getAverageByCategory(
+  accountId: number,
+  startDate: string,
+  endDate: string
+): Promise<CategoryAverage[]> {
+  return invoke<CategoryAverage[]>("get_average_by_category", { accountId, startDate, endDate });
+}
+
+/** Omit accountId to compute across all accounts */
+export async function getSavingsRate(
+  startDate: string,
+  endDate: string,
+  accountId?: number
+): Promise<SavingsRate> {
+  return invoke<SavingsRate>("get_savings_rate", {
+    accountId: accountId ?? null,
+    startDate,
+    endDate,
+  });
+}
+
+export interface PayeeFirstSeen {
+  payee: string;
+  first_seen: string;
+  total_visits: number;
+}
+
+/** Most recently discovered payees first; sinceDate limits to payees first seen on or after it */
+export async function getPayeeFirstSeen(
+  accountId?: number,
+  sinceDate?: string
+): Promise<PayeeFirstSeen[]> {
+  return invoke<PayeeFirstSeen[]>("get_payee_first_seen", {
+    accountId: accountId ?? null,
+    sinceDate: sinceDate ?? null,
+  });
+}
+
+export interface PayeeCategoryInconsistency {
+  payee: string;
+  /** Each category with its transaction count, largest first */
+  categories: [Category, number][];
+}
+
+/** Payees filed under more than one category, most categories first */
+export async function getPayeeCategoryConsistency(accountId?: number): Promise<PayeeCategoryInconsistency[]> {
+  return invoke<PayeeCategoryInconsistency[]>("get_payee_category_consistency", {
+    accountId: accountId ?? null,
+  });
+}
+
+export interface QuarterlySummary {
+  quarter: number;
+  income: number;
+  /** Positive sum of outflows */
+  expense: number;
+  net: number;
+}
+
+/** Always returns Q1-Q4, with zeros for quarters without data */
+export async function getQuarterlySummary(accountId: number, year: number): Promise<QuarterlySummary[]> {
+  return invoke<QuarterlySummary[]>("get_quarterly_summary", { accountId, year });
+}
+
+export interface MonthSpendProjection {
+  month: string;
+  /** Positive sum of outflows so far */
+  actual_to_date: number;
+  projected_total: number;
+  percent_month_elapsed: number;
+}
+
+/** `month` is YYYY-MM */
+export async function projectMonthSpend(accountId: number, month: string): Promise<MonthSpendProjection> {
+  return invoke<MonthSpendProjection>("project_month_spend", { accountId, month });
+}
+
+export interface CategoryAnnualProjection {
+  /** Subcategories are counted in their parent */
+  category_name: string;
+  actual_to_date: number;
+  projected_total: number;
+}
+
+export interface AnnualSpendingProjection {
+  year: number;
+  /** Positive sum of outflows so far */
+  actual_to_date: number;
+  projected_total: number;
+  percent_year_elapsed: number;
+  categories: CategoryAnnualProjection[];
+}
+
+/** Year-to-date spending scaled up to a full-year estimate */
+export async function annualizeSpending(accountId: number, year: number): Promise<AnnualSpendingProjection> {
+  return invoke<AnnualSpendingProjection>("annualize_spending", { accountId, year });
+}
+
+export interface ReconciliationSummary {
+  reconciled_count: number;
+  reconciled_total: number;
+  unreconciled_count: number;
+  unreconciled_total: number;
+  /** From the latest reconciliation session; null if never reconciled */
+  last_reconciled_date: string | null;
+}
+
+export async function getReconciliationSummary(
+  accountId: number,
+  startDate: string,
+  endDate: string
+): Promise<ReconciliationSummary> {
+  return invoke<ReconciliationSummary>("get_reconciliation_summary", {
+    accountId,
+    startDate,
+    endDate,
+  });
+}
+
+export interface ReconciliationSession {
+  id: number;
+  account_id: number;
+  /** Latest transaction date covered by the session */
+  reconciled_date: string;
+  ending_balance: number;
+  transaction_count: number;
+  created_at: string;
+}
+
+export async function createReconciliationSession(
+  accountId: number,
+  endingBalance: number,
+  markTransactions: number[]
+): Promise<ReconciliationSession> {
+  return invoke<ReconciliationSession>("create_reconciliation_session", {
+    accountId,
+    endingBalance,
+    markTransactions,
+  });
+}
+
+export async function getAccountReconciliationHistory(accountId: number): Promise<ReconciliationSession[]> {
+  return invoke<ReconciliationSession[]>("get_account_reconciliation_history", { accountId });
+}
+
+export interface BalanceDiscrepancy {
+  transaction_id: number;
+  date: string;
+  payee: string;
+  amount: number;
+  expected_balance: number;
+  actual_balance: number;
+  /** actual - expected */
+  difference: number;
+}
+
+export async function verifyBalanceContinuity(accountId: number): Promise<BalanceDiscrepancy[]> {
+  return invoke<BalanceDiscrepancy[]>("verify_balance_continuity", { accountId });
+}
+
+export interface TransactionGap {
+  start_date: string;
+  end_date: string;
+  gap_days: number;
+}
+
+/** Periods with no transactions that may indicate a missing import (default threshold 15 days) */
+export async function getTransactionGapDetection(
+  accountId: number,
+  maxGapDays?: number
+): Promise<TransactionGap[]> {
+  return invoke<TransactionGap[]>("get_transaction_gap_detection", {
+    accountId,
+    maxGapDays: maxGapDays ?? null,
+  });
+}
+
 export async function updateTransactionCategory(
   transactionId: number,
   categoryId: number | null
@@ -141,6 +584,51 @@ export async function updateBatchCategories(
   });
 }
 
+/** Clears the category on the given transactions; returns the number updated */
+export async function uncategorizeTransactions(transactionIds: number[]): Promise<number> {
+  return invoke<number>("uncategorize_transactions", { transactionIds });
+}
+
+/** Set the category on every transaction whose payee contains the query */
+export async function recategorizeByPayee(
+  payeeQuery: string,
+  categoryId: number | null,
+  accountId?: number
+): Promise<number> {
+  return invoke<number>("recategorize_by_payee", {
+    payeeQuery,
+    categoryId,
+    accountId: accountId ?? null,
+  });
+}
+
+/** Rename every transaction with exactly `oldPayee`. Returns the number renamed. */
+export async function renamePayeeAcrossTransactions(
+  oldPayee: string,
+  newPayee: string,
+  accountId?: number
+): Promise<number> {
+  return invoke<number>("rename_payee_across_transactions", {
+    oldPayee,
+    newPayee,
+    accountId: accountId ?? null,
+  });
+}
+
+export interface AuditLogEntry {
+  id: number;
+  transaction_id: number;
+  field_changed: "category_id" | "payee";
+  old_value: string | null;
+  new_value: string | null;
+  changed_at: string;
+}
+
+/** Edits to one transaction, or the most recent edits across all transactions */
+export async function getTransactionCorrectionLog(transactionId?: number): Promise<AuditLogEntry[]> {
+  return invoke<AuditLogEntry[]>("get_transaction_correction_log", { transactionId: transactionId ?? null });
+}
+
 export async function deleteTransaction(id: number): Promise<number> {
   return invoke<number>("delete_transaction", { id });
 }
@@ -179,77 +667,320 @@ export async function importCsvBytes(
   });
 }
 
-// ===== Subscription Types =====
-
-export interface Subscription {
-  id: number | null;
-  account_id: number;
-  payee_pattern: string;
-  amount: number;
-  frequency: string;
-  last_charge_date: string | null;
-  next_charge_date: string | null;
-  is_active: boolean;
-  category_id: number | null;
-  confidence: number;
-  transaction_ids: number[];
-}
-
-// ===== Subscription API =====
-
-export async function detectSubscriptions(accountId: number): Promise<Subscription[]> {
-  return invoke<Subscription[]>("detect_subscriptions", { accountId });
+/** Import an Apple Card CSV export (MM/DD/YYYY dates, amounts in USD) */
+export async function importAppleCardCsv(
+  csvContent: string,
+  accountId: number,
+  filename: string
+): Promise<ImportResult> {
+  return invoke<ImportResult>("import_apple_card_csv", {
+    csvContent,
+    accountId,
+    filename,
+  });
 }
 
-export async function getSubscriptions(accountId: number): Promise<Subscription[]> {
-  return invoke<Subscription[]>("get_subscriptions", { accountId });
+export interface ImportGap {
+  month: string;
+  transaction_count: number;
+  median_count: number;
 }
 
-export async function saveSubscription(subscription: Subscription): Promise<number> {
-  return invoke<number>("save_subscription", { subscription });
+/** Months with suspiciously few transactions, likely statements that were never imported */
+export async function detectImportGaps(accountId: number): Promise<ImportGap[]> {
+  return invoke<ImportGap[]>("detect_import_gaps", { accountId });
 }
 
-export async function dismissSubscription(id: number): Promise<number> {
-  return invoke<number>("dismiss_subscription", { id });
-}
+// ===== Import Wizard API =====
 
-// ===== Budget API =====
+/** Column index per field: date, category, subcategory, payee, memo, amount, balance, status, reconciled */
+export type ColumnMap = Record<string, number>;
 
-export interface Budget {
-  id: number | null;
-  name: string;
-  color: string;
-  icon: string | null;
+export interface CsvValidationResult {
+  detected_format: "semicolon" | "comma" | "unknown";
+  headers: string[];
+  column_map: ColumnMap;
+  sample_rows: string[][];
+  ambiguous_columns: AmbiguousColumn[];
+  skipped_preamble_lines: number;
+  /** e.g. amounts that look like another number format than the profile's */
+  warnings: string[];
+  error: string | null;
 }
 
-export interface BudgetAllocation {
-  id: number | null;
-  budget_id: number;
-  month: string;
-  allocated_amount: number;
+export interface PreviewRow {
+  date: string;
+  payee: string;
+  memo: string | null;
+  amount: number;
+  balance: number | null;
+  category: string | null;
+  subcategory: string | null;
+  is_duplicate: boolean;
 }
 
-export interface BudgetWithSpending {
-  budget: Budget;
-  category_ids: number[];
-  allocated_amount: number;
-  spent_amount: number;
+export interface ImportPreview {
+  account_id: number;
+  total_rows: number;
+  new_rows: number;
+  duplicate_rows: number;
+  rows: PreviewRow[];
+  ambiguous_columns: AmbiguousColumn[];
 }
 
-export async function createBudget(budget: Omit<Budget, "id">): Promise<number> {
-  return invoke<number>("create_budget", { budget });
+export interface ImportProfile {
+  /** Column index per field, taking precedence over detection */
+  column_overrides: ColumnMap;
+  /** Headers summed into the amount (e.g. principal + fee); empty uses the detected amount column */
+  amount_columns: string[];
+  /** Danish when omitted; a file that looks like another format only produces a warning */
+  number_format?: NumberFormat | null;
+  /** Bank category (or subcategory) name -> existing category id, matched case-insensitively */
+  category_mapping?: Record<string, number>;
+  /** Bank categories missing from the mapping are created (default) or imported uncategorized and flagged */
+  unmapped_categories?: UnmappedCategoryMode;
 }
 
-export async function getBudgets(): Promise<Budget[]> {
-  return invoke<Budget[]>("get_budgets");
-}
+export type UnmappedCategoryMode = "create" | "flag";
 
-export async function updateBudget(budget: Budget): Promise<number> {
-  return invoke<number>("update_budget", { budget });
+export async function validateCsvFile(
+  csvContent: string,
+  accountId: number,
+  profile?: ImportProfile
+): Promise<CsvValidationResult> {
+  return invoke<CsvValidationResult>("validate_csv_file", {
+    csvContent,
+    accountId,
+    profile: profile ?? null,
+  });
 }
 
-export async function deleteBudget(id: number): Promise<number> {
-  return invoke<number>("delete_budget", { id });
+export async function previewCsvImport(
+  csvContent: string,
+  accountId: number,
+  profile?: ImportProfile
+): Promise<ImportPreview> {
+  return invoke<ImportPreview>("preview_csv_import", {
+    csvContent,
+    accountId,
+    profile: profile ?? null,
+  });
+}
+
+export async function commitCsvImport(
+  csvContent: string,
+  accountId: number,
+  filename: string,
+  profile?: ImportProfile
+): Promise<ImportResult> {
+  return invoke<ImportResult>("commit_csv_import", {
+    csvContent,
+    accountId,
+    filename,
+    profile: profile ?? null,
+  });
+}
+
+export interface HashCollision {
+  transaction_id: number;
+  duplicate_of: number;
+}
+
+export interface RecomputeHashesResult {
+  updated: number;
+  unchanged: number;
+  /** Transactions left without a hash and flagged for review */
+  collisions: HashCollision[];
+}
+
+export async function recomputeImportHashes(accountId: number): Promise<RecomputeHashesResult> {
+  return invoke<RecomputeHashesResult>("recompute_import_hashes", { accountId });
+}
+
+// ===== Payee Rule API =====
+
+export interface PayeeRule {
+  id: number | null;
+  pattern: string;
+  replacement: string;
+  order: number;
+}
+
+export async function createPayeeRule(rule: Omit<PayeeRule, "id">): Promise<number> {
+  return invoke<number>("create_payee_rule", { rule });
+}
+
+export async function getPayeeRules(): Promise<PayeeRule[]> {
+  return invoke<PayeeRule[]>("get_payee_rules");
+}
+
+export async function updatePayeeRule(rule: PayeeRule): Promise<number> {
+  return invoke<number>("update_payee_rule", { rule });
+}
+
+export async function deletePayeeRule(id: number): Promise<number> {
+  return invoke<number>("delete_payee_rule", { id });
+}
+
+/** Re-apply payee rules to all existing transactions, returns number changed */
+export async function cleanExistingPayees(): Promise<number> {
+  return invoke<number>("clean_existing_payees");
+}
+
+// ===== Subscription Types =====
+
+export interface Subscription {
+  id: number | null;
+  account_id: number;
+  payee_pattern: string;
+  amount: number;
+  frequency: string;
+  last_charge_date: string | null;
+  next_charge_date: string | null;
+  is_active: boolean;
+  category_id: number | null;
+  confidence: number;
+  transaction_ids: number[];
+}
+
+// ===== Subscription API =====
+
+/** Only patterns with transactions since the last reviewed detection are returned unless forceFull is set */
+export async function detectSubscriptions(accountId: number, forceFull?: boolean): Promise<Subscription[]> {
+  return invoke<Subscription[]>("detect_subscriptions", { accountId, forceFull: forceFull ?? null });
+}
+
+/** Call once every detected candidate has been saved or ignored, so they aren't offered again */
+export async function markSubscriptionDetectionReviewed(accountId: number): Promise<void> {
+  return invoke<void>("mark_subscription_detection_reviewed", { accountId });
+}
+
+export interface SubscriptionOccurrence {
+  transaction_id: number;
+  date: string;
+  amount: number;
+  payee: string;
+}
+
+export interface DetectedSubscription extends Subscription {
+  /** The transactions the pattern was detected from, oldest first */
+  occurrences: SubscriptionOccurrence[];
+}
+
+export async function detectSubscriptionsDetailed(accountId: number): Promise<DetectedSubscription[]> {
+  return invoke<DetectedSubscription[]>("detect_subscriptions_detailed", { accountId });
+}
+
+export async function getSubscriptions(accountId: number): Promise<Subscription[]> {
+  return invoke<Subscription[]>("get_subscriptions", { accountId });
+}
+
+export async function saveSubscription(subscription: Subscription): Promise<number> {
+  return invoke<number>("save_subscription", { subscription });
+}
+
+export async function dismissSubscription(id: number): Promise<number> {
+  return invoke<number>("dismiss_subscription", { id });
+}
+
+// ===== Transfer API =====
+
+export interface Transfer {
+  id: number | null;
+  /** The outflow leg */
+  debit_transaction_id: number;
+  /** The inflow leg */
+  credit_transaction_id: number;
+}
+
+export async function createTransfer(transfer: Transfer): Promise<number> {
+  return invoke<number>("create_transfer", { transfer });
+}
+
+export async function getTransfers(): Promise<Transfer[]> {
+  return invoke<Transfer[]>("get_transfers");
+}
+
+export async function deleteTransfer(id: number): Promise<number> {
+  return invoke<number>("delete_transfer", { id });
+}
+
+// ===== Savings Goal API =====
+
+export interface SavingsGoal {
+  id: number | null;
+  name: string;
+  target_amount: number;
+  /** YYYY-MM-DD */
+  target_date: string;
+  /** Saved towards the goal so far */
+  current_amount: number;
+  /** null counts savings across all accounts */
+  account_id: number | null;
+}
+
+export async function createSavingsGoal(goal: SavingsGoal): Promise<number> {
+  return invoke<number>("create_savings_goal", { goal });
+}
+
+export async function getSavingsGoals(): Promise<SavingsGoal[]> {
+  return invoke<SavingsGoal[]>("get_savings_goals");
+}
+
+export async function updateSavingsGoal(goal: SavingsGoal): Promise<number> {
+  return invoke<number>("update_savings_goal", { goal });
+}
+
+export async function deleteSavingsGoal(id: number): Promise<number> {
+  return invoke<number>("delete_savings_goal", { id });
+}
+
+// ===== Budget API =====
+
+export interface Budget {
+  id: number | null;
+  name: string;
+  color: string;
+  icon: string | null;
+}
+
+export interface BudgetAllocation {
+  id: number | null;
+  budget_id: number;
+  month: string;
+  allocated_amount: number;
+}
+
+export interface BudgetWithSpending {
+  budget: Budget;
+  category_ids: number[];
+  allocated_amount: number;
+  spent_amount: number;
+  percent_spent: number;
+  /** Only set when viewing the current month */
+  percent_month_elapsed: number | null;
+  pace_status: PaceStatus | null;
+  /** allocated_amount was scaled down for a mid-month start */
+  is_prorated: boolean;
+}
+
+/** "ahead" means spending faster than the month is passing */
+export type PaceStatus = "on_track" | "ahead" | "behind";
+
+export async function createBudget(budget: Omit<Budget, "id">): Promise<number> {
+  return invoke<number>("create_budget", { budget });
+}
+
+export async function getBudgets(): Promise<Budget[]> {
+  return invoke<Budget[]>("get_budgets");
+}
+
+export async function updateBudget(budget: Budget): Promise<number> {
+  return invoke<number>("update_budget", { budget });
+}
+
+export async function deleteBudget(id: number): Promise<number> {
+  return invoke<number>("delete_budget", { id });
 }
 
 export async function setBudgetCategories(budgetId: number, categoryIds: number[]): Promise<void> {
@@ -260,12 +991,95 @@ export async function getBudgetCategories(budgetId: number): Promise<number[]> {
   return invoke<number[]>("get_budget_categories", { budgetId });
 }
 
+/** An empty list means the budget covers all accounts */
+export async function setBudgetAccounts(budgetId: number, accountIds: number[]): Promise<void> {
+  return invoke<void>("set_budget_accounts", { budgetId, accountIds });
+}
+
+export async function getBudgetAccounts(budgetId: number): Promise<number[]> {
+  return invoke<number[]>("get_budget_accounts", { budgetId });
+}
+
+export interface OverlapReport {
+  category: Category;
+  budget_ids: number[];
+  budget_names: string[];
+  transaction_count: number;
+}
+
+export async function getBudgetCategoryOverlap(): Promise<OverlapReport[]> {
+  return invoke<OverlapReport[]>("get_budget_category_overlap");
+}
+
+/** Removes the category from every budget except `keepBudgetId`; returns the number of links removed */
+export async function resolveCategoryOverlap(categoryId: number, keepBudgetId: number): Promise<number> {
+  return invoke<number>("resolve_category_overlap", { categoryId, keepBudgetId });
+}
+
+export async function assignTransactionsToBudget(
+  budgetId: number,
+  transactionIds: number[],
+  categoryId: number
+): Promise<number> {
+  return invoke<number>("assign_transactions_to_budget", { budgetId, transactionIds, categoryId });
+}
+
 export async function setBudgetAllocation(budgetId: number, month: string, amount: number): Promise<void> {
   return invoke<void>("set_budget_allocation", { budgetId, month, amount });
 }
 
-export async function getBudgetsWithSpending(month: string): Promise<BudgetWithSpending[]> {
-  return invoke<BudgetWithSpending[]>("get_budgets_with_spending", { month });
+/** Change an allocation by a signed delta; clamped at zero unless allowNegative. Returns the new allocation. */
+export async function adjustBudgetAllocation(
+  budgetId: number,
+  month: string,
+  delta: number,
+  allowNegative?: boolean
+): Promise<number> {
+  return invoke<number>("adjust_budget_allocation", {
+    budgetId,
+    month,
+    delta,
+    allowNegative: allowNegative ?? null,
+  });
+}
+
+/** Move allocation from one budget to another for the month. Returns the amount actually moved. */
+export async function moveBetweenBudgets(
+  fromBudgetId: number,
+  toBudgetId: number,
+  month: string,
+  amount: number,
+  allowNegative?: boolean
+): Promise<number> {
+  return invoke<number>("move_between_budgets", {
+    fromBudgetId,
+    toBudgetId,
+    month,
+    amount,
+    allowNegative: allowNegative ?? null,
+  });
+}
+
+/** prorateFromDay scales the current month's allocations to the days from that day onwards */
+export async function getBudgetsWithSpending(
+  month: string,
+  prorateFromDay?: number
+): Promise<BudgetWithSpending[]> {
+  return invoke<BudgetWithSpending[]>("get_budgets_with_spending", {
+    month,
+    prorateFromDay: prorateFromDay ?? null,
+  });
+}
+
+export interface BudgetFundingStatus {
+  month: string;
+  total_allocated: number;
+  expected_income: number;
+  unallocated: number;
+}
+
+export async function getBudgetFundingStatus(month: string): Promise<BudgetFundingStatus> {
+  return invoke<BudgetFundingStatus>("get_budget_funding_status", { month });
 }
 
 // ===== Income Stream API =====
@@ -277,6 +1091,10 @@ export interface IncomeStream {
   frequency: string;
   category_id: number | null;
   is_active: boolean;
+  /** "outflow" streams track recurring obligations such as loan payments, with a negative amount */
+  direction?: "inflow" | "outflow";
+  /** A known deposit date (YYYY-MM-DD) that upcoming dates are projected from, instead of the last matched transaction */
+  anchor_date?: string | null;
 }
 
 export async function createIncomeStream(stream: Omit<IncomeStream, "id">): Promise<number> {
@@ -295,3 +1113,677 @@ export async function deleteIncomeStream(id: number): Promise<number> {
   return invoke<number>("delete_income_stream", { id });
 }
 
+export interface IncomeStreamWithLastReceived {
+  stream: IncomeStream;
+  last_received: Transaction | null;
+}
+
+export interface PaycheckPattern {
+  payee: string;
+  typical_amount: number;
+  typical_day_of_month: number;
+  consistency_score: number;
+  last_received: string;
+}
+
+export async function getPaycheckPattern(accountId: number): Promise<PaycheckPattern | null> {
+  return invoke<PaycheckPattern | null>("get_paycheck_pattern", { accountId });
+}
+
+export async function getIncomeStreamLastReceived(): Promise<IncomeStreamWithLastReceived[]> {
+  return invoke<IncomeStreamWithLastReceived[]>("get_income_stream_last_received");
+}
+
+export interface MonthCoverage {
+  month: string;
+  received: boolean;
+  actual_amount: number | null;
+  expected_amount: number;
+}
+
+/** Always 12 entries, one per month of the year */
+export async function getIncomeStreamCoverageByMonth(
+  streamId: number,
+  year: number
+): Promise<MonthCoverage[]> {
+  return invoke<MonthCoverage[]>("get_income_stream_coverage_by_month", { streamId, year });
+}
+
+export interface IncomeStreamHistoryEntry {
+  id: number;
+  stream_id: number;
+  expected_amount: number;
+  changed_at: string;
+}
+
+/** Earlier expected amounts of a stream, most recent change first */
+export async function getIncomeStreamAmountHistory(streamId: number): Promise<IncomeStreamHistoryEntry[]> {
+  return invoke<IncomeStreamHistoryEntry[]>("get_income_stream_amount_history", { streamId });
+}
+
+/** Percentage change since the first recorded amount, or null if the amount was never changed */
+export async function getIncomeStreamGrowth(streamId: number): Promise<number | null> {
+  return invoke<number | null>("get_income_stream_growth", { streamId });
+}
+
+export interface UpcomingIncome {
+  date: string;
+  amount: number;
+  name: string;
+  stream_id: number;
+}
+
+export async function getUpcomingIncome(daysAhead: number): Promise<UpcomingIncome[]> {
+  return invoke<UpcomingIncome[]>("get_upcoming_income", { daysAhead });
+}
+
+
+// ===== Report API =====
+
+export interface HomeSummary {
+  month: string;
+  net_worth: number;
+  income: number;
+  expense: number;
+  net: number;
+  overspent_budget_count: number;
+  /** Subscription charges due in the next 30 days, positive */
+  upcoming_charges_total: number;
+}
+
+/** Everything the home screen needs for a month in a single call */
+export async function getHomeSummary(month: string): Promise<HomeSummary> {
+  return invoke<HomeSummary>("get_home_summary", { month });
+}
+
+export interface AttentionItems {
+  uncategorized_count: number;
+  unreconciled_count: number;
+  /** Next charge date has passed */
+  overdue_subscriptions: Subscription[];
+  upcoming_subscriptions_7d: Subscription[];
+  /** Expected deposit is more than a few days late */
+  missed_income_streams: IncomeStream[];
+  /** This month */
+  budget_over_90pct: BudgetWithSpending[];
+  transaction_gaps: TransactionGap[];
+}
+
+/** Actionable items across all accounts, fetched on startup for the notification badge */
+export async function getAccountsNeedingAttention(): Promise<AttentionItems> {
+  return invoke<AttentionItems>("get_accounts_needing_attention");
+}
+
+export interface MonthlyAmount {
+  month: string;
+  total: number;
+  count: number;
+}
+
+export interface PayeeTrendSummary {
+  avg_monthly: number;
+  trend_direction: "increasing" | "decreasing" | "stable";
+}
+
+export interface PayeeTrend {
+  months: MonthlyAmount[];
+  summary: PayeeTrendSummary;
+}
+
+export async function getPayeeSpendingTrend(
+  payee: string,
+  months: number,
+  accountId?: number
+): Promise<PayeeTrend> {
+  return invoke<PayeeTrend>("get_payee_spending_trend", {
+    payee,
+    months,
+    accountId: accountId ?? null,
+  });
+}
+
+export interface AmountBucket {
+  lower_bound: number;
+  upper_bound: number;
+  count: number;
+  total: number;
+}
+
+/** Only populated buckets are returned; `bucketSize` is in øre (default 10000 = 100 DKK) */
+export async function getPayeeSpendingDistribution(
+  payee: string,
+  bucketSize?: number
+): Promise<AmountBucket[]> {
+  return invoke<AmountBucket[]>("get_payee_spending_distribution", {
+    payee,
+    bucketSize: bucketSize ?? null,
+  });
+}
+
+export interface EIRatioPoint {
+  month: string;
+  /** Spending / income; null for months without income */
+  expense_ratio: number | null;
+  trend: "improving" | "worsening" | "stable";
+}
+
+export async function getExpenseIncomeRatioTrend(
+  months: number,
+  accountId?: number
+): Promise<EIRatioPoint[]> {
+  return invoke<EIRatioPoint[]>("get_expense_income_ratio_trend", {
+    accountId: accountId ?? null,
+    months,
+  });
+}
+
+export interface StressTestResult {
+  /** Expenses minus income per month; negative is a surplus */
+  monthly_deficit: number;
+  /** Capped at 120 when income still covers expenses */
+  months_survivable: number;
+  risk_level: "high" | "medium" | "low";
+}
+
+export async function getCashflowStressTest(
+  removeStreamId?: number,
+  emergencyFundMonths?: number
+): Promise<StressTestResult> {
+  return invoke<StressTestResult>("get_cashflow_stress_test", {
+    removeStreamId: removeStreamId ?? null,
+    emergencyFundMonths: emergencyFundMonths ?? null,
+  });
+}
+
+export interface CashRunway {
+  total_balance: number;
+  /** Positive, expenses only; transfers between accounts excluded */
+  avg_daily_spend: number;
+  runway_days: number;
+  runway_months: number;
+  /** At least 90 days of runway */
+  is_emergency_fund_adequate: boolean;
+}
+
+/** How long the balance on non-credit accounts would last with no income, at the last 90 days' pace */
+export async function getCashRunway(): Promise<CashRunway> {
+  return invoke<CashRunway>("get_cash_runway");
+}
+
+export interface SavingsRateProjection {
+  current_savings_rate: number;
+  projected_savings_rate: number;
+  days_remaining: number;
+  spending_pace: number;
+}
+
+export async function getSavingsRateProjection(accountId?: number): Promise<SavingsRateProjection> {
+  return invoke<SavingsRateProjection>("get_savings_rate_projection", {
+    accountId: accountId ?? null,
+  });
+}
+
+export interface SpendingVelocity {
+  first_half_daily_avg: number;
+  second_half_daily_avg: number;
+  full_month_projected: number;
+  acceleration_factor: number;
+}
+
+export async function getTransactionVelocity(
+  month: string,
+  accountId?: number
+): Promise<SpendingVelocity> {
+  return invoke<SpendingVelocity>("get_transaction_velocity", {
+    accountId: accountId ?? null,
+    month,
+  });
+}
+
+export interface SpendingTypeBreakdown {
+  essential: number;
+  discretionary: number;
+  savings: number;
+  uncategorized: number;
+}
+
+export async function getDiscretionaryVsEssentialSpending(
+  month: string,
+  accountId?: number
+): Promise<SpendingTypeBreakdown> {
+  return invoke<SpendingTypeBreakdown>("get_discretionary_vs_essential_spending", {
+    accountId: accountId ?? null,
+    month,
+  });
+}
+
+export interface CommittedSplit {
+  month: string;
+  /** Positive, transactions linked to a subscription */
+  committed_total: number;
+  /** Positive, every other expense */
+  discretionary_total: number;
+  committed_pct: number;
+}
+
+/** Subscription charges vs. one-off spending for a month, by actual subscription links */
+export async function getSubscriptionVsOneOffSpendingSplit(
+  accountId: number,
+  month: string
+): Promise<CommittedSplit> {
+  return invoke<CommittedSplit>("get_subscription_vs_one_off_spending_split", { accountId, month });
+}
+
+export interface MonthlySummary {
+  subject: string;
+  body_plain_text: string;
+  body_html: string;
+}
+
+/** Month-end report as plain text and HTML, ready to display or copy to the clipboard */
+export async function getMonthlySummaryContent(month: string, accountId?: number): Promise<MonthlySummary> {
+  return invoke<MonthlySummary>("get_monthly_summary_content", {
+    accountId: accountId ?? null,
+    month,
+  });
+}
+
+export interface BenchmarkReport {
+  month: string;
+  profile: string;
+  actual_needs_pct: number;
+  target_needs_pct: number;
+  actual_wants_pct: number;
+  target_wants_pct: number;
+  actual_savings_pct: number;
+  target_savings_pct: number;
+  overall_score: number;
+}
+
+/** `benchmarkProfile` is "50-30-20" or the name of a custom `benchmark_profile:{name}` setting */
+export async function getSpendingBenchmark(
+  month: string,
+  benchmarkProfile: string,
+  accountId?: number
+): Promise<BenchmarkReport> {
+  return invoke<BenchmarkReport>("get_spending_benchmark", {
+    accountId: accountId ?? null,
+    month,
+    benchmarkProfile,
+  });
+}
+
+export interface MerchantBreakdown {
+  payee: string;
+  total_spent: number;
+  category_breakdown: [string, number][];
+  last_visit: string;
+  visit_count: number;
+}
+
+export async function getMerchantSpendingMap(
+  accountId: number,
+  startDate: string,
+  endDate: string
+): Promise<MerchantBreakdown[]> {
+  return invoke<MerchantBreakdown[]>("get_merchant_spending_map", {
+    accountId,
+    startDate,
+    endDate,
+  });
+}
+
+export interface MerchantTenure {
+  payee: string;
+  first_date: string;
+  last_date: string;
+  tenure_days: number;
+  total_spent: number;
+  visit_count: number;
+}
+
+export async function getMerchantLoyaltyAnalysis(
+  minVisits: number,
+  accountId?: number
+): Promise<MerchantTenure[]> {
+  return invoke<MerchantTenure[]>("get_merchant_loyalty_analysis", {
+    accountId: accountId ?? null,
+    minVisits,
+  });
+}
+
+export interface NetWorthChangeAttribution {
+  from: number;
+  to: number;
+  change: number;
+  income: number;
+  expenses: number;
+  subscriptions_total: number;
+  transfers_net: number;
+}
+
+export async function getNetWorthChangeAttribution(
+  fromMonth: string,
+  toMonth: string
+): Promise<NetWorthChangeAttribution> {
+  return invoke<NetWorthChangeAttribution>("get_net_worth_change_attribution", {
+    fromMonth,
+    toMonth,
+  });
+}
+
+export interface CategoryMoMChange {
+  /** null for uncategorized spending */
+  category_id: number | null;
+  category_name: string;
+  current: number;
+  prior: number;
+  absolute_change: number;
+  pct_change: number;
+}
+
+export async function getCategoryMonthOverMonthChange(
+  currentMonth: string,
+  accountId?: number
+): Promise<CategoryMoMChange[]> {
+  return invoke<CategoryMoMChange[]>("get_category_month_over_month_change", {
+    accountId: accountId ?? null,
+    currentMonth,
+  });
+}
+
+export interface BillingComparison {
+  subscription: Subscription;
+  monthly_equivalent: number;
+  annual_estimate: number;
+  potential_savings: number;
+}
+
+export async function getSubscriptionAnnualVsMonthlySavings(
+  accountId: number
+): Promise<BillingComparison[]> {
+  return invoke<BillingComparison[]>("get_subscription_annual_vs_monthly_savings", { accountId });
+}
+
+export interface SubscriptionEngagement {
+  subscription: Subscription;
+  engagement_score: number;
+  related_transactions: Transaction[];
+}
+
+/** Other purchases at the subscription's merchant over the last `months` months (default 6) */
+export async function getSubscriptionRoi(
+  subscriptionId: number,
+  months?: number
+): Promise<SubscriptionEngagement> {
+  return invoke<SubscriptionEngagement>("get_subscription_roi", { subscriptionId, months: months ?? null });
+}
+
+export interface CategoryStreak {
+  category_id: number;
+  category_name: string;
+  current_streak_months: number;
+  longest_streak_months: number;
+}
+
+export async function getTransactionStreak(
+  categoryId: number,
+  accountId?: number
+): Promise<CategoryStreak> {
+  return invoke<CategoryStreak>("get_transaction_streak", {
+    categoryId,
+    accountId: accountId ?? null,
+  });
+}
+
+export interface CategoryConsistency {
+  category_id: number;
+  category_name: string;
+  mean_monthly: number;
+  std_dev: number;
+  /** Coefficient of variation, std_dev / mean */
+  cv: number;
+  consistency_label: "very consistent" | "consistent" | "variable" | "unpredictable";
+}
+
+export async function getCategorySpendingConsistency(
+  accountId: number,
+  months: number
+): Promise<CategoryConsistency[]> {
+  return invoke<CategoryConsistency[]>("get_category_spending_consistency", { accountId, months });
+}
+
+export interface SpendingPercentile {
+  category_name: string;
+  month_spending: number;
+  historical_mean: number;
+  percentile: number;
+  label: "very low" | "below average" | "average" | "above average" | "very high";
+}
+
+/** Where a month's spending in a category falls among all months so far */
+export async function getCategorySpendingPercentile(
+  categoryId: number,
+  month: string,
+  accountId?: number
+): Promise<SpendingPercentile> {
+  return invoke<SpendingPercentile>("get_category_spending_percentile", {
+    categoryId,
+    month,
+    accountId: accountId ?? null,
+  });
+}
+
+export interface ForecastPoint {
+  month: string;
+  predicted: number;
+  confidence_interval: [number, number];
+}
+
+export interface SpendingForecast {
+  historical: MonthlyAmount[];
+  forecast: ForecastPoint[];
+}
+
+export async function getSpendingForecast(
+  categoryId: number,
+  forecastMonths: number,
+  historyMonths: number
+): Promise<SpendingForecast> {
+  return invoke<SpendingForecast>("get_spending_forecast", {
+    categoryId,
+    forecastMonths,
+    historyMonths,
+  });
+}
+
+export interface BudgetEfficiency {
+  budget: Budget;
+  /** Average spent / allocated, in percent */
+  avg_utilization: number;
+  variance: number;
+  suggestion: string;
+}
+
+export async function getCategoryBudgetEfficiency(months: number): Promise<BudgetEfficiency[]> {
+  return invoke<BudgetEfficiency[]>("get_category_budget_efficiency", { months });
+}
+
+export interface CategoryCutSuggestion {
+  category_name: string;
+  current_monthly: number;
+  suggested_reduction_pct: number;
+  estimated_savings: number;
+}
+
+export interface SavingsPlan {
+  goal: SavingsGoal;
+  required_monthly_savings: number;
+  current_monthly_savings: number;
+  /** 0 when current savings already cover the requirement */
+  monthly_gap: number;
+  suggested_cuts: CategoryCutSuggestion[];
+}
+
+export async function getSavingsAccelerationPlan(goalId: number): Promise<SavingsPlan> {
+  return invoke<SavingsPlan>("get_savings_acceleration_plan", { goalId });
+}
+
+export interface SeasonalPattern {
+  /** 1-4 */
+  quarter: number;
+  avg_quarterly_spend: number;
+  years_of_data: number;
+  max_year_spend: number;
+  min_year_spend: number;
+}
+
+export async function getSpendingByTimeOfYear(
+  accountId?: number,
+  categoryId?: number
+): Promise<SeasonalPattern[]> {
+  return invoke<SeasonalPattern[]>("get_spending_by_time_of_year", {
+    accountId: accountId ?? null,
+    categoryId: categoryId ?? null,
+  });
+}
+
+export interface IncomeGrowth {
+  year: number;
+  total_income: number;
+  /** null for the first year */
+  growth_pct: number | null;
+  /** Current year, extrapolated from year-to-date income */
+  is_projected: boolean;
+}
+
+export async function getIncomeGrowthRate(accountId?: number): Promise<IncomeGrowth[]> {
+  return invoke<IncomeGrowth[]>("get_income_growth_rate", { accountId: accountId ?? null });
+}
+
+export interface DayOfMonthSpending {
+  day: number;
+  avg_spent: number;
+  sample_count: number;
+}
+
+export interface SpendingCadence {
+  by_day_of_month: DayOfMonthSpending[];
+  /** Ratio of days 1-15 to days 16-31 average daily spending; > 1 means front-loaded */
+  early_month_bias: number;
+}
+
+export async function getSpendingCadence(accountId: number, months: number): Promise<SpendingCadence> {
+  return invoke<SpendingCadence>("get_spending_cadence", { accountId, months });
+}
+
+export interface WeekendAnomaly {
+  category_name: string;
+  weekday_avg: number;
+  weekend_avg: number;
+  /** weekend_avg / weekday_avg, always above 2 */
+  ratio: number;
+}
+
+export async function getUnusualWeekendSpending(accountId: number, months: number): Promise<WeekendAnomaly[]> {
+  return invoke<WeekendAnomaly[]>("get_unusual_weekend_spending", { accountId, months });
+}
+
+export interface AccountTypeSpending {
+  account_type: string;
+  total_spent: number;
+  total_income: number;
+  account_count: number;
+}
+
+export async function getSpendingByAccountType(
+  startDate: string,
+  endDate: string
+): Promise<AccountTypeSpending[]> {
+  return invoke<AccountTypeSpending[]>("get_spending_by_account_type", { startDate, endDate });
+}
+
+export interface CalendarEvent {
+  date: string;
+  event_type: "charge" | "income";
+  name: string;
+  amount: number;
+  source_id: number;
+  source_type: "subscription" | "income_stream";
+}
+
+export async function getRecurringPaymentCalendar(
+  accountId: number,
+  startDate: string,
+  endDate: string
+): Promise<CalendarEvent[]> {
+  return invoke<CalendarEvent[]>("get_recurring_payment_calendar", {
+    accountId,
+    startDate,
+    endDate,
+  });
+}
+
+export interface AccountStatement {
+  account: Account;
+  period_start: string;
+  period_end: string;
+  /** Balance at the end of the day before period_start */
+  opening_balance: number;
+  closing_balance: number;
+  total_credits: number;
+  /** Positive sum of outflows */
+  total_debits: number;
+  transaction_count: number;
+  category_breakdown: SpendingByCategory;
+  largest_expense: TransactionWithCategory | null;
+  transactions: TransactionWithCategory[];
+}
+
+export async function getBankStatementSummary(
+  accountId: number,
+  startDate: string,
+  endDate: string
+): Promise<AccountStatement> {
+  return invoke<AccountStatement>("get_bank_statement_summary", {
+    accountId,
+    startDate,
+    endDate,
+  });
+}
+
+export interface TransferReconciliation {
+  debit: TransactionWithCategory;
+  credit: TransactionWithCategory;
+  /** False when amounts differ, both legs are on one account, or they are over 3 business days apart */
+  is_balanced: boolean;
+  date_difference_days: number;
+}
+
+export async function getTransferReconciliationReport(): Promise<TransferReconciliation[]> {
+  return invoke<TransferReconciliation[]>("get_transfer_reconciliation_report");
+}
+
+export type DateRangePreset =
+  | "this_month"
+  | "last_month"
+  | "last_3_months"
+  | "last_12_months"
+  | "ytd"
+  | "this_year";
+
+/** Returns [startDate, endDate] as inclusive ISO dates */
+export async function dateRange(preset: DateRangePreset): Promise<[string, string]> {
+  return invoke<[string, string]>("date_range", { preset });
+}
+
+// ===== Settings API =====
+
+/** Returns the stored value, or the built-in default if the setting has never been set */
+export async function getSetting<T = unknown>(key: string): Promise<T | null> {
+  return invoke<T | null>("get_setting", { key });
+}
+
+export async function setSetting(key: string, value: unknown): Promise<void> {
+  return invoke<void>("set_setting", { key, value });
+}
@@ -60,6 +60,7 @@ export function AccountsPage() {
         name: newAccountName.trim(),
         account_number: newAccountNumber.trim() || null,
         currency: "DKK",
+        account_type: "checking",
       });
       toast.success("Account created");
       setCreateDialogOpen(false);
@@ -93,6 +93,7 @@ export function BudgetsPage() {
       switch(s.frequency) {
         case "weekly": return acc + (s.expected_amount * 4.33);
         case "biweekly": return acc + (s.expected_amount * 2.17);
+        case "quarterly": return acc + (s.expected_amount / 3);
         case "yearly": return acc + (s.expected_amount / 12);
         default: return acc + s.expected_amount;
       }
@@ -11,7 +11,7 @@ import {
 } from "@/components/ui/alert-dialog";
 import { Button } from "@/components/ui/button";
 import { Card } from "@/components/ui/card";
-import { deleteCategory, getCategories, type Category } from "@/lib/api";
+import { deleteCategory, getCategories, restoreCategory, type Category } from "@/lib/api";
 import { cn } from "@/lib/utils";
 import { ChevronDown, ChevronRight, Plus, Tags, Trash2 } from "lucide-react";
 import { useCallback, useEffect, useState } from "react";
@@ -141,7 +141,7 @@ export function CategoriesPage() {
 
   const loadCategories = useCallback(async () => {
     try {
-      const data = await getCategories();
+      const data = await getCategories(true);
       setCategories(data);
     } catch (error) {
       console.error("Failed to load categories:", error);
@@ -173,8 +173,21 @@ export function CategoriesPage() {
     if (categoryToDelete === null) return;
 
     try {
-      await deleteCategory(categoryToDelete);
-      toast.success("Category deleted");
+      const snapshot = await deleteCategory(categoryToDelete);
+      toast.success("Category deleted", {
+        action: {
+          label: "Undo",
+          onClick: async () => {
+            try {
+              await restoreCategory(snapshot);
+              loadCategories();
+            } catch (error) {
+              console.error("Failed to restore category:", error);
+              toast.error("Failed to restore category");
+            }
+          },
+        },
+      });
       loadCategories();
     } catch (error) {
       console.error("Failed to delete category:", error);
@@ -7,6 +7,7 @@ import {
   dismissSubscription,
   getAccounts,
   getSubscriptions,
+  markSubscriptionDetectionReviewed,
   saveSubscription,
   type Account,
   type Subscription,
@@ -94,7 +95,7 @@ export function SubscriptionsPage() {
     try {
       await saveSubscription(sub);
       toast.success("Subscription saved");
-      setDetected(detected.filter((d) => d.payee_pattern !== sub.payee_pattern));
+      removeDetected(sub);
       loadSubscriptions();
     } catch (error) {
       console.error("Failed to save subscription:", error);
@@ -104,7 +105,18 @@ export function SubscriptionsPage() {
 
   // Dismiss (ignore) a detected subscription
   const handleIgnore = (sub: Subscription) => {
-    setDetected(detected.filter((d) => d.payee_pattern !== sub.payee_pattern));
+    removeDetected(sub);
+  };
+
+  // Once every candidate is saved or ignored, the next detection only looks at newer transactions
+  const removeDetected = (sub: Subscription) => {
+    const remaining = detected.filter((d) => d.payee_pattern !== sub.payee_pattern);
+    setDetected(remaining);
+    if (remaining.length === 0 && selectedAccountId) {
+      markSubscriptionDetectionReviewed(selectedAccountId).catch((error) =>
+        console.error("Failed to record subscription review:", error)
+      );
+    }
   };
 
   // Dismiss a saved subscription